@@ -0,0 +1,780 @@
+//! [`Sender`] and [`Receiver`] are the two halves of a closable multi-producer multi-consumer
+//! channel built on [`Queue`].
+
+use super::wait_queue::AsyncWait;
+use super::Queue;
+
+use std::cell::UnsafeCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// `CLOSED` is set in the state of a closed channel.
+const CLOSED: usize = 1_usize << (usize::BITS - 1);
+
+/// Creates an unbounded channel.
+///
+/// # Examples
+///
+/// ```
+/// use scc::channel;
+///
+/// let (sender, receiver) = channel::unbounded();
+///
+/// assert!(sender.send(1).is_ok());
+/// assert_eq!(receiver.try_recv().ok().flatten(), Some(1));
+/// ```
+#[inline]
+#[must_use]
+pub fn unbounded<T: 'static>() -> (Sender<T>, Receiver<T>) {
+    new_channel(Queue::default())
+}
+
+/// Creates a bounded channel that holds at most `capacity` entries.
+///
+/// # Panics
+///
+/// Panics if `capacity` is `0`.
+///
+/// # Examples
+///
+/// ```
+/// use scc::channel::{self, SendError};
+///
+/// let (sender, receiver) = channel::bounded(1);
+///
+/// assert!(sender.try_send(1).is_ok());
+/// assert_eq!(sender.try_send(2), Err(SendError::Full(2)));
+/// ```
+#[inline]
+#[must_use]
+pub fn bounded<T: 'static>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    new_channel(Queue::with_capacity(capacity))
+}
+
+/// [`Sender`] pushes entries into the channel.
+///
+/// The channel is closed when [`Sender::close`] or [`Receiver::close`] is called, or when all
+/// the [`Sender`] or all the [`Receiver`] instances are dropped.
+pub struct Sender<T: 'static> {
+    channel: Arc<Channel<T>>,
+
+    /// `async_wait` is the pending wait of [`Sender::poll_ready`].
+    async_wait: Option<Box<AsyncWait>>,
+
+    /// `reserved` is `true` if [`Sender::poll_ready`] has reserved room for an entry.
+    reserved: bool,
+}
+
+/// [`Receiver`] pops entries from the channel.
+///
+/// Entries that were sent before the channel was closed can still be received; once they are
+/// all received, [`Receiver`] returns [`Disconnected`].
+pub struct Receiver<T: 'static> {
+    channel: Arc<Channel<T>>,
+
+    /// `async_wait` is the pending wait of [`Receiver::poll_recv`].
+    async_wait: Option<Box<AsyncWait>>,
+}
+
+/// [`SendError`] is the error type of [`Sender`].
+#[derive(Debug, Eq, PartialEq)]
+pub enum SendError<T> {
+    /// The channel is full.
+    Full(T),
+
+    /// The channel is closed.
+    Disconnected(T),
+}
+
+/// [`Disconnected`] is returned by [`Receiver`] when the channel is closed and empty, and by
+/// [`Sender::poll_ready`] when the channel is closed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Disconnected;
+
+impl<T: 'static> Sender<T> {
+    /// Sends an instance of `T`, waiting for an entry to be received if the channel is full.
+    ///
+    /// The calling thread is blocked until there is room for the new entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError::Disconnected`] along with the supplied instance if the channel is
+    /// closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel::{self, SendError};
+    ///
+    /// let (sender, receiver) = channel::unbounded();
+    ///
+    /// assert!(sender.send(1).is_ok());
+    /// receiver.close();
+    /// assert_eq!(sender.send(2), Err(SendError::Disconnected(2)));
+    /// ```
+    #[inline]
+    pub fn send(&self, mut val: T) -> Result<(), SendError<T>> {
+        loop {
+            match self.try_send(val) {
+                Err(SendError::Full(returned)) => val = returned,
+                result => return result,
+            }
+            let _ = self
+                .channel
+                .queue
+                .push_waiters()
+                .wait_sync(|| self.channel.check_sendable());
+        }
+    }
+
+    /// Sends an instance of `T`, waiting for an entry to be received if the channel is full.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError::Disconnected`] along with the supplied instance if the channel is
+    /// closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel;
+    ///
+    /// let (sender, receiver) = channel::bounded(1);
+    ///
+    /// assert!(sender.try_send(1).is_ok());
+    /// let future_send = sender.send_async(2);
+    /// ```
+    #[inline]
+    pub async fn send_async(&self, mut val: T) -> Result<(), SendError<T>> {
+        loop {
+            match self.try_send(val) {
+                Err(SendError::Full(returned)) => val = returned,
+                result => return result,
+            }
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if self
+                .channel
+                .queue
+                .push_waiters()
+                .push_async_entry(async_wait_pinned.mut_ptr(), || {
+                    self.channel.check_sendable()
+                })
+                .is_ok()
+            {
+                continue;
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Sends an instance of `T` if the channel is not full.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied instance if the channel is full or closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel::{self, SendError};
+    ///
+    /// let (sender, receiver) = channel::bounded(1);
+    ///
+    /// assert!(sender.try_send(1).is_ok());
+    /// assert_eq!(sender.try_send(2), Err(SendError::Full(2)));
+    ///
+    /// sender.close();
+    /// assert_eq!(sender.try_send(3), Err(SendError::Disconnected(3)));
+    /// ```
+    #[inline]
+    pub fn try_send(&self, val: T) -> Result<(), SendError<T>> {
+        if !self.channel.begin_send() {
+            return Err(SendError::Disconnected(val));
+        }
+        let result = self.channel.queue.try_push(Message::new(val));
+        self.channel.end_send();
+        match result {
+            Ok(_) => Ok(()),
+            Err(message) => Err(SendError::Full(message.into_inner())),
+        }
+    }
+
+    /// Polls the channel for room for a new entry.
+    ///
+    /// The room is reserved for the next [`Sender::start_send`] call once it returns
+    /// `Poll::Ready(Ok(()))`, and the current task is woken up when an entry is received or the
+    /// channel is closed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Disconnected`] if the channel is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel;
+    /// use std::task::{Context, Poll};
+    ///
+    /// let (mut sender, receiver) = channel::bounded(1);
+    ///
+    /// let waker = futures::task::noop_waker();
+    /// let mut cx = Context::from_waker(&waker);
+    /// assert!(sender.poll_ready(&mut cx).is_ready());
+    /// assert!(sender.start_send(1).is_ok());
+    /// assert!(sender.poll_ready(&mut cx).is_pending());
+    ///
+    /// assert_eq!(receiver.try_recv().ok().flatten(), Some(1));
+    /// assert!(sender.poll_ready(&mut cx).is_ready());
+    /// ```
+    #[inline]
+    pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Disconnected>> {
+        loop {
+            if let Some(async_wait) = self.async_wait.as_mut() {
+                if Pin::new(&mut **async_wait).poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                self.async_wait.take();
+            }
+            if self.channel.is_closed() {
+                self.release_reserved();
+                return Poll::Ready(Err(Disconnected));
+            }
+            if self.reserved || self.channel.queue.reserve_entry() {
+                self.reserved = true;
+                return Poll::Ready(Ok(()));
+            }
+
+            // `async_wait` is boxed so that it stays in place while the `Sender` is moved.
+            let mut async_wait = Box::new(AsyncWait::default());
+            if self
+                .channel
+                .queue
+                .push_waiters()
+                .push_async_entry(async_wait.mut_ptr(), || self.channel.check_sendable())
+                .is_err()
+            {
+                self.async_wait.replace(async_wait);
+            }
+        }
+    }
+
+    /// Sends an instance of `T` into the room reserved by [`Sender::poll_ready`].
+    ///
+    /// It is equivalent to [`Sender::try_send`] if no room has been reserved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied instance if the channel is closed, or if no room
+    /// has been reserved and the channel is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel::{self, SendError};
+    ///
+    /// let (mut sender, receiver) = channel::bounded(1);
+    ///
+    /// assert!(sender.start_send(1).is_ok());
+    /// assert_eq!(sender.start_send(2), Err(SendError::Full(2)));
+    /// ```
+    #[inline]
+    pub fn start_send(&mut self, val: T) -> Result<(), SendError<T>> {
+        if !self.reserved {
+            return self.try_send(val);
+        }
+        self.reserved = false;
+        if !self.channel.begin_send() {
+            self.channel.queue.release_entry();
+            return Err(SendError::Disconnected(val));
+        }
+        self.channel.queue.push_reserved(Message::new(val));
+        self.channel.end_send();
+        Ok(())
+    }
+
+    /// Closes the channel.
+    ///
+    /// Waiting threads and tasks are woken up, and entries that were sent before the channel was
+    /// closed can still be received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel;
+    ///
+    /// let (sender, receiver) = channel::unbounded::<usize>();
+    ///
+    /// sender.close();
+    /// assert!(receiver.is_closed());
+    /// ```
+    #[inline]
+    pub fn close(&self) {
+        self.channel.close();
+    }
+
+    /// Returns `true` if the channel is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel;
+    ///
+    /// let (sender, receiver) = channel::unbounded::<usize>();
+    /// assert!(!sender.is_closed());
+    ///
+    /// drop(receiver);
+    /// assert!(sender.is_closed());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.channel.is_closed()
+    }
+}
+
+impl<T: 'static> Sender<T> {
+    /// Gives back the room reserved by [`Sender::poll_ready`].
+    fn release_reserved(&mut self) {
+        if self.reserved {
+            self.reserved = false;
+            self.channel.queue.release_entry();
+        }
+    }
+}
+
+impl<T: 'static> Clone for Sender<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        self.channel.num_senders.fetch_add(1, Relaxed);
+        Self {
+            channel: self.channel.clone(),
+            async_wait: None,
+            reserved: false,
+        }
+    }
+}
+
+impl<T: 'static> Drop for Sender<T> {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(async_wait) = self.async_wait.take() {
+            AsyncWait::deregister(async_wait);
+        }
+        self.release_reserved();
+        if self.channel.num_senders.fetch_sub(1, AcqRel) == 1 {
+            self.channel.close();
+        }
+    }
+}
+
+impl<T: 'static> Receiver<T> {
+    /// Receives the oldest entry, waiting for an entry to be sent if the channel is empty.
+    ///
+    /// The calling thread is blocked until an entry becomes available.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Disconnected`] if the channel is closed and empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel::{self, Disconnected};
+    /// use std::thread;
+    ///
+    /// let (sender, receiver) = channel::unbounded();
+    ///
+    /// let thread = thread::spawn(move || {
+    ///     assert!(sender.send(1).is_ok());
+    /// });
+    ///
+    /// assert_eq!(receiver.recv(), Ok(1));
+    /// assert_eq!(receiver.recv(), Err(Disconnected));
+    ///
+    /// thread.join().unwrap();
+    /// ```
+    #[inline]
+    pub fn recv(&self) -> Result<T, Disconnected> {
+        loop {
+            if let Some(entry) = self.try_recv()? {
+                return Ok(entry);
+            }
+            let _ = self
+                .channel
+                .queue
+                .pop_waiters()
+                .wait_sync(|| self.channel.check_receivable());
+        }
+    }
+
+    /// Receives the oldest entry, waiting for an entry to be sent if the channel is empty.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Disconnected`] if the channel is closed and empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel;
+    ///
+    /// let (sender, receiver) = channel::unbounded::<usize>();
+    ///
+    /// let future_recv = receiver.recv_async();
+    /// ```
+    #[inline]
+    pub async fn recv_async(&self) -> Result<T, Disconnected> {
+        loop {
+            if let Some(entry) = self.try_recv()? {
+                return Ok(entry);
+            }
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if self
+                .channel
+                .queue
+                .pop_waiters()
+                .push_async_entry(async_wait_pinned.mut_ptr(), || {
+                    self.channel.check_receivable()
+                })
+                .is_ok()
+            {
+                continue;
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Receives the oldest entry if the channel is not empty.
+    ///
+    /// Returns `None` if the channel is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Disconnected`] if the channel is closed and empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel::{self, Disconnected};
+    ///
+    /// let (sender, receiver) = channel::unbounded();
+    ///
+    /// assert!(receiver.try_recv().unwrap().is_none());
+    ///
+    /// assert!(sender.send(1).is_ok());
+    /// drop(sender);
+    ///
+    /// assert_eq!(receiver.try_recv().unwrap(), Some(1));
+    /// assert!(receiver.try_recv().is_err());
+    /// ```
+    #[inline]
+    pub fn try_recv(&self) -> Result<Option<T>, Disconnected> {
+        if let Some(entry) = self.channel.queue.pop() {
+            return Ok(Some(unsafe { entry.take() }));
+        }
+        if self.channel.is_drained() {
+            // Entries that were sent right before the channel was closed have to be received.
+            return self
+                .channel
+                .queue
+                .pop()
+                .map_or(Err(Disconnected), |entry| Ok(Some(unsafe { entry.take() })));
+        }
+        Ok(None)
+    }
+
+    /// Polls the channel for the oldest entry.
+    ///
+    /// The current task is woken up when an entry is sent or the channel is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel;
+    /// use std::task::{Context, Poll};
+    ///
+    /// let (sender, mut receiver) = channel::unbounded::<usize>();
+    /// drop(sender);
+    ///
+    /// let waker = futures::task::noop_waker();
+    /// let mut cx = Context::from_waker(&waker);
+    /// assert!(matches!(receiver.poll_recv(&mut cx), Poll::Ready(Err(_))));
+    /// ```
+    #[inline]
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, Disconnected>> {
+        loop {
+            if let Some(async_wait) = self.async_wait.as_mut() {
+                if Pin::new(&mut **async_wait).poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                self.async_wait.take();
+            }
+            match self.try_recv() {
+                Ok(Some(entry)) => return Poll::Ready(Ok(entry)),
+                Ok(None) => (),
+                Err(error) => return Poll::Ready(Err(error)),
+            }
+
+            // `async_wait` is boxed so that it stays in place while the `Receiver` is moved.
+            let mut async_wait = Box::new(AsyncWait::default());
+            if self
+                .channel
+                .queue
+                .pop_waiters()
+                .push_async_entry(async_wait.mut_ptr(), || self.channel.check_receivable())
+                .is_err()
+            {
+                self.async_wait.replace(async_wait);
+            }
+        }
+    }
+
+    /// Closes the channel.
+    ///
+    /// Waiting threads and tasks are woken up, and entries that were sent before the channel was
+    /// closed can still be received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel::{self, SendError};
+    ///
+    /// let (sender, receiver) = channel::unbounded();
+    ///
+    /// receiver.close();
+    /// assert_eq!(sender.send(1), Err(SendError::Disconnected(1)));
+    /// ```
+    #[inline]
+    pub fn close(&self) {
+        self.channel.close();
+    }
+
+    /// Returns `true` if the channel is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel;
+    ///
+    /// let (sender, receiver) = channel::unbounded::<usize>();
+    /// assert!(!receiver.is_closed());
+    ///
+    /// drop(sender);
+    /// assert!(receiver.is_closed());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.channel.is_closed()
+    }
+}
+
+impl<T: 'static> Clone for Receiver<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        self.channel.num_receivers.fetch_add(1, Relaxed);
+        Self {
+            channel: self.channel.clone(),
+            async_wait: None,
+        }
+    }
+}
+
+impl<T: 'static> Drop for Receiver<T> {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(async_wait) = self.async_wait.take() {
+            AsyncWait::deregister(async_wait);
+        }
+        if self.channel.num_receivers.fetch_sub(1, AcqRel) == 1 {
+            self.channel.close();
+        }
+    }
+}
+
+#[cfg(feature = "futures-core")]
+impl<T: 'static> futures_core::Stream for Receiver<T> {
+    type Item = T;
+
+    #[inline]
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx).map(Result::ok)
+    }
+}
+
+#[cfg(feature = "futures-sink")]
+impl<T: 'static> futures_sink::Sink<T> for Sender<T> {
+    type Error = Disconnected;
+
+    #[inline]
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().poll_ready(cx)
+    }
+
+    /// Sends an instance of `T` into the room reserved by [`Sender::poll_ready`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if no room has been reserved by [`Sender::poll_ready`].
+    #[inline]
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        let sender = self.get_mut();
+        assert!(sender.reserved, "start_send was called before poll_ready");
+        sender.start_send(item).map_err(|_| Disconnected)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // The channel is closed when all the `Sender` instances are dropped.
+        self.get_mut().release_reserved();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T> SendError<T> {
+    /// Returns the instance that could not be sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::channel::SendError;
+    ///
+    /// assert_eq!(SendError::Full(1).into_inner(), 1);
+    /// ```
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(val) | Self::Disconnected(val) => val,
+        }
+    }
+}
+
+/// [`Message`] holds a sent instance of `T` until the [`Receiver`] that popped it takes it.
+struct Message<T>(UnsafeCell<Option<T>>);
+
+impl<T> Message<T> {
+    /// Creates a new [`Message`].
+    fn new(val: T) -> Message<T> {
+        Message(UnsafeCell::new(Some(val)))
+    }
+
+    /// Extracts the instance from a [`Message`] that was never pushed.
+    fn into_inner(self) -> T {
+        self.0.into_inner().unwrap()
+    }
+
+    /// Takes the instance out of the [`Message`].
+    ///
+    /// The caller must be the only [`Receiver`] that popped the [`Message`].
+    unsafe fn take(&self) -> T {
+        (*self.0.get()).take().unwrap()
+    }
+}
+
+// The instance is only accessed by the `Sender` before the `Message` is pushed, and by the
+// `Receiver` that popped it afterwards.
+unsafe impl<T: Send> Sync for Message<T> {}
+
+/// [`Channel`] is the state shared by [`Sender`] and [`Receiver`].
+struct Channel<T: 'static> {
+    queue: Queue<Message<T>>,
+
+    /// `state` is the number of ongoing send operations and the `CLOSED` flag.
+    state: AtomicUsize,
+
+    num_senders: AtomicUsize,
+    num_receivers: AtomicUsize,
+}
+
+impl<T: 'static> Channel<T> {
+    /// Starts a send operation if the channel is not closed.
+    fn begin_send(&self) -> bool {
+        self.state
+            .fetch_update(Acquire, Relaxed, |state| {
+                if state & CLOSED == 0 {
+                    Some(state + 1)
+                } else {
+                    None
+                }
+            })
+            .is_ok()
+    }
+
+    /// Finishes a send operation.
+    fn end_send(&self) {
+        if self.state.fetch_sub(1, AcqRel) == CLOSED + 1 {
+            // Receivers waiting for the last send operation to finish have to be woken up.
+            self.queue.pop_waiters().signal();
+        }
+    }
+
+    /// Closes the channel.
+    fn close(&self) {
+        if self.state.fetch_or(CLOSED, AcqRel) & CLOSED == 0 {
+            self.queue.pop_waiters().signal();
+            self.queue.push_waiters().signal();
+        }
+    }
+
+    /// Returns `true` if the channel is closed.
+    fn is_closed(&self) -> bool {
+        self.state.load(Relaxed) & CLOSED != 0
+    }
+
+    /// Returns `true` if the channel is closed and no send operations are in progress.
+    fn is_drained(&self) -> bool {
+        self.state.load(Acquire) == CLOSED
+    }
+
+    /// Returns `Ok` if an entry can be received or the channel is drained.
+    fn check_receivable(&self) -> Result<(), ()> {
+        if self.is_drained() {
+            return Ok(());
+        }
+        self.queue.check_non_empty()
+    }
+
+    /// Returns `Ok` if an entry can be sent or the channel is closed.
+    fn check_sendable(&self) -> Result<(), ()> {
+        if self.is_closed() {
+            return Ok(());
+        }
+        self.queue.check_not_full()
+    }
+}
+
+/// Creates a channel over the supplied [`Queue`].
+fn new_channel<T: 'static>(queue: Queue<Message<T>>) -> (Sender<T>, Receiver<T>) {
+    let channel = Arc::new(Channel {
+        queue,
+        state: AtomicUsize::new(0),
+        num_senders: AtomicUsize::new(1),
+        num_receivers: AtomicUsize::new(1),
+    });
+    (
+        Sender {
+            channel: channel.clone(),
+            async_wait: None,
+            reserved: false,
+        },
+        Receiver {
+            channel,
+            async_wait: None,
+        },
+    )
+}
@@ -0,0 +1,640 @@
+//! [`Deque`] is a lock-free concurrent double-ended queue.
+
+use super::ebr::{Arc, AtomicArc, Barrier, Ptr, Tag};
+
+use std::fmt::{Debug, Display};
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
+
+/// [`Deque`] is a lock-free concurrent double-ended queue.
+///
+/// [`Deque`] implements the lock-free deque algorithm proposed by Maged M. Michael: the front
+/// and back entries are atomically updated through a single snapshot, and a push operation
+/// leaves the snapshot unstable until the new entry is linked to its neighbor.
+#[derive(Debug)]
+pub struct Deque<T: 'static> {
+    /// `anchor` points to the snapshot of both ends of the [`Deque`].
+    anchor: AtomicArc<Anchor<T>>,
+}
+
+impl<T: 'static> Deque<T> {
+    /// Pushes a new instance of `T` at the front.
+    ///
+    /// Returns an [`Arc`] holding a strong reference to the newly pushed entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    ///
+    /// deque.push_front(11);
+    /// assert_eq!(**deque.push_front(17), 17);
+    ///
+    /// assert_eq!(deque.peek_front(|v| **v), Some(17));
+    /// ```
+    #[inline]
+    pub fn push_front(&self, val: T) -> Arc<Entry<T>> {
+        self.push(End::Front, val)
+    }
+
+    /// Pushes a new instance of `T` at the back.
+    ///
+    /// Returns an [`Arc`] holding a strong reference to the newly pushed entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    ///
+    /// deque.push_back(11);
+    /// assert_eq!(**deque.push_back(17), 17);
+    ///
+    /// assert_eq!(deque.peek_back(|v| **v), Some(17));
+    /// ```
+    #[inline]
+    pub fn push_back(&self, val: T) -> Arc<Entry<T>> {
+        self.push(End::Back, val)
+    }
+
+    /// Pops the front entry.
+    ///
+    /// Returns `None` if the [`Deque`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    ///
+    /// deque.push_back(37);
+    /// deque.push_back(3);
+    ///
+    /// assert_eq!(deque.pop_front().map(|e| **e), Some(37));
+    /// assert_eq!(deque.pop_front().map(|e| **e), Some(3));
+    /// assert!(deque.pop_front().is_none());
+    /// ```
+    #[inline]
+    pub fn pop_front(&self) -> Option<Arc<Entry<T>>> {
+        match self.pop_if(End::Front, |_| true) {
+            Ok(result) => result,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Pops the back entry.
+    ///
+    /// Returns `None` if the [`Deque`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    ///
+    /// deque.push_back(37);
+    /// deque.push_back(3);
+    ///
+    /// assert_eq!(deque.pop_back().map(|e| **e), Some(3));
+    /// assert_eq!(deque.pop_back().map(|e| **e), Some(37));
+    /// assert!(deque.pop_back().is_none());
+    /// ```
+    #[inline]
+    pub fn pop_back(&self) -> Option<Arc<Entry<T>>> {
+        match self.pop_if(End::Back, |_| true) {
+            Ok(result) => result,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Pops the front entry if the front entry satisfies the given condition.
+    ///
+    /// Returns `None` if the [`Deque`] is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the front entry if the given condition is not met.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    ///
+    /// deque.push_back(3);
+    /// deque.push_back(1);
+    ///
+    /// assert!(deque.pop_front_if(|v| **v == 1).is_err());
+    /// assert_eq!(deque.pop_front_if(|v| **v == 3).ok().and_then(|e| e).map(|e| **e), Some(3));
+    /// ```
+    #[inline]
+    pub fn pop_front_if<F: FnMut(&Entry<T>) -> bool>(
+        &self,
+        cond: F,
+    ) -> Result<Option<Arc<Entry<T>>>, Arc<Entry<T>>> {
+        self.pop_if(End::Front, cond)
+    }
+
+    /// Pops the back entry if the back entry satisfies the given condition.
+    ///
+    /// Returns `None` if the [`Deque`] is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the back entry if the given condition is not met.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    ///
+    /// deque.push_back(3);
+    /// deque.push_back(1);
+    ///
+    /// assert!(deque.pop_back_if(|v| **v == 3).is_err());
+    /// assert_eq!(deque.pop_back_if(|v| **v == 1).ok().and_then(|e| e).map(|e| **e), Some(1));
+    /// ```
+    #[inline]
+    pub fn pop_back_if<F: FnMut(&Entry<T>) -> bool>(
+        &self,
+        cond: F,
+    ) -> Result<Option<Arc<Entry<T>>>, Arc<Entry<T>>> {
+        self.pop_if(End::Back, cond)
+    }
+
+    /// Peeks the front entry.
+    ///
+    /// Returns `None` if the [`Deque`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    ///
+    /// assert!(deque.peek_front(|v| **v).is_none());
+    ///
+    /// deque.push_back(37);
+    /// deque.push_back(3);
+    ///
+    /// assert_eq!(deque.peek_front(|v| **v), Some(37));
+    /// ```
+    #[inline]
+    pub fn peek_front<R, F: FnOnce(&Entry<T>) -> R>(&self, reader: F) -> Option<R> {
+        self.peek(End::Front, reader)
+    }
+
+    /// Peeks the back entry.
+    ///
+    /// Returns `None` if the [`Deque`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    ///
+    /// assert!(deque.peek_back(|v| **v).is_none());
+    ///
+    /// deque.push_back(37);
+    /// deque.push_back(3);
+    ///
+    /// assert_eq!(deque.peek_back(|v| **v), Some(3));
+    /// ```
+    #[inline]
+    pub fn peek_back<R, F: FnOnce(&Entry<T>) -> R>(&self, reader: F) -> Option<R> {
+        self.peek(End::Back, reader)
+    }
+
+    /// Returns `true` if the [`Deque`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    /// assert!(deque.is_empty());
+    ///
+    /// deque.push_front(7);
+    /// assert!(!deque.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        let barrier = Barrier::new();
+        match self.anchor.load(Acquire, &barrier).as_ref() {
+            Some(anchor) => anchor.front.is_none(),
+            None => true,
+        }
+    }
+
+    /// Pushes a new entry at the specified end.
+    fn push(&self, end: End, val: T) -> Arc<Entry<T>> {
+        let barrier = Barrier::new();
+        let new_entry = Arc::new(Entry::new(val));
+        loop {
+            let anchor_ptr = self.anchor.load(Acquire, &barrier);
+            let new_anchor = if let Some((anchor, end_entry)) = Anchor::end_of(anchor_ptr, end) {
+                if anchor_ptr.tag() != Tag::None {
+                    self.stabilize(anchor_ptr, &barrier);
+                    continue;
+                }
+                // The new entry is not reachable by other threads until it is pushed.
+                new_entry
+                    .link(end.opposite())
+                    .swap((Some(end_entry.clone()), Tag::None), Relaxed);
+                (
+                    Some(Arc::new(anchor.with_end(end, new_entry.clone()))),
+                    end.tag(),
+                )
+            } else {
+                (
+                    Some(Arc::new(Anchor::with_entry(new_entry.clone()))),
+                    Tag::None,
+                )
+            };
+            if let Ok((_, new_anchor_ptr)) = self
+                .anchor
+                .compare_exchange(anchor_ptr, new_anchor, AcqRel, Acquire, &barrier)
+            {
+                self.stabilize(new_anchor_ptr, &barrier);
+                return new_entry;
+            }
+        }
+    }
+
+    /// Pops the entry at the specified end if the entry satisfies the given condition.
+    ///
+    /// Logically removed entries at the end are popped regardless of the condition.
+    fn pop_if<F: FnMut(&Entry<T>) -> bool>(
+        &self,
+        end: End,
+        mut cond: F,
+    ) -> Result<Option<Arc<Entry<T>>>, Arc<Entry<T>>> {
+        let barrier = Barrier::new();
+        let mut anchor_ptr = self.anchor.load(Acquire, &barrier);
+        while let Some((anchor, end_entry)) = Anchor::end_of(anchor_ptr, end) {
+            if !end_entry.is_removed() && !cond(end_entry) {
+                return Err(end_entry.clone());
+            }
+            if anchor_ptr.tag() != Tag::None {
+                self.stabilize(anchor_ptr, &barrier);
+            } else if let Some(new_anchor) = anchor.without_end(end, &barrier) {
+                if self
+                    .anchor
+                    .compare_exchange(
+                        anchor_ptr,
+                        (Some(Arc::new(new_anchor)), Tag::None),
+                        AcqRel,
+                        Acquire,
+                        &barrier,
+                    )
+                    .is_ok()
+                {
+                    // The entry may have been removed in the meantime.
+                    let removed = end_entry.remove();
+                    end_entry.unlink();
+                    if removed {
+                        return Ok(Some(end_entry.clone()));
+                    }
+                }
+            }
+            anchor_ptr = self.anchor.load(Acquire, &barrier);
+        }
+        Ok(None)
+    }
+
+    /// Peeks the entry at the specified end.
+    fn peek<R, F: FnOnce(&Entry<T>) -> R>(&self, end: End, reader: F) -> Option<R> {
+        // Logically removed entries at the end are popped.
+        match self.pop_if(end, |_| false) {
+            Ok(_) => None,
+            Err(entry) => Some(reader(&*entry)),
+        }
+    }
+
+    /// Links the newly pushed entry to its neighbor, and then marks the anchor stable.
+    ///
+    /// The anchor is marked stable by clearing the tag of the pointer, therefore no new
+    /// [`Anchor`] is allocated.
+    fn stabilize(&self, anchor_ptr: Ptr<'_, Anchor<T>>, barrier: &Barrier) {
+        if let Some(end) = End::pushing(anchor_ptr.tag()) {
+            if let Some((_, end_entry)) = Anchor::end_of(anchor_ptr, end) {
+                let neighbor_ptr = end_entry.link(end.opposite()).load(Acquire, barrier);
+                if let Some(neighbor) = neighbor_ptr.as_ref() {
+                    if self.anchor.load(Acquire, barrier) != anchor_ptr {
+                        return;
+                    }
+                    let link = neighbor.link(end);
+                    let link_ptr = link.load(Acquire, barrier);
+                    if link_ptr != end_entry.ptr(barrier) {
+                        if self.anchor.load(Acquire, barrier) != anchor_ptr {
+                            return;
+                        }
+                        if link
+                            .compare_exchange(
+                                link_ptr,
+                                (Some(end_entry.clone()), Tag::None),
+                                AcqRel,
+                                Acquire,
+                                barrier,
+                            )
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+                }
+                let _result = self.anchor.compare_exchange(
+                    anchor_ptr,
+                    (anchor_ptr.get_arc(), Tag::None),
+                    AcqRel,
+                    Acquire,
+                    barrier,
+                );
+            }
+        }
+    }
+}
+
+impl<T: 'static> Default for Deque<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            anchor: AtomicArc::default(),
+        }
+    }
+}
+
+impl<T: 'static> Drop for Deque<T> {
+    #[inline]
+    fn drop(&mut self) {
+        // Entries are linked to each other in both directions, therefore they have to be
+        // unlinked one by one.
+        while self.pop_back().is_some() {}
+    }
+}
+
+/// [`Entry`] stores an instance of `T` and links to its neighbors.
+pub struct Entry<T: 'static> {
+    /// `instance` is the instance of `T`.
+    instance: T,
+
+    /// `prev` points to the entry toward the front.
+    prev: AtomicArc<Self>,
+
+    /// `next` points to the entry toward the back.
+    next: AtomicArc<Self>,
+
+    /// `removed` is set when the entry is popped or removed.
+    removed: AtomicBool,
+}
+
+impl<T: 'static> Entry<T> {
+    /// Tries to remove the entry from its associated [`Deque`].
+    ///
+    /// The entry is only logically removed from the [`Deque`] and it will be popped from the
+    /// [`Deque`] on a subsequent pop or peek operation when the entry reaches either end of the
+    /// [`Deque`]. `false` is returned if the entry has already been removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    ///
+    /// deque.push_back(3);
+    /// let entry = deque.push_back(7);
+    /// deque.push_back(11);
+    ///
+    /// assert!(entry.remove());
+    /// assert!(!entry.remove());
+    ///
+    /// assert_eq!(deque.pop_back().map(|e| **e), Some(11));
+    /// assert_eq!(deque.pop_back().map(|e| **e), Some(3));
+    /// ```
+    #[inline]
+    pub fn remove(&self) -> bool {
+        self.removed
+            .compare_exchange(false, true, Release, Relaxed)
+            .is_ok()
+    }
+
+    /// Checks if the entry has been removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Deque;
+    ///
+    /// let deque: Deque<usize> = Deque::default();
+    ///
+    /// let entry = deque.push_front(7);
+    /// assert!(!entry.is_removed());
+    ///
+    /// assert_eq!(deque.pop_back().map(|e| **e), Some(7));
+    /// assert!(entry.is_removed());
+    /// ```
+    #[inline]
+    pub fn is_removed(&self) -> bool {
+        self.removed.load(Relaxed)
+    }
+
+    /// Creates a new [`Entry`].
+    fn new(val: T) -> Entry<T> {
+        Entry {
+            instance: val,
+            prev: AtomicArc::default(),
+            next: AtomicArc::default(),
+            removed: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the link to the neighbor at the specified end.
+    fn link(&self, end: End) -> &AtomicArc<Self> {
+        match end {
+            End::Front => &self.prev,
+            End::Back => &self.next,
+        }
+    }
+
+    /// Clears the links of a popped entry.
+    ///
+    /// Neighbors may still point to the entry, however the entry does not keep them alive.
+    fn unlink(&self) {
+        self.prev.swap((None, Tag::None), Relaxed);
+        self.next.swap((None, Tag::None), Relaxed);
+    }
+}
+
+impl<T: 'static> AsRef<T> for Entry<T> {
+    fn as_ref(&self) -> &T {
+        &self.instance
+    }
+}
+
+impl<T: 'static> AsMut<T> for Entry<T> {
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.instance
+    }
+}
+
+impl<T: 'static + Debug> Debug for Entry<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Entry")
+            .field("instance", &self.instance)
+            .field("removed", &self.removed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: 'static> Deref for Entry<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.instance
+    }
+}
+
+impl<T: 'static> DerefMut for Entry<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.instance
+    }
+}
+
+impl<T: 'static + Display> Display for Entry<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.instance)
+    }
+}
+
+impl<T: Eq + 'static> Eq for Entry<T> {}
+
+impl<T: PartialEq + 'static> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.instance == other.instance
+    }
+}
+
+/// [`End`] is either end of a [`Deque`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum End {
+    Front,
+    Back,
+}
+
+impl End {
+    /// Returns the other end.
+    fn opposite(self) -> End {
+        match self {
+            End::Front => End::Back,
+            End::Back => End::Front,
+        }
+    }
+
+    /// Returns the [`Tag`] of an [`Anchor`] pointer while a new entry is being pushed at the end.
+    fn tag(self) -> Tag {
+        match self {
+            End::Front => Tag::First,
+            End::Back => Tag::Second,
+        }
+    }
+
+    /// Returns the end at which a new entry is being pushed according to the [`Tag`].
+    fn pushing(tag: Tag) -> Option<End> {
+        match tag {
+            Tag::First => Some(End::Front),
+            Tag::Second => Some(End::Back),
+            Tag::None | Tag::Both => None,
+        }
+    }
+}
+
+/// [`Anchor`] is an immutable snapshot of both ends of a [`Deque`].
+///
+/// The tag of the pointer to an [`Anchor`] is set if the entry at the end is not yet linked to
+/// its neighbor; [`Tag::First`] stands for the front, and [`Tag::Second`] for the back.
+#[derive(Debug)]
+struct Anchor<T: 'static> {
+    front: Option<Arc<Entry<T>>>,
+    back: Option<Arc<Entry<T>>>,
+}
+
+impl<T: 'static> Anchor<T> {
+    /// Creates a new [`Anchor`] of a single entry.
+    fn with_entry(entry: Arc<Entry<T>>) -> Anchor<T> {
+        Anchor {
+            front: Some(entry.clone()),
+            back: Some(entry),
+        }
+    }
+
+    /// Returns the [`Anchor`] and the entry at the specified end if the [`Anchor`] is not empty.
+    fn end_of(
+        anchor_ptr: Ptr<'_, Anchor<T>>,
+        end: End,
+    ) -> Option<(&'_ Anchor<T>, &'_ Arc<Entry<T>>)> {
+        anchor_ptr
+            .as_ref()
+            .and_then(|anchor| anchor.end(end).map(|entry| (anchor, entry)))
+    }
+
+    /// Returns the entry at the specified end.
+    fn end(&self, end: End) -> Option<&Arc<Entry<T>>> {
+        match end {
+            End::Front => self.front.as_ref(),
+            End::Back => self.back.as_ref(),
+        }
+    }
+
+    /// Creates a new [`Anchor`] with a new entry pushed at the specified end.
+    fn with_end(&self, end: End, entry: Arc<Entry<T>>) -> Anchor<T> {
+        let (front, back) = match end {
+            End::Front => (Some(entry), self.back.clone()),
+            End::Back => (self.front.clone(), Some(entry)),
+        };
+        Anchor { front, back }
+    }
+
+    /// Creates a new [`Anchor`] without the entry at the specified end.
+    ///
+    /// Returns `None` if the entry has already been popped.
+    fn without_end(&self, end: End, barrier: &Barrier) -> Option<Anchor<T>> {
+        if let (Some(front), Some(back)) = (self.front.as_ref(), self.back.as_ref()) {
+            if ptr::eq(front.as_ptr(), back.as_ptr()) {
+                return Some(Anchor::default());
+            }
+        }
+        let neighbor = self
+            .end(end)?
+            .link(end.opposite())
+            .get_arc(Acquire, barrier)?;
+        let (front, back) = match end {
+            End::Front => (Some(neighbor), self.back.clone()),
+            End::Back => (self.front.clone(), Some(neighbor)),
+        };
+        Some(Anchor { front, back })
+    }
+}
+
+impl<T: 'static> Default for Anchor<T> {
+    fn default() -> Self {
+        Anchor {
+            front: None,
+            back: None,
+        }
+    }
+}
@@ -70,6 +70,19 @@ impl<T: 'static> Arc<T> {
         self.instance_ptr.as_mut().get_mut()
     }
 
+    /// Takes the underlying instance out of the [`Arc`] if the instance is exclusively owned.
+    ///
+    /// The caller must ensure that there is no [`Ptr`] to the instance.
+    #[inline]
+    pub(crate) unsafe fn try_unwrap(mut self) -> Result<T, Arc<T>> {
+        if self.get_mut().is_none() {
+            return Err(self);
+        }
+        let underlying = Box::from_raw(self.instance_ptr.as_ptr());
+        std::mem::forget(self);
+        Ok(underlying.into_instance())
+    }
+
     /// Provides a raw pointer to the underlying instance.
     ///
     /// # Examples
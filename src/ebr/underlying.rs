@@ -43,6 +43,12 @@ impl<T> Underlying<T> {
         }
     }
 
+    /// Consumes itself, and returns the instance.
+    #[inline]
+    pub(super) fn into_instance(self) -> T {
+        self.instance
+    }
+
     /// Adds a strong reference to the underlying instance.
     #[inline]
     pub(super) fn add_ref(&self) {
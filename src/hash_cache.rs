@@ -0,0 +1,584 @@
+//! [`HashCache`] is a concurrent and asynchronous cache with a fixed capacity.
+
+use super::ebr::{AtomicArc, Barrier};
+use super::hash_map;
+use super::hash_table::cell::{Cell, Locker, Reader, CELL_LEN};
+use super::hash_table::cell_array::CellArray;
+use super::hash_table::HashTable;
+use super::wait_queue::AsyncWait;
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::mem::transmute;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering::Acquire;
+
+/// Scalable concurrent cache.
+///
+/// [`HashCache`] is a concurrent and asynchronous hash map that never grows beyond its capacity.
+/// Each bucket keeps track of the recency of its entries by marking an entry whenever it is
+/// read, and when a new entry is put into a full bucket, the bucket evicts an entry according to
+/// the CLOCK algorithm, thereby approximating least-recently-used eviction.
+///
+/// ## The key features of [`HashCache`]
+///
+/// * Fixed capacity: the entry array is allocated once, and is never resized.
+/// * Per-bucket eviction: eviction is decided locally within a bucket without a global list.
+/// * Approximate LRU: entries that have been read since the last sweep get a second chance.
+///
+/// Since the recency is tracked per bucket, an entry can be evicted before the [`HashCache`] is
+/// full if the bucket that the new key belongs to is full.
+pub struct HashCache<K, V, H = RandomState>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    array: AtomicArc<CellArray<K, V, false>>,
+    capacity: usize,
+    resize_mutex: AtomicU8,
+    build_hasher: H,
+}
+
+/// [`RefGuard`] keeps the bucket containing the key-value pair shared-locked.
+pub type RefGuard<'h, K, V> = hash_map::RefGuard<'h, K, V>;
+
+impl<K, V, H> HashCache<K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    /// Creates an empty [`HashCache`] with the given capacity and [`BuildHasher`].
+    ///
+    /// The actual capacity is equal to or greater than the given capacity, and it never
+    /// changes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let hashcache: HashCache<u64, u32, RandomState> = HashCache::new(1000, RandomState::new());
+    ///
+    /// let result = hashcache.capacity();
+    /// assert_eq!(result, 1024);
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    /// let result = hashcache.capacity();
+    /// assert_eq!(result, 64);
+    /// ```
+    #[inline]
+    pub fn new(capacity: usize, build_hasher: H) -> HashCache<K, V, H> {
+        let array = CellArray::<K, V, false>::new(
+            capacity.max(Self::default_capacity()),
+            AtomicArc::null(),
+        );
+        let capacity = array.num_entries();
+        HashCache {
+            array: AtomicArc::new(array),
+            capacity,
+            resize_mutex: AtomicU8::new(0),
+            build_hasher,
+        }
+    }
+
+    /// Gets a reference to the value associated with the key, and marks the entry recently
+    /// accessed.
+    ///
+    /// The returned [`RefGuard`] keeps the bucket shared-locked during its lifetime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// assert!(hashcache.get(&1).is_none());
+    /// assert!(hashcache.put(1, 10).is_none());
+    /// assert_eq!(*hashcache.get(&1).unwrap().get(), 10);
+    /// ```
+    #[inline]
+    pub fn get<Q>(&self, key_ref: &Q) -> Option<RefGuard<'_, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (hash, partial_hash) = self.hash(key_ref);
+        self.acquire_reader(key_ref, hash, partial_hash, None, &Barrier::new())
+            .ok()
+            .and_then(|r| r)
+    }
+
+    /// Gets a reference to the value associated with the key, and marks the entry recently
+    /// accessed.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    /// let future_put = hashcache.put_async(11, 17);
+    /// let future_get = hashcache.get_async(&11);
+    /// ```
+    #[inline]
+    pub async fn get_async<Q>(&self, key_ref: &Q) -> Option<RefGuard<'_, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (hash, partial_hash) = self.hash(key_ref);
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if let Ok(result) = self.acquire_reader(
+                key_ref,
+                hash,
+                partial_hash,
+                Some(async_wait_pinned.mut_ptr()),
+                &Barrier::new(),
+            ) {
+                return result;
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Reads a key-value pair, and marks the entry recently accessed.
+    ///
+    /// It returns `None` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// assert!(hashcache.read(&1, |_, v| *v).is_none());
+    /// assert!(hashcache.put(1, 10).is_none());
+    /// assert_eq!(hashcache.read(&1, |_, v| *v).unwrap(), 10);
+    /// ```
+    #[inline]
+    pub fn read<Q, R, F: FnOnce(&K, &V) -> R>(&self, key_ref: &Q, reader: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.get(key_ref)
+            .map(|guard| reader(guard.key(), guard.get()))
+    }
+
+    /// Puts a key-value pair into the [`HashCache`].
+    ///
+    /// If the key exists, the value is replaced, the entry is marked recently accessed, and the
+    /// supplied key is returned along with the old value. Otherwise, if the bucket that the key
+    /// belongs to is full, an entry in the bucket that has not been accessed recently is evicted
+    /// and returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// assert_eq!(hashcache.put(1, 0), None);
+    /// assert_eq!(hashcache.put(1, 1), Some((1, 0)));
+    /// assert_eq!(*hashcache.get(&1).unwrap().get(), 1);
+    /// ```
+    #[inline]
+    pub fn put(&self, key: K, val: V) -> Option<(K, V)> {
+        let (hash, partial_hash) = self.hash(&key);
+        self.put_entry(key, val, hash, partial_hash, None, &Barrier::new())
+            .ok()
+            .and_then(|r| r)
+    }
+
+    /// Puts a key-value pair into the [`HashCache`].
+    ///
+    /// If the key exists, the value is replaced, the entry is marked recently accessed, and the
+    /// supplied key is returned along with the old value. Otherwise, if the bucket that the key
+    /// belongs to is full, an entry in the bucket that has not been accessed recently is evicted
+    /// and returned. It is an asynchronous method returning an `impl Future` for the caller to
+    /// await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    /// let future_put = hashcache.put_async(11, 17);
+    /// ```
+    #[inline]
+    pub async fn put_async(&self, mut key: K, mut val: V) -> Option<(K, V)> {
+        let (hash, partial_hash) = self.hash(&key);
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            match self.put_entry(
+                key,
+                val,
+                hash,
+                partial_hash,
+                Some(async_wait_pinned.mut_ptr()),
+                &Barrier::new(),
+            ) {
+                Ok(result) => return result,
+                Err(returned) => {
+                    key = returned.0;
+                    val = returned.1;
+                }
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Removes a key-value pair if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// assert!(hashcache.remove(&1).is_none());
+    /// assert!(hashcache.put(1, 0).is_none());
+    /// assert_eq!(hashcache.remove(&1).unwrap(), (1, 0));
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&self, key_ref: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (hash, partial_hash) = self.hash(key_ref);
+        self.remove_entry(key_ref, hash, partial_hash, None, &Barrier::new())
+            .ok()
+            .and_then(|r| r)
+    }
+
+    /// Removes a key-value pair if the key exists.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    /// let future_put = hashcache.put_async(11, 17);
+    /// let future_remove = hashcache.remove_async(&11);
+    /// ```
+    #[inline]
+    pub async fn remove_async<Q>(&self, key_ref: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (hash, partial_hash) = self.hash(key_ref);
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if let Ok(result) = self.remove_entry(
+                key_ref,
+                hash,
+                partial_hash,
+                Some(async_wait_pinned.mut_ptr()),
+                &Barrier::new(),
+            ) {
+                return result;
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Checks if the key exists without marking the entry accessed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// assert!(!hashcache.contains(&1));
+    /// assert!(hashcache.put(1, 0).is_none());
+    /// assert!(hashcache.contains(&1));
+    /// ```
+    #[inline]
+    pub fn contains<Q>(&self, key_ref: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (hash, partial_hash) = self.hash(key_ref);
+        let barrier = Barrier::new();
+        let cell = self.cell(hash, &barrier);
+        if let Some(reader) = Reader::lock(cell, &barrier) {
+            return reader
+                .cell()
+                .search(key_ref, partial_hash, &barrier)
+                .is_some();
+        }
+        false
+    }
+
+    /// Clears all the key-value pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// assert!(hashcache.put(1, 0).is_none());
+    /// assert_eq!(hashcache.clear(), 1);
+    /// ```
+    #[inline]
+    pub fn clear(&self) -> usize {
+        let mut num_removed: usize = 0;
+        let barrier = Barrier::new();
+        if let Some(array_ref) = self.array.load(Acquire, &barrier).as_ref() {
+            for cell_index in 0..array_ref.num_cells() {
+                if let Some(locker) = Locker::lock(array_ref.cell(cell_index), &barrier) {
+                    let mut iterator = locker.cell().iter(&barrier);
+                    while iterator.next().is_some() {
+                        locker.erase(&mut iterator);
+                        num_removed = num_removed.saturating_add(1);
+                    }
+                }
+            }
+        }
+        num_removed
+    }
+
+    /// Returns the number of entries in the [`HashCache`].
+    ///
+    /// It scans the entire array to calculate the number of valid entries, making its time
+    /// complexity `O(N)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// assert!(hashcache.put(1, 0).is_none());
+    /// assert_eq!(hashcache.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.num_entries(&Barrier::new())
+    }
+
+    /// Returns `true` if the [`HashCache`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// assert!(hashcache.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the capacity of the [`HashCache`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let hashcache: HashCache<u64, u32, RandomState> = HashCache::new(100, RandomState::new());
+    /// assert_eq!(hashcache.capacity(), 128);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the [`Cell`] that the hash value belongs to.
+    fn cell<'b>(&self, hash: u64, barrier: &'b Barrier) -> &'b Cell<K, V, false> {
+        let array_ref = self.array.load(Acquire, barrier).as_ref().unwrap();
+        array_ref.cell(array_ref.calculate_cell_index(hash))
+    }
+
+    /// Puts a key-value pair into the [`Cell`], replacing the value if the key exists, or evicting
+    /// an entry if the [`Cell`] is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns the key-value pair if locking failed.
+    fn put_entry(
+        &self,
+        key: K,
+        val: V,
+        hash: u64,
+        partial_hash: u8,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<Option<(K, V)>, (K, V)> {
+        let cell = self.cell(hash, barrier);
+        let lock_result = if let Some(&async_wait) = async_wait.as_ref() {
+            match Locker::try_lock_or_wait(cell, async_wait, barrier) {
+                Ok(result) => result,
+                Err(()) => return Err((key, val)),
+            }
+        } else {
+            Locker::lock(cell, barrier)
+        };
+        if let Some(locker) = lock_result {
+            if let Some(mut iterator) = locker.cell().get(&key, partial_hash, barrier) {
+                iterator.mark_accessed();
+                let (_, v) = iterator.get_mut(&locker);
+                return Ok(Some((key, std::mem::replace(v, val))));
+            }
+            let evicted = if locker.cell().num_entries() >= CELL_LEN {
+                locker.evict_unaccessed(partial_hash as usize)
+            } else {
+                None
+            };
+            locker.insert(key, val, partial_hash, barrier);
+            return Ok(evicted);
+        }
+
+        // The `Cell` of a `HashCache` is never killed while the `HashCache` is alive.
+        unreachable!()
+    }
+
+    /// Removes an entry from the [`Cell`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if locking failed.
+    fn remove_entry<Q>(
+        &self,
+        key_ref: &Q,
+        hash: u64,
+        partial_hash: u8,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<Option<(K, V)>, ()>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let cell = self.cell(hash, barrier);
+        let lock_result = if let Some(&async_wait) = async_wait.as_ref() {
+            Locker::try_lock_or_wait(cell, async_wait, barrier)?
+        } else {
+            Locker::lock(cell, barrier)
+        };
+        if let Some(locker) = lock_result {
+            if let Some(mut iterator) = locker.cell().get(key_ref, partial_hash, barrier) {
+                return Ok(locker.erase(&mut iterator));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Acquires a [`Reader`] on the bucket containing the key, and builds a [`RefGuard`].
+    ///
+    /// The [`Reader`] outlives the supplied [`Barrier`]; the [`CellArray`] of a [`HashCache`] is
+    /// only dropped when the [`HashCache`] is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if locking failed.
+    fn acquire_reader<'h, Q>(
+        &'h self,
+        key_ref: &Q,
+        hash: u64,
+        partial_hash: u8,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<Option<RefGuard<'h, K, V>>, ()>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let cell = self.cell(hash, barrier);
+        let lock_result = if let Some(&async_wait) = async_wait.as_ref() {
+            Reader::try_lock_or_wait(cell, async_wait, barrier)?
+        } else {
+            Reader::lock(cell, barrier)
+        };
+        if let Some(reader) = lock_result {
+            if let Some(entry_ref) = reader
+                .cell()
+                .search_and_mark(key_ref, partial_hash, barrier)
+            {
+                let reader: Reader<'h, K, V, false> = unsafe { transmute(reader) };
+                return Ok(Some(RefGuard::new(reader, entry_ref)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<K, V> Default for HashCache<K, V, RandomState>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+{
+    /// Creates a [`HashCache`] with the default parameters.
+    ///
+    /// The default hash builder is [`RandomState`], and the default capacity is `64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashCache;
+    ///
+    /// let hashcache: HashCache<u64, u32> = HashCache::default();
+    ///
+    /// let result = hashcache.capacity();
+    /// assert_eq!(result, 64);
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        Self::new(Self::default_capacity(), RandomState::new())
+    }
+}
+
+impl<K, V, H> HashTable<K, V, H, false> for HashCache<K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    fn hasher(&self) -> &H {
+        &self.build_hasher
+    }
+    fn copier(_: &K, _: &V) -> Option<(K, V)> {
+        None
+    }
+    fn cell_array(&self) -> &AtomicArc<CellArray<K, V, false>> {
+        &self.array
+    }
+    fn minimum_capacity(&self) -> usize {
+        self.capacity
+    }
+    fn resize_mutex(&self) -> &AtomicU8 {
+        &self.resize_mutex
+    }
+}
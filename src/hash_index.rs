@@ -279,6 +279,81 @@ where
         }
     }
 
+    /// Updates the value associated with the key if the key exists.
+    ///
+    /// A new value is built by the given closure, and it atomically replaces the old one; readers
+    /// observe either the old value or the new one. The old value is only marked unreachable, and
+    /// the memory will be reclaimed later.
+    ///
+    /// Returns `false` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndex;
+    ///
+    /// let hashindex: HashIndex<u64, u32> = HashIndex::default();
+    ///
+    /// assert!(!hashindex.update(&1, |v| *v + 1));
+    /// assert!(hashindex.insert(1, 0).is_ok());
+    /// assert!(hashindex.update(&1, |v| *v + 1));
+    /// assert_eq!(hashindex.read(&1, |_, v| *v).unwrap(), 1);
+    /// ```
+    #[inline]
+    pub fn update<Q, F: FnMut(&V) -> V>(&self, key_ref: &Q, mut updater: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (hash, partial_hash) = self.hash(key_ref);
+        self.update_entry::<Q, _>(
+            key_ref,
+            hash,
+            partial_hash,
+            &mut updater,
+            None,
+            &Barrier::new(),
+        )
+        .unwrap_or(false)
+    }
+
+    /// Updates the value associated with the key if the key exists.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndex;
+    ///
+    /// let hashindex: HashIndex<u64, u32> = HashIndex::default();
+    /// let future_insert = hashindex.insert_async(11, 17);
+    /// let future_update = hashindex.update_async(&11, |v| *v + 1);
+    /// ```
+    #[inline]
+    pub async fn update_async<Q, F: FnMut(&V) -> V>(&self, key_ref: &Q, mut updater: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (hash, partial_hash) = self.hash(key_ref);
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if let Ok(result) = self.update_entry::<Q, F>(
+                key_ref,
+                hash,
+                partial_hash,
+                &mut updater,
+                Some(async_wait_pinned.mut_ptr()),
+                &Barrier::new(),
+            ) {
+                return result;
+            }
+            async_wait_pinned.await;
+        }
+    }
+
     /// Reads a key-value pair.
     ///
     /// It returns `None` if the key does not exist.
@@ -590,6 +665,35 @@ where
             barrier_ref: barrier,
         }
     }
+
+    /// Replaces the entry associated with the key with a new one built by the updater.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if locking failed.
+    fn update_entry<Q, F: FnMut(&V) -> V>(
+        &self,
+        key_ref: &Q,
+        hash: u64,
+        partial_hash: u8,
+        updater: &mut F,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<bool, ()>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (_, locker, iterator) =
+            self.acquire::<Q>(key_ref, hash, partial_hash, async_wait, barrier)?;
+        if let Some(mut iterator) = iterator {
+            let (key, val) = iterator.get();
+            let new_val = updater(val);
+            locker.replace(&mut iterator, key.clone(), new_val, partial_hash);
+            return Ok(true);
+        }
+        Ok(false)
+    }
 }
 
 impl<K, V> Default for HashIndex<K, V, RandomState>
@@ -0,0 +1,460 @@
+//! [`HashIndexSet`] is a read-optimized concurrent and asynchronous hash set.
+
+use super::ebr::Barrier;
+use super::hash_index;
+use super::HashIndex;
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FusedIterator;
+
+/// Scalable concurrent hash set optimized for read.
+///
+/// [`HashIndexSet`] is a concurrent and asynchronous hash set based on [`HashIndex`]. Its read
+/// and scan operations are lock-free, whereas [`HashSet`](super::HashSet) shared-locks the bucket
+/// containing the key.
+pub struct HashIndexSet<K, H = RandomState>
+where
+    K: 'static + Clone + Eq + Hash + Sync,
+    H: BuildHasher,
+{
+    index: HashIndex<K, (), H>,
+}
+
+impl<K, H> HashIndexSet<K, H>
+where
+    K: 'static + Clone + Eq + Hash + Sync,
+    H: BuildHasher,
+{
+    /// Creates an empty [`HashIndexSet`] with the given capacity and [`BuildHasher`].
+    ///
+    /// The actual capacity is equal to or greater than the given capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let hashindexset: HashIndexSet<u64, RandomState> =
+    ///     HashIndexSet::new(1000, RandomState::new());
+    ///
+    /// let result = hashindexset.capacity();
+    /// assert_eq!(result, 1024);
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    /// let result = hashindexset.capacity();
+    /// assert_eq!(result, 64);
+    /// ```
+    #[inline]
+    pub fn new(capacity: usize, build_hasher: H) -> HashIndexSet<K, H> {
+        HashIndexSet {
+            index: HashIndex::new(capacity, build_hasher),
+        }
+    }
+
+    /// Inserts a key into the [`HashIndexSet`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied key if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// assert!(hashindexset.insert(1).is_ok());
+    /// assert_eq!(hashindexset.insert(1).unwrap_err(), 1);
+    /// ```
+    #[inline]
+    pub fn insert(&self, key: K) -> Result<(), K> {
+        self.index.insert(key, ()).map_err(|(k, ())| k)
+    }
+
+    /// Inserts a key into the [`HashIndexSet`].
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied key if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    /// let future_insert = hashindexset.insert_async(11);
+    /// ```
+    #[inline]
+    pub async fn insert_async(&self, key: K) -> Result<(), K> {
+        self.index.insert_async(key, ()).await.map_err(|(k, ())| k)
+    }
+
+    /// Removes a key if the key exists.
+    ///
+    /// Unlike [`HashSet::remove`](super::HashSet::remove), the key is not returned since the key
+    /// may still be read by other threads; it returns `false` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// assert!(!hashindexset.remove(&1));
+    /// assert!(hashindexset.insert(1).is_ok());
+    /// assert!(hashindexset.remove(&1));
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&self, key_ref: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.index.remove(key_ref)
+    }
+
+    /// Removes a key if the key exists.
+    ///
+    /// It returns `false` if the key does not exist. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    /// let future_insert = hashindexset.insert_async(11);
+    /// let future_remove = hashindexset.remove_async(&11);
+    /// ```
+    #[inline]
+    pub async fn remove_async<Q>(&self, key_ref: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.index.remove_async(key_ref).await
+    }
+
+    /// Removes a key if the key exists and the given condition is met.
+    ///
+    /// It returns `false` if the key does not exist or the condition was not met.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// assert!(hashindexset.insert(1).is_ok());
+    /// assert!(!hashindexset.remove_if(&1, || false));
+    /// assert!(hashindexset.remove_if(&1, || true));
+    /// ```
+    #[inline]
+    pub fn remove_if<Q, F: FnMut() -> bool>(&self, key_ref: &Q, mut condition: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.index.remove_if(key_ref, |()| condition())
+    }
+
+    /// Removes a key if the key exists and the given condition is met.
+    ///
+    /// It returns `false` if the key does not exist or the condition was not met. It is an
+    /// asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    /// let future_insert = hashindexset.insert_async(11);
+    /// let future_remove = hashindexset.remove_if_async(&11, || true);
+    /// ```
+    #[inline]
+    pub async fn remove_if_async<Q, F: FnMut() -> bool>(
+        &self,
+        key_ref: &Q,
+        mut condition: F,
+    ) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.index.remove_if_async(key_ref, |()| condition()).await
+    }
+
+    /// Reads a key.
+    ///
+    /// It returns `None` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// assert!(hashindexset.read(&1, |_| true).is_none());
+    /// assert!(hashindexset.insert(1).is_ok());
+    /// assert!(hashindexset.read(&1, |_| true).unwrap());
+    /// ```
+    #[inline]
+    pub fn read<Q, R, F: Fn(&K) -> R>(&self, key_ref: &Q, reader: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.index.read(key_ref, |k, ()| reader(k))
+    }
+
+    /// Reads a key using the supplied [`Barrier`].
+    ///
+    /// It enables the caller to use the key reference outside the method. It returns `None` if
+    /// the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::ebr::Barrier;
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<String> = HashIndexSet::default();
+    ///
+    /// assert!(hashindexset.insert(String::from("one")).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// let key_ref = hashindexset.read_with("one", |k| k, &barrier).unwrap();
+    /// assert_eq!(key_ref, "one");
+    /// ```
+    #[inline]
+    pub fn read_with<'b, Q, R, F: FnMut(&'b K) -> R>(
+        &self,
+        key_ref: &Q,
+        mut reader: F,
+        barrier: &'b Barrier,
+    ) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.index.read_with(key_ref, |k, ()| reader(k), barrier)
+    }
+
+    /// Checks if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// assert!(!hashindexset.contains(&1));
+    /// assert!(hashindexset.insert(1).is_ok());
+    /// assert!(hashindexset.contains(&1));
+    /// ```
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.index.contains(key)
+    }
+
+    /// Clears all the keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// assert!(hashindexset.insert(1).is_ok());
+    /// assert_eq!(hashindexset.clear(), 1);
+    /// ```
+    #[inline]
+    pub fn clear(&self) -> usize {
+        self.index.clear()
+    }
+
+    /// Clears all the keys.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// let future_insert = hashindexset.insert_async(1);
+    /// let future_clear = hashindexset.clear_async();
+    /// ```
+    #[inline]
+    pub async fn clear_async(&self) -> usize {
+        self.index.clear_async().await
+    }
+
+    /// Returns the number of keys in the [`HashIndexSet`].
+    ///
+    /// It scans the entire array to calculate the number of valid entries, making its time
+    /// complexity `O(N)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// assert!(hashindexset.insert(1).is_ok());
+    /// assert_eq!(hashindexset.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns `true` if the [`HashIndexSet`] is empty.
+    ///
+    /// It scans the entire array to calculate the number of valid entries, making its time
+    /// complexity `O(N)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// assert!(hashindexset.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns the capacity of the [`HashIndexSet`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let hashindexset: HashIndexSet<u64, RandomState> =
+    ///     HashIndexSet::new(1000000, RandomState::new());
+    /// assert_eq!(hashindexset.capacity(), 1048576);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.index.capacity()
+    }
+
+    /// Returns a [`Visitor`] that iterates over all the keys in the [`HashIndexSet`].
+    ///
+    /// It is guaranteed to go through all the keys pertaining in the [`HashIndexSet`] at the
+    /// moment, however the same key can be visited more than once if the [`HashIndexSet`] is
+    /// being resized.
+    ///
+    /// It requires the user to supply a reference to a [`Barrier`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::ebr::Barrier;
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// assert!(hashindexset.insert(1).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    ///
+    /// let mut iter = hashindexset.iter(&barrier);
+    /// let key_ref = iter.next().unwrap();
+    /// assert_eq!(iter.next(), None);
+    ///
+    /// drop(hashindexset);
+    ///
+    /// assert_eq!(key_ref, &1);
+    /// ```
+    #[inline]
+    pub fn iter<'h, 'b>(&'h self, barrier: &'b Barrier) -> Visitor<'h, 'b, K, H> {
+        Visitor {
+            visitor: self.index.iter(barrier),
+        }
+    }
+}
+
+impl<K> Default for HashIndexSet<K, RandomState>
+where
+    K: 'static + Clone + Eq + Hash + Sync,
+{
+    /// Creates a [`HashIndexSet`] with the default parameters.
+    ///
+    /// The default hash builder is [`RandomState`], and the default capacity is `64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashIndexSet;
+    ///
+    /// let hashindexset: HashIndexSet<u64> = HashIndexSet::default();
+    ///
+    /// let result = hashindexset.capacity();
+    /// assert_eq!(result, 64);
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        HashIndexSet {
+            index: HashIndex::default(),
+        }
+    }
+}
+
+/// [`Visitor`] traverses all the keys in the [`HashIndexSet`].
+///
+/// It has the same guarantees as [`hash_index::Visitor`].
+pub struct Visitor<'h, 'b, K, H>
+where
+    K: 'static + Clone + Eq + Hash + Sync,
+    H: BuildHasher,
+{
+    visitor: hash_index::Visitor<'h, 'b, K, (), H>,
+}
+
+impl<'b, K, H> Iterator for Visitor<'_, 'b, K, H>
+where
+    K: 'static + Clone + Eq + Hash + Sync,
+    H: 'static + BuildHasher,
+{
+    type Item = &'b K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.visitor.next().map(|(k, ())| k)
+    }
+}
+
+impl<K, H> FusedIterator for Visitor<'_, '_, K, H>
+where
+    K: 'static + Clone + Eq + Hash + Sync,
+    H: 'static + BuildHasher,
+{
+}
@@ -1,14 +1,18 @@
 //! [`HashMap`] is a concurrent and asynchronous hash map.
 
-use super::ebr::{Arc, AtomicArc, Barrier};
-use super::hash_table::cell::{Locker, Reader};
+use super::ebr::{Arc, AtomicArc, Barrier, Ptr};
+use super::hash_table::cell::{EntryIterator, Locker, Reader, CELL_LEN};
 use super::hash_table::cell_array::CellArray;
 use super::hash_table::HashTable;
 use super::wait_queue::AsyncWait;
 
 use std::borrow::Borrow;
 use std::collections::hash_map::RandomState;
+use std::fmt::{self, Debug};
 use std::hash::{BuildHasher, Hash};
+use std::iter::FusedIterator;
+use std::mem::transmute;
+use std::ops::{ControlFlow, Deref, DerefMut};
 use std::pin::Pin;
 use std::sync::atomic::Ordering::{Acquire, Relaxed};
 use std::sync::atomic::{AtomicU8, AtomicUsize};
@@ -51,6 +55,7 @@ where
     additional_capacity: AtomicUsize,
     resize_mutex: AtomicU8,
     build_hasher: H,
+    limit: Option<Limit<K, V>>,
 }
 
 impl<K, V, H> HashMap<K, V, H>
@@ -92,9 +97,81 @@ where
             additional_capacity: AtomicUsize::new(0),
             resize_mutex: AtomicU8::new(0),
             build_hasher,
+            limit: None,
         }
     }
 
+    /// Creates an empty [`HashMap`] that holds at most `max_entries` entries.
+    ///
+    /// When the limit is reached, [`HashMap::insert`] and [`HashMap::try_insert`] either fail or
+    /// evict an existing entry, depending on the supplied [`Overflow`] action. An eviction
+    /// policy only examines the bucket that the new key belongs to, or the next non-empty
+    /// bucket, therefore the choice is local rather than global.
+    ///
+    /// [`HashMap::try_upsert`] and [`VacantEntry::try_insert_entry`] respect the limit in the
+    /// same manner, except that the latter only evicts an entry in the bucket that it holds
+    /// locked. [`HashMap::upsert`] and [`VacantEntry::insert_entry`] never exceed the limit
+    /// either; they panic where their fallible counterparts would return an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::hash_map::{InsertError, Overflow};
+    /// use scc::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let bounded: HashMap<u64, u32> =
+    ///     HashMap::with_limit(64, 2, Overflow::Reject, RandomState::new());
+    /// assert_eq!(bounded.max_entries(), Some(2));
+    ///
+    /// assert!(bounded.try_insert(1, 0).is_ok());
+    /// assert!(bounded.try_insert(2, 0).is_ok());
+    /// assert_eq!(bounded.try_insert(2, 1), Err(InsertError::Occupied(2, 1)));
+    /// assert_eq!(bounded.try_insert(3, 0), Err(InsertError::Full(3, 0)));
+    ///
+    /// let evicting: HashMap<u64, u32> = HashMap::with_limit(
+    ///     64,
+    ///     1,
+    ///     Overflow::Evict(Box::new(|c: (&u64, &u32), s: (&u64, &u32)| c.1 < s.1)),
+    ///     RandomState::new(),
+    /// );
+    /// assert!(evicting.insert(1, 0).is_ok());
+    /// assert!(evicting.insert(2, 0).is_ok());
+    /// assert_eq!(evicting.len(), 1);
+    /// assert!(evicting.contains(&2));
+    /// ```
+    #[inline]
+    pub fn with_limit(
+        capacity: usize,
+        max_entries: usize,
+        overflow: Overflow<K, V>,
+        build_hasher: H,
+    ) -> HashMap<K, V, H> {
+        let mut hashmap = Self::new(capacity, build_hasher);
+        hashmap.limit.replace(Limit {
+            max_entries,
+            num_entries: AtomicUsize::new(0),
+            overflow,
+        });
+        hashmap
+    }
+
+    /// Returns the maximum number of entries if the [`HashMap`] is bounded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// assert!(hashmap.max_entries().is_none());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn max_entries(&self) -> Option<usize> {
+        self.limit.as_ref().map(|limit| limit.max_entries)
+    }
+
     /// Temporarily increases the minimum capacity of the [`HashMap`].
     ///
     /// The reserved space is not exclusively owned by the [`Ticket`], thus can be overtaken.
@@ -149,11 +226,76 @@ where
         }
     }
 
+    /// Gets the corresponding entry in the [`HashMap`] for in-place manipulation.
+    ///
+    /// The returned [`Entry`] exclusively locks the bucket that the key belongs to until it is
+    /// dropped, therefore the caller must not access the same bucket through the [`HashMap`]
+    /// while holding it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<char, u32> = HashMap::default();
+    ///
+    /// for ch in "a short treatise on fungi".chars() {
+    ///     hashmap.entry(ch).and_modify(|counter| *counter += 1).or_insert(1);
+    /// }
+    ///
+    /// assert_eq!(hashmap.read(&'s', |_, v| *v), Some(2));
+    /// assert_eq!(hashmap.read(&'t', |_, v| *v), Some(3));
+    /// assert!(hashmap.read(&'y', |_, v| *v).is_none());
+    /// ```
+    #[inline]
+    pub fn entry(&self, key: K) -> Entry<'_, K, V, H> {
+        let (hash, partial_hash) = self.hash(&key);
+        let barrier = Barrier::new();
+        if let Ok(entry) = self.acquire_entry(key, hash, partial_hash, None, &barrier) {
+            return entry;
+        }
+        unreachable!()
+    }
+
+    /// Gets the corresponding entry in the [`HashMap`] for in-place manipulation.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<char, u32> = HashMap::default();
+    ///
+    /// let future_entry = hashmap.entry_async('b');
+    /// ```
+    #[inline]
+    pub async fn entry_async(&self, mut key: K) -> Entry<'_, K, V, H> {
+        let (hash, partial_hash) = self.hash(&key);
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            match self.acquire_entry(
+                key,
+                hash,
+                partial_hash,
+                Some(async_wait_pinned.mut_ptr()),
+                &Barrier::new(),
+            ) {
+                Ok(entry) => return entry,
+                Err(returned) => key = returned,
+            }
+            async_wait_pinned.await;
+        }
+    }
+
     /// Inserts a key-value pair into the [`HashMap`].
     ///
     /// # Errors
     ///
-    /// Returns an error along with the supplied key-value pair if the key exists.
+    /// Returns an error along with the supplied key-value pair if the key exists, or if the
+    /// [`HashMap`] is bounded and full.
     ///
     /// # Examples
     ///
@@ -167,14 +309,7 @@ where
     /// ```
     #[inline]
     pub fn insert(&self, key: K, val: V) -> Result<(), (K, V)> {
-        let (hash, partial_hash) = self.hash(&key);
-        if let Ok(Some((k, v))) =
-            self.insert_entry(key, val, hash, partial_hash, None, &Barrier::new())
-        {
-            Err((k, v))
-        } else {
-            Ok(())
-        }
+        self.try_insert(key, val).map_err(InsertError::into_inner)
     }
 
     /// Inserts a key-value pair into the [`HashMap`].
@@ -183,7 +318,8 @@ where
     ///
     /// # Errors
     ///
-    /// Returns an error along with the supplied key-value pair if the key exists.
+    /// Returns an error along with the supplied key-value pair if the key exists, or if the
+    /// [`HashMap`] is bounded and full.
     ///
     /// # Examples
     ///
@@ -194,24 +330,94 @@ where
     /// let future_insert = hashmap.insert_async(11, 17);
     /// ```
     #[inline]
-    pub async fn insert_async(&self, mut key: K, mut val: V) -> Result<(), (K, V)> {
+    pub async fn insert_async(&self, key: K, val: V) -> Result<(), (K, V)> {
+        self.try_insert_async(key, val)
+            .await
+            .map_err(InsertError::into_inner)
+    }
+
+    /// Inserts a key-value pair into the [`HashMap`], telling apart the reasons for a failure.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertError::Occupied`] if the key exists, or [`InsertError::Full`] if the
+    /// [`HashMap`] has reached its maximum number of entries and cannot evict any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::hash_map::InsertError;
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.try_insert(1, 0).is_ok());
+    /// assert_eq!(hashmap.try_insert(1, 1), Err(InsertError::Occupied(1, 1)));
+    /// ```
+    #[inline]
+    pub fn try_insert(&self, mut key: K, val: V) -> Result<(), InsertError<K, V>> {
+        let (hash, partial_hash) = self.hash(&key);
+        let mut constructor = || val;
+        let mut updater = None::<fn(&K, &mut V)>;
+        loop {
+            match self.insert_bounded(
+                key,
+                constructor,
+                updater,
+                hash,
+                partial_hash,
+                None,
+                &Barrier::new(),
+            ) {
+                Ok(result) => return result,
+                Err(returned) => {
+                    key = returned.0;
+                    constructor = returned.1;
+                    updater = returned.2;
+                }
+            }
+        }
+    }
+
+    /// Inserts a key-value pair into the [`HashMap`], telling apart the reasons for a failure.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertError::Occupied`] if the key exists, or [`InsertError::Full`] if the
+    /// [`HashMap`] has reached its maximum number of entries and cannot evict any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let future_insert = hashmap.try_insert_async(11, 17);
+    /// ```
+    #[inline]
+    pub async fn try_insert_async(&self, mut key: K, val: V) -> Result<(), InsertError<K, V>> {
         let (hash, partial_hash) = self.hash(&key);
+        let mut constructor = || val;
+        let mut updater = None::<fn(&K, &mut V)>;
         loop {
             let mut async_wait = AsyncWait::default();
             let mut async_wait_pinned = Pin::new(&mut async_wait);
-            match self.insert_entry(
+            match self.insert_bounded(
                 key,
-                val,
+                constructor,
+                updater,
                 hash,
                 partial_hash,
                 Some(async_wait_pinned.mut_ptr()),
                 &Barrier::new(),
             ) {
-                Ok(Some(returned)) => return Err(returned),
-                Ok(None) => return Ok(()),
+                Ok(result) => return result,
                 Err(returned) => {
                     key = returned.0;
-                    val = returned.1;
+                    constructor = returned.1;
+                    updater = returned.2;
                 }
             }
             async_wait_pinned.await;
@@ -302,6 +508,11 @@ where
 
     /// Constructs the value in-place, or modifies an existing value corresponding to the key.
     ///
+    /// # Panics
+    ///
+    /// Panics if the [`HashMap`] has reached its maximum number of entries and cannot evict any;
+    /// use [`HashMap::try_upsert`] to handle the case.
+    ///
     /// # Examples
     ///
     /// ```
@@ -321,26 +532,19 @@ where
         constructor: FI,
         updater: FU,
     ) {
-        let (hash, partial_hash) = self.hash(&key);
-        let barrier = Barrier::new();
-        if let Ok((_, locker, iterator)) =
-            self.acquire::<_>(&key, hash, partial_hash, None, &barrier)
-        {
-            if let Some(iterator) = iterator {
-                let (k, v) = iterator.get();
-                // The presence of `locker` prevents the entry from being modified outside it.
-                #[allow(clippy::cast_ref_to_mut)]
-                updater(k, unsafe { &mut *(v as *const V as *mut V) });
-                return;
-            }
-            locker.insert(key, constructor(), partial_hash, &barrier);
-        };
+        let result = self.try_upsert(key, constructor, updater);
+        assert!(result.is_ok(), "the HashMap is full");
     }
 
     /// Constructs the value in-place, or modifies an existing value corresponding to the key.
     ///
     /// It is an asynchronous method returning an `impl Future` for the caller to await.
     ///
+    /// # Panics
+    ///
+    /// Panics if the [`HashMap`] has reached its maximum number of entries and cannot evict any;
+    /// use [`HashMap::try_upsert_async`] to handle the case.
+    ///
     /// # Examples
     ///
     /// ```
@@ -357,25 +561,111 @@ where
         constructor: FI,
         updater: FU,
     ) {
+        let result = self.try_upsert_async(key, constructor, updater).await;
+        assert!(result.is_ok(), "the HashMap is full");
+    }
+
+    /// Constructs the value in-place, or modifies an existing value corresponding to the key,
+    /// while respecting the maximum number of entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertError::Full`] along with the key and the constructed value if the
+    /// [`HashMap`] has reached its maximum number of entries and cannot evict any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::hash_map::{InsertError, Overflow};
+    /// use scc::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let hashmap: HashMap<u64, u32> =
+    ///     HashMap::with_limit(64, 1, Overflow::Reject, RandomState::new());
+    ///
+    /// assert!(hashmap.try_upsert(1, || 2, |_, v| *v = 2).is_ok());
+    /// assert!(hashmap.try_upsert(1, || 2, |_, v| *v = 3).is_ok());
+    /// assert_eq!(hashmap.read(&1, |_, v| *v).unwrap(), 3);
+    /// assert_eq!(
+    ///     hashmap.try_upsert(2, || 2, |_, v| *v = 3),
+    ///     Err(InsertError::Full(2, 2))
+    /// );
+    /// ```
+    #[inline]
+    pub fn try_upsert<FI: FnOnce() -> V, FU: FnOnce(&K, &mut V)>(
+        &self,
+        mut key: K,
+        mut constructor: FI,
+        updater: FU,
+    ) -> Result<(), InsertError<K, V>> {
+        let (hash, partial_hash) = self.hash(&key);
+        let mut updater = Some(updater);
+        loop {
+            match self.insert_bounded(
+                key,
+                constructor,
+                updater,
+                hash,
+                partial_hash,
+                None,
+                &Barrier::new(),
+            ) {
+                Ok(result) => return result,
+                Err(returned) => {
+                    key = returned.0;
+                    constructor = returned.1;
+                    updater = returned.2;
+                }
+            }
+        }
+    }
+
+    /// Constructs the value in-place, or modifies an existing value corresponding to the key,
+    /// while respecting the maximum number of entries.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertError::Full`] along with the key and the constructed value if the
+    /// [`HashMap`] has reached its maximum number of entries and cannot evict any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// let future_upsert = hashmap.try_upsert_async(1, || 2, |_, v| *v = 3);
+    /// ```
+    #[inline]
+    pub async fn try_upsert_async<FI: FnOnce() -> V, FU: FnOnce(&K, &mut V)>(
+        &self,
+        mut key: K,
+        mut constructor: FI,
+        updater: FU,
+    ) -> Result<(), InsertError<K, V>> {
         let (hash, partial_hash) = self.hash(&key);
+        let mut updater = Some(updater);
         loop {
             let mut async_wait = AsyncWait::default();
             let mut async_wait_pinned = Pin::new(&mut async_wait);
-            if let Ok((_, locker, iterator)) = self.acquire::<_>(
-                &key,
+            match self.insert_bounded(
+                key,
+                constructor,
+                updater,
                 hash,
                 partial_hash,
                 Some(async_wait_pinned.mut_ptr()),
                 &Barrier::new(),
             ) {
-                if let Some(iterator) = iterator {
-                    let (k, v) = iterator.get();
-                    #[allow(clippy::cast_ref_to_mut)]
-                    updater(k, unsafe { &mut *(v as *const V as *mut V) });
-                    return;
+                Ok(result) => return result,
+                Err(returned) => {
+                    key = returned.0;
+                    constructor = returned.1;
+                    updater = returned.2;
                 }
-                locker.insert(key, constructor(), partial_hash, &Barrier::new());
-                return;
             }
             async_wait_pinned.await;
         }
@@ -449,16 +739,21 @@ where
         Q: Eq + Hash + ?Sized,
     {
         let (hash, partial_hash) = self.hash(key_ref);
-        self.remove_entry::<Q, _>(
-            key_ref,
-            hash,
-            partial_hash,
-            &mut condition,
-            None,
-            &Barrier::new(),
-        )
-        .ok()
-        .and_then(|(r, _)| r)
+        let removed = self
+            .remove_entry::<Q, _>(
+                key_ref,
+                hash,
+                partial_hash,
+                &mut condition,
+                None,
+                &Barrier::new(),
+            )
+            .ok()
+            .and_then(|(r, _)| r);
+        if removed.is_some() {
+            self.release_entries(1);
+        }
+        removed
     }
 
     /// Removes a key-value pair if the key exists and the given condition is met.
@@ -496,6 +791,9 @@ where
                 Some(async_wait_pinned.mut_ptr()),
                 &Barrier::new(),
             ) {
+                if result.0.is_some() {
+                    self.release_entries(1);
+                }
                 return result.0;
             }
             async_wait_pinned.await;
@@ -572,7 +870,12 @@ where
         }
     }
 
-    /// Checks if the key exists.
+    /// Gets a reference to the value associated with the key.
+    ///
+    /// The returned [`RefGuard`] keeps the bucket shared-locked during its lifetime, therefore
+    /// any modification to the bucket is blocked until the [`RefGuard`] is dropped.
+    ///
+    /// It returns `None` if the key does not exist.
     ///
     /// # Examples
     ///
@@ -581,22 +884,26 @@ where
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
-    /// assert!(!hashmap.contains(&1));
-    /// assert!(hashmap.insert(1, 0).is_ok());
-    /// assert!(hashmap.contains(&1));
+    /// assert!(hashmap.get(&1).is_none());
+    /// assert!(hashmap.insert(1, 10).is_ok());
+    /// assert_eq!(*hashmap.get(&1).unwrap(), 10);
     /// ```
     #[inline]
-    pub fn contains<Q>(&self, key: &Q) -> bool
+    pub fn get<Q>(&self, key_ref: &Q) -> Option<RefGuard<'_, K, V>>
     where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
     {
-        self.read(key, |_, _| ()).is_some()
+        let (hash, partial_hash) = self.hash(key_ref);
+        self.acquire_reader(key_ref, hash, partial_hash, None, &Barrier::new())
+            .ok()
+            .and_then(|r| r)
     }
 
-    /// Checks if the key exists.
+    /// Gets a reference to the value associated with the key.
     ///
-    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    /// It returns `None` if the key does not exist. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
     ///
     /// # Examples
     ///
@@ -604,154 +911,145 @@ where
     /// use scc::HashMap;
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
-    ///
-    /// let future_contains = hashmap.contains_async(&1);
+    /// let future_insert = hashmap.insert_async(11, 17);
+    /// let future_get = hashmap.get_async(&11);
     /// ```
     #[inline]
-    pub async fn contains_async<Q>(&self, key: &Q) -> bool
+    pub async fn get_async<Q>(&self, key_ref: &Q) -> Option<RefGuard<'_, K, V>>
     where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
     {
-        self.read_async(key, |_, _| ()).await.is_some()
+        let (hash, partial_hash) = self.hash(key_ref);
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if let Ok(result) = self.acquire_reader(
+                key_ref,
+                hash,
+                partial_hash,
+                Some(async_wait_pinned.mut_ptr()),
+                &Barrier::new(),
+            ) {
+                return result;
+            }
+            async_wait_pinned.await;
+        }
     }
 
-    /// Scans all the key-value pairs.
+    /// Gets a mutable reference to the value associated with the key.
     ///
-    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
-    /// visited if they are not removed, however the same key-value pair can be visited more than
-    /// once if the [`HashMap`] gets resized by another thread.
+    /// The returned [`MutGuard`] keeps the bucket exclusively locked during its lifetime,
+    /// therefore any access to the bucket is blocked until the [`MutGuard`] is dropped.
+    ///
+    /// It returns `None` if the key does not exist.
     ///
     /// # Examples
     ///
     /// ```
     /// use scc::HashMap;
     ///
-    /// let hashmap: HashMap<usize, usize> = HashMap::default();
-    ///
-    /// assert!(hashmap.insert(1, 0).is_ok());
-    /// assert!(hashmap.insert(2, 1).is_ok());
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
-    /// let mut sum = 0;
-    /// hashmap.scan(|k, v| { sum += *k + *v; });
-    /// assert_eq!(sum, 4);
+    /// assert!(hashmap.get_mut(&1).is_none());
+    /// assert!(hashmap.insert(1, 10).is_ok());
+    /// *hashmap.get_mut(&1).unwrap() += 1;
+    /// assert_eq!(hashmap.read(&1, |_, v| *v).unwrap(), 11);
     /// ```
-    pub fn scan<F: FnMut(&K, &V)>(&self, mut scanner: F) {
-        let barrier = Barrier::new();
+    #[inline]
+    pub fn get_mut<Q>(&self, key_ref: &Q) -> Option<MutGuard<'_, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (hash, partial_hash) = self.hash(key_ref);
+        self.acquire_locker(key_ref, hash, partial_hash, None, &Barrier::new())
+            .ok()
+            .and_then(|r| r)
+    }
 
-        // An acquire fence is required to correctly load the contents of the array.
-        let mut current_array_ptr = self.array.load(Acquire, &barrier);
-        while let Some(current_array_ref) = current_array_ptr.as_ref() {
-            while !current_array_ref.old_array(&barrier).is_null() {
-                if current_array_ref.partial_rehash::<_, _, _>(
-                    |key| self.hash(key),
-                    |_, _| None,
-                    None,
-                    &barrier,
-                ) == Ok(true)
-                {
-                    break;
-                }
-            }
-
-            for cell_index in 0..current_array_ref.num_cells() {
-                if let Some(locker) = Reader::lock(current_array_ref.cell(cell_index), &barrier) {
-                    locker
-                        .cell()
-                        .iter(&barrier)
-                        .for_each(|((k, v), _)| scanner(k, v));
-                }
-            }
-
-            let new_current_array_ptr = self.array.load(Acquire, &barrier);
-            if current_array_ptr == new_current_array_ptr {
-                break;
+    /// Gets a mutable reference to the value associated with the key.
+    ///
+    /// It returns `None` if the key does not exist. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let future_insert = hashmap.insert_async(11, 17);
+    /// let future_get_mut = hashmap.get_mut_async(&11);
+    /// ```
+    #[inline]
+    pub async fn get_mut_async<Q>(&self, key_ref: &Q) -> Option<MutGuard<'_, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (hash, partial_hash) = self.hash(key_ref);
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if let Ok(result) = self.acquire_locker(
+                key_ref,
+                hash,
+                partial_hash,
+                Some(async_wait_pinned.mut_ptr()),
+                &Barrier::new(),
+            ) {
+                return result;
             }
-            current_array_ptr = new_current_array_ptr;
+            async_wait_pinned.await;
         }
     }
 
-    /// Scans all the key-value pairs.
-    ///
-    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
-    /// visited if they are not removed, however the same key-value pair can be visited more than
-    /// once if the [`HashMap`] gets resized by another task.
+    /// Checks if the key exists.
     ///
     /// # Examples
     ///
     /// ```
     /// use scc::HashMap;
     ///
-    /// let hashmap: HashMap<usize, usize> = HashMap::default();
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
-    /// let future_insert = hashmap.insert_async(1, 0);
-    /// let future_scan = hashmap.scan_async(|k, v| println!("{k} {v}"));
+    /// assert!(!hashmap.contains(&1));
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.contains(&1));
     /// ```
-    pub async fn scan_async<F: FnMut(&K, &V)>(&self, mut scanner: F) {
-        // An acquire fence is required to correctly load the contents of the array.
-        let mut current_array_holder = self.array.get_arc(Acquire, &Barrier::new());
-        while let Some(current_array) = current_array_holder.take() {
-            while !current_array.old_array(&Barrier::new()).is_null() {
-                let mut async_wait = AsyncWait::default();
-                let mut async_wait_pinned = Pin::new(&mut async_wait);
-                if current_array.partial_rehash::<_, _, _>(
-                    |key| self.hash(key),
-                    |_, _| None,
-                    Some(async_wait_pinned.mut_ptr()),
-                    &Barrier::new(),
-                ) == Ok(true)
-                {
-                    break;
-                }
-                async_wait_pinned.await;
-            }
-
-            for cell_index in 0..current_array.num_cells() {
-                let killed = loop {
-                    let mut async_wait = AsyncWait::default();
-                    let mut async_wait_pinned = Pin::new(&mut async_wait);
-                    {
-                        let barrier = Barrier::new();
-                        if let Ok(result) = Reader::try_lock_or_wait(
-                            current_array.cell(cell_index),
-                            async_wait_pinned.mut_ptr(),
-                            &barrier,
-                        ) {
-                            if let Some(locker) = result {
-                                let mut iterator = locker.cell().iter(&barrier);
-                                while iterator.next().is_some() {
-                                    let (k, v) = iterator.get();
-                                    scanner(k, v);
-                                }
-                                break false;
-                            }
-
-                            // The `Cell` having been killed means that a new array has been
-                            // allocated.
-                            break true;
-                        };
-                    }
-                    async_wait_pinned.await;
-                };
-                if killed {
-                    break;
-                }
-            }
-
-            if let Some(new_current_array) = self.array.get_arc(Acquire, &Barrier::new()) {
-                if new_current_array.as_ptr() == current_array.as_ptr() {
-                    break;
-                }
-                current_array_holder.replace(new_current_array);
-                continue;
-            }
-            break;
-        }
+    #[inline]
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.read(key, |_, _| ()).is_some()
     }
 
-    /// Iterates over all the entries in the [`HashMap`].
+    /// Checks if the key exists.
     ///
-    /// This method allows modifying each value.
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// let future_contains = hashmap.contains_async(&1);
+    /// ```
+    #[inline]
+    pub async fn contains_async<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.read_async(key, |_, _| ()).await.is_some()
+    }
+
+    /// Scans all the key-value pairs.
     ///
     /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
     /// visited if they are not removed, however the same key-value pair can be visited more than
@@ -762,147 +1060,381 @@ where
     /// ```
     /// use scc::HashMap;
     ///
-    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let hashmap: HashMap<usize, usize> = HashMap::default();
     ///
     /// assert!(hashmap.insert(1, 0).is_ok());
     /// assert!(hashmap.insert(2, 1).is_ok());
     ///
-    /// let mut acc = 0;
-    /// hashmap.for_each(|k, v| { acc += *k; *v = 2; });
-    /// assert_eq!(acc, 3);
-    /// assert_eq!(hashmap.read(&1, |_, v| *v).unwrap(), 2);
-    /// assert_eq!(hashmap.read(&2, |_, v| *v).unwrap(), 2);
+    /// let mut sum = 0;
+    /// hashmap.scan(|k, v| { sum += *k + *v; });
+    /// assert_eq!(sum, 4);
     /// ```
     #[inline]
-    pub fn for_each<F: FnMut(&K, &mut V)>(&self, mut f: F) {
-        self.retain(|k, v| {
-            f(k, v);
-            true
+    pub fn scan<F: FnMut(&K, &V)>(&self, mut scanner: F) {
+        self.scan_while(|k, v| {
+            scanner(k, v);
+            ControlFlow::<()>::Continue(())
         });
     }
 
-    /// Iterates over all the entries in the [`HashMap`].
-    ///
-    /// This method allows modifying each value.
+    /// Scans all the key-value pairs.
     ///
     /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
     /// visited if they are not removed, however the same key-value pair can be visited more than
     /// once if the [`HashMap`] gets resized by another task.
     ///
-    /// It is an asynchronous method returning an `impl Future` for the caller to await.
-    ///
     /// # Examples
     ///
     /// ```
     /// use scc::HashMap;
     ///
-    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let hashmap: HashMap<usize, usize> = HashMap::default();
     ///
     /// let future_insert = hashmap.insert_async(1, 0);
-    /// let future_for_each = hashmap.for_each_async(|k, v| println!("{} {}", k, v));
+    /// let future_scan = hashmap.scan_async(|k, v| println!("{k} {v}"));
     /// ```
     #[inline]
-    pub async fn for_each_async<F: FnMut(&K, &mut V)>(&self, mut f: F) {
-        self.retain_async(|k, v| {
-            f(k, v);
-            true
+    pub async fn scan_async<F: FnMut(&K, &V)>(&self, mut scanner: F) {
+        self.scan_while_async(|k, v| {
+            scanner(k, v);
+            ControlFlow::<()>::Continue(())
         })
         .await;
     }
 
-    /// Retains key-value pairs that satisfy the given predicate.
-    ///
-    /// This method allows modifying each value.
-    ///
-    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
-    /// visited if they are not removed, however the same key-value pair can be visited more than
-    /// once if the [`HashMap`] gets resized by another thread.
+    /// Scans the key-value pairs until the closure returns [`ControlFlow::Break`].
     ///
-    /// It returns the number of entries remaining and removed.
+    /// Returns the value carried by [`ControlFlow::Break`], or `None` if all the key-value pairs
+    /// have been visited. The same guarantees as [`HashMap::scan`] apply.
     ///
     /// # Examples
     ///
     /// ```
     /// use scc::HashMap;
+    /// use std::ops::ControlFlow;
     ///
-    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let hashmap: HashMap<usize, usize> = HashMap::default();
     ///
-    /// assert!(hashmap.insert(1, 0).is_ok());
-    /// assert!(hashmap.insert(2, 1).is_ok());
-    /// assert!(hashmap.insert(3, 2).is_ok());
+    /// for i in 0..64 {
+    ///     assert!(hashmap.insert(i, i).is_ok());
+    /// }
     ///
-    /// assert_eq!(hashmap.retain(|k, v| *k == 1 && *v == 0), (1, 2));
+    /// let mut visited = 0;
+    /// let result = hashmap.scan_while(|k, v| {
+    ///     visited += 1;
+    ///     if *k == 7 {
+    ///         ControlFlow::Break(*v)
+    ///     } else {
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// });
+    /// assert_eq!(result, Some(7));
+    /// assert!(visited <= 64);
+    /// assert_eq!(hashmap.scan_while(|_, _| ControlFlow::<()>::Continue(())), None);
     /// ```
-    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&self, mut filter: F) -> (usize, usize) {
-        let mut num_retained: usize = 0;
-        let mut num_removed: usize = 0;
-
-        let barrier = Barrier::new();
-
-        // An acquire fence is required to correctly load the contents of the array.
-        let mut current_array_ptr = self.array.load(Acquire, &barrier);
-        while let Some(current_array_ref) = current_array_ptr.as_ref() {
-            while !current_array_ref.old_array(&barrier).is_null() {
-                if current_array_ref.partial_rehash::<_, _, _>(
-                    |key| self.hash(key),
-                    |_, _| None,
-                    None,
-                    &barrier,
-                ) == Ok(true)
-                {
-                    break;
-                }
-            }
-
-            for cell_index in 0..current_array_ref.num_cells() {
-                if let Some(locker) = Locker::lock(current_array_ref.cell(cell_index), &barrier) {
-                    let mut iterator = locker.cell().iter(&barrier);
-                    while iterator.next().is_some() {
-                        let (k, v) = iterator.get();
-                        #[allow(clippy::cast_ref_to_mut)]
-                        let retain = filter(k, unsafe { &mut *(v as *const V as *mut V) });
-                        if retain {
-                            num_retained = num_retained.saturating_add(1);
-                        } else {
-                            locker.erase(&mut iterator);
-                            num_removed = num_removed.saturating_add(1);
-                        }
-                    }
-                }
-            }
-
-            let new_current_array_ptr = self.array.load(Acquire, &barrier);
-            if current_array_ptr == new_current_array_ptr {
-                break;
-            }
-            num_retained = 0;
-            current_array_ptr = new_current_array_ptr;
-        }
-
-        if num_removed >= num_retained {
-            self.resize(&barrier);
-        }
-
-        (num_retained, num_removed)
+    #[inline]
+    pub fn scan_while<R, F: FnMut(&K, &V) -> ControlFlow<R>>(&self, mut scanner: F) -> Option<R> {
+        self.scan_entries(|_, (k, v)| scanner(k, v))
     }
 
-    /// Retains key-value pairs that satisfy the given predicate.
-    ///
-    /// This method allows modifying each value.
+    /// Scans the key-value pairs until the closure returns [`ControlFlow::Break`].
     ///
-    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
-    /// visited if they are not removed, however the same key-value pair can be visited more than
-    /// once if the [`HashMap`] gets resized by another task.
+    /// Returns the value carried by [`ControlFlow::Break`], or `None` if all the key-value pairs
+    /// have been visited. The same guarantees as [`HashMap::scan_async`] apply.
     ///
-    /// It returns the number of entries remaining and removed. It is an asynchronous method
-    /// returning an `impl Future` for the caller to await.
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
     ///
     /// # Examples
     ///
     /// ```
     /// use scc::HashMap;
+    /// use std::ops::ControlFlow;
     ///
-    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let hashmap: HashMap<usize, usize> = HashMap::default();
+    ///
+    /// let future_insert = hashmap.insert_async(1, 0);
+    /// let future_scan_while = hashmap.scan_while_async(|k, _| ControlFlow::Break(*k));
+    /// ```
+    #[inline]
+    pub async fn scan_while_async<R, F: FnMut(&K, &V) -> ControlFlow<R>>(
+        &self,
+        mut scanner: F,
+    ) -> Option<R> {
+        self.scan_entries_async(|_, (k, v)| scanner(k, v)).await
+    }
+
+    /// Checks if any key-value pair satisfies the given predicate.
+    ///
+    /// The scan stops as soon as a matching key-value pair is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 10).is_ok());
+    /// assert!(hashmap.insert(2, 20).is_ok());
+    ///
+    /// assert!(hashmap.any(|_, v| *v == 20));
+    /// assert!(!hashmap.any(|k, _| *k == 3));
+    /// ```
+    #[inline]
+    pub fn any<P: FnMut(&K, &V) -> bool>(&self, mut pred: P) -> bool {
+        self.scan_while(|k, v| {
+            if pred(k, v) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .is_some()
+    }
+
+    /// Checks if any key-value pair satisfies the given predicate.
+    ///
+    /// The scan stops as soon as a matching key-value pair is found.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// let future_insert = hashmap.insert_async(1, 10);
+    /// let future_any = hashmap.any_async(|_, v| *v == 10);
+    /// ```
+    #[inline]
+    pub async fn any_async<P: FnMut(&K, &V) -> bool>(&self, mut pred: P) -> bool {
+        self.scan_while_async(|k, v| {
+            if pred(k, v) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .await
+        .is_some()
+    }
+
+    /// Finds a key-value pair satisfying the given predicate.
+    ///
+    /// The scan stops as soon as a matching key-value pair is found, and the returned
+    /// [`RefGuard`] keeps the bucket containing it shared-locked until dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 10).is_ok());
+    /// assert!(hashmap.insert(2, 20).is_ok());
+    ///
+    /// assert_eq!(*hashmap.find(|_, v| *v == 20).unwrap().key(), 2);
+    /// assert!(hashmap.find(|k, _| *k == 3).is_none());
+    /// ```
+    #[inline]
+    pub fn find<P: FnMut(&K, &V) -> bool>(&self, mut pred: P) -> Option<RefGuard<'_, K, V>> {
+        self.scan_entries(|reader, entry_ref| {
+            if pred(&entry_ref.0, &entry_ref.1) {
+                return ControlFlow::Break(Self::ref_guard(reader, entry_ref));
+            }
+            ControlFlow::Continue(())
+        })
+    }
+
+    /// Finds a key-value pair satisfying the given predicate.
+    ///
+    /// The scan stops as soon as a matching key-value pair is found, and the returned
+    /// [`RefGuard`] keeps the bucket containing it shared-locked until dropped.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// let future_insert = hashmap.insert_async(1, 10);
+    /// let future_find = hashmap.find_async(|_, v| *v == 10);
+    /// ```
+    #[inline]
+    pub async fn find_async<P: FnMut(&K, &V) -> bool>(
+        &self,
+        mut pred: P,
+    ) -> Option<RefGuard<'_, K, V>> {
+        self.scan_entries_async(|reader, entry_ref| {
+            if pred(&entry_ref.0, &entry_ref.1) {
+                return ControlFlow::Break(Self::ref_guard(reader, entry_ref));
+            }
+            ControlFlow::Continue(())
+        })
+        .await
+    }
+
+    /// Iterates over all the entries in the [`HashMap`].
+    ///
+    /// This method allows modifying each value.
+    ///
+    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
+    /// visited if they are not removed, however the same key-value pair can be visited more than
+    /// once if the [`HashMap`] gets resized by another thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    ///
+    /// let mut acc = 0;
+    /// hashmap.for_each(|k, v| { acc += *k; *v = 2; });
+    /// assert_eq!(acc, 3);
+    /// assert_eq!(hashmap.read(&1, |_, v| *v).unwrap(), 2);
+    /// assert_eq!(hashmap.read(&2, |_, v| *v).unwrap(), 2);
+    /// ```
+    #[inline]
+    pub fn for_each<F: FnMut(&K, &mut V)>(&self, mut f: F) {
+        self.retain(|k, v| {
+            f(k, v);
+            true
+        });
+    }
+
+    /// Iterates over all the entries in the [`HashMap`].
+    ///
+    /// This method allows modifying each value.
+    ///
+    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
+    /// visited if they are not removed, however the same key-value pair can be visited more than
+    /// once if the [`HashMap`] gets resized by another task.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// let future_insert = hashmap.insert_async(1, 0);
+    /// let future_for_each = hashmap.for_each_async(|k, v| println!("{} {}", k, v));
+    /// ```
+    #[inline]
+    pub async fn for_each_async<F: FnMut(&K, &mut V)>(&self, mut f: F) {
+        self.retain_async(|k, v| {
+            f(k, v);
+            true
+        })
+        .await;
+    }
+
+    /// Retains key-value pairs that satisfy the given predicate.
+    ///
+    /// This method allows modifying each value.
+    ///
+    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
+    /// visited if they are not removed, however the same key-value pair can be visited more than
+    /// once if the [`HashMap`] gets resized by another thread.
+    ///
+    /// It returns the number of entries remaining and removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    /// assert!(hashmap.insert(3, 2).is_ok());
+    ///
+    /// assert_eq!(hashmap.retain(|k, v| *k == 1 && *v == 0), (1, 2));
+    /// ```
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&self, mut filter: F) -> (usize, usize) {
+        let mut num_retained: usize = 0;
+        let mut num_removed: usize = 0;
+
+        let barrier = Barrier::new();
+
+        // An acquire fence is required to correctly load the contents of the array.
+        let mut current_array_ptr = self.array.load(Acquire, &barrier);
+        while let Some(current_array_ref) = current_array_ptr.as_ref() {
+            while !current_array_ref.old_array(&barrier).is_null() {
+                if current_array_ref.partial_rehash::<_, _, _>(
+                    |key| self.hash(key),
+                    |_, _| None,
+                    None,
+                    &barrier,
+                ) == Ok(true)
+                {
+                    break;
+                }
+            }
+
+            for cell_index in 0..current_array_ref.num_cells() {
+                if let Some(locker) = Locker::lock(current_array_ref.cell(cell_index), &barrier) {
+                    let mut iterator = locker.cell().iter(&barrier);
+                    while iterator.next().is_some() {
+                        let (k, v) = iterator.get();
+                        #[allow(clippy::cast_ref_to_mut)]
+                        let retain = filter(k, unsafe { &mut *(v as *const V as *mut V) });
+                        if retain {
+                            num_retained = num_retained.saturating_add(1);
+                        } else {
+                            locker.erase(&mut iterator);
+                            num_removed = num_removed.saturating_add(1);
+                        }
+                    }
+                }
+            }
+
+            let new_current_array_ptr = self.array.load(Acquire, &barrier);
+            if current_array_ptr == new_current_array_ptr {
+                break;
+            }
+            num_retained = 0;
+            current_array_ptr = new_current_array_ptr;
+        }
+
+        self.release_entries(num_removed);
+        if num_removed >= num_retained {
+            self.resize(&barrier);
+        }
+
+        (num_retained, num_removed)
+    }
+
+    /// Retains key-value pairs that satisfy the given predicate.
+    ///
+    /// This method allows modifying each value.
+    ///
+    /// Key-value pairs that have existed since the invocation of the method are guaranteed to be
+    /// visited if they are not removed, however the same key-value pair can be visited more than
+    /// once if the [`HashMap`] gets resized by another task.
+    ///
+    /// It returns the number of entries remaining and removed. It is an asynchronous method
+    /// returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
     /// let future_insert = hashmap.insert_async(1, 0);
     /// let future_retain = hashmap.retain_async(|k, v| *k == 1);
@@ -960,37 +1492,1260 @@ where
                                 break false;
                             }
 
-                            // The `Cell` having been killed means that a new array has been
-                            // allocated.
-                            break true;
-                        };
-                    }
-                    async_wait_pinned.await;
-                };
-                if killed {
-                    break;
-                }
-            }
+                            // The `Cell` having been killed means that a new array has been
+                            // allocated.
+                            break true;
+                        };
+                    }
+                    async_wait_pinned.await;
+                };
+                if killed {
+                    break;
+                }
+            }
+
+            if let Some(new_current_array) = self.array.get_arc(Acquire, &Barrier::new()) {
+                if new_current_array.as_ptr() == current_array.as_ptr() {
+                    break;
+                }
+                num_retained = 0;
+                current_array_holder.replace(new_current_array);
+                continue;
+            }
+            break;
+        }
+
+        self.release_entries(num_removed);
+        if num_removed >= num_retained {
+            self.resize(&Barrier::new());
+        }
+
+        (num_retained, num_removed)
+    }
+
+    /// Clears all the key-value pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert_eq!(hashmap.clear(), 1);
+    /// ```
+    #[inline]
+    pub fn clear(&self) -> usize {
+        self.retain(|_, _| false).1
+    }
+
+    /// Clears all the key-value pairs.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// let future_insert = hashmap.insert_async(1, 0);
+    /// let future_clear = hashmap.clear_async();
+    /// ```
+    #[inline]
+    pub async fn clear_async(&self) -> usize {
+        self.retain_async(|_, _| false).await.1
+    }
+
+    /// Returns the number of entries in the [`HashMap`].
+    ///
+    /// It scans the entire array to calculate the number of valid entries, making its time
+    /// complexity `O(N)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert_eq!(hashmap.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.num_entries(&Barrier::new())
+    }
+
+    /// Returns `true` if the [`HashMap`] is empty.
+    ///
+    /// It scans the entire array to calculate the number of valid entries, making its time
+    /// complexity `O(N)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the capacity of the [`HashMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let hashmap: HashMap<u64, u32, RandomState> = HashMap::new(1000000, RandomState::new());
+    /// assert_eq!(hashmap.capacity(), 1048576);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.num_slots(&Barrier::new())
+    }
+
+    /// Returns a [`Visitor`] that iterates over all the entries in the [`HashMap`].
+    ///
+    /// It is guaranteed to go through all the key-value pairs pertaining in the [`HashMap`] at
+    /// the moment, however the same key-value pair can be visited more than once if the
+    /// [`HashMap`] is being resized.
+    ///
+    /// Each [`RefGuard`] returned by the [`Visitor`] keeps the bucket containing the entry
+    /// shared-locked until it is dropped, and it may outlive the [`Barrier`]; the [`Visitor`]
+    /// itself shared-locks the bucket that it is currently visiting.
+    ///
+    /// It requires the user to supply a reference to a [`Barrier`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::ebr::Barrier;
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 0).is_ok());
+    /// assert!(hashmap.insert(2, 1).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    ///
+    /// let mut iter = hashmap.iter(&barrier);
+    /// let entry = iter.next().unwrap();
+    /// assert!(iter.next().is_some());
+    /// assert!(iter.next().is_none());
+    /// assert!(*entry.key() == 1 || *entry.key() == 2);
+    /// drop(entry);
+    ///
+    /// assert_eq!(hashmap.iter(&barrier).map(|e| *e.key() + u64::from(*e)).sum::<u64>(), 4);
+    /// ```
+    #[inline]
+    pub fn iter<'h, 'b>(&'h self, barrier: &'b Barrier) -> Visitor<'h, 'b, K, V, H> {
+        Visitor {
+            hash_map: self,
+            current_array_ptr: Ptr::null(),
+            current_index: 0,
+            current_entry_iterator: None,
+            current_reader: None,
+            barrier_ref: barrier,
+        }
+    }
+
+    /// Inserts a key-value pair while respecting the maximum number of entries.
+    ///
+    /// If the key exists, `updater` is applied to the entry, or [`InsertError::Occupied`] is
+    /// returned when no `updater` is given.
+    ///
+    /// # Errors
+    ///
+    /// Returns the key, constructor, and updater if locking failed.
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn insert_bounded<FI: FnOnce() -> V, FU: FnOnce(&K, &mut V)>(
+        &self,
+        key: K,
+        constructor: FI,
+        updater: Option<FU>,
+        hash: u64,
+        partial_hash: u8,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<Result<(), InsertError<K, V>>, (K, FI, Option<FU>)> {
+        loop {
+            if let Ok((_, locker, iterator)) =
+                self.acquire::<_>(&key, hash, partial_hash, async_wait, barrier)
+            {
+                if let Some(mut iterator) = iterator {
+                    if let Some(updater) = updater {
+                        let (k, v) = iterator.get_mut(&locker);
+                        updater(k, v);
+                        return Ok(Ok(()));
+                    }
+                    return Ok(Err(InsertError::Occupied(key, constructor())));
+                }
+                if self.reserve_entry(&locker, barrier) {
+                    locker.insert(key, constructor(), partial_hash, barrier);
+                    return Ok(Ok(()));
+                }
+                let policy = match self.limit.as_ref().map(|limit| &limit.overflow) {
+                    Some(Overflow::Evict(policy)) => policy.as_ref(),
+                    _ => return Ok(Err(InsertError::Full(key, constructor()))),
+                };
+
+                // The bucket is empty, therefore the victim has to be found elsewhere.
+                drop(locker);
+                match self.evict_entry(hash, policy, async_wait, barrier) {
+                    Ok(true) => continue,
+                    Ok(false) => return Ok(Err(InsertError::Full(key, constructor()))),
+                    Err(()) => (),
+                }
+            }
+            return Err((key, constructor, updater));
+        }
+    }
+
+    /// Makes room for a new entry that is about to be inserted into the locked bucket.
+    ///
+    /// If the [`HashMap`] is full, an entry in the bucket is evicted if the policy allows.
+    /// Returns `false` if there is no room for the new entry.
+    fn reserve_entry(&self, locker: &Locker<K, V, false>, barrier: &Barrier) -> bool {
+        if let Some(limit) = self.limit.as_ref() {
+            let reserved = limit
+                .num_entries
+                .fetch_update(Relaxed, Relaxed, |n| {
+                    if n < limit.max_entries {
+                        Some(n + 1)
+                    } else {
+                        None
+                    }
+                })
+                .is_ok();
+            if reserved {
+                return true;
+            }
+            if let Overflow::Evict(policy) = &limit.overflow {
+                // The new entry takes the place of the evicted one.
+                return Self::evict_locked(locker, policy.as_ref(), barrier).is_some();
+            }
+            return false;
+        }
+        true
+    }
+
+    /// Gives back the room of the given number of removed entries.
+    fn release_entries(&self, num_entries: usize) {
+        if let Some(limit) = self.limit.as_ref() {
+            if num_entries != 0 {
+                let result = limit.num_entries.fetch_sub(num_entries, Relaxed);
+                debug_assert!(result >= num_entries);
+            }
+        }
+    }
+
+    /// Evicts an entry in another bucket than the one the hash value belongs to.
+    ///
+    /// Returns `false` if there is no entry to evict.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if locking failed.
+    fn evict_entry(
+        &self,
+        hash: u64,
+        policy: &dyn EvictionPolicy<K, V>,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<bool, ()> {
+        let current_array_ref = self.array.load(Acquire, barrier).as_ref().unwrap();
+        if !current_array_ref.old_array(barrier).is_null() {
+            // Entries in the old array are relocated first; the caller will retry.
+            current_array_ref.partial_rehash::<_, _, _>(
+                |key| self.hash(key),
+                &Self::copier,
+                async_wait,
+                barrier,
+            )?;
+            return Ok(true);
+        }
+
+        let num_cells = current_array_ref.num_cells();
+        let start_index = current_array_ref.calculate_cell_index(hash);
+        for i in 1..=num_cells {
+            let cell_ref = current_array_ref.cell((start_index + i) % num_cells);
+            if cell_ref.num_entries() == 0 {
+                continue;
+            }
+            let lock_result = if let Some(&async_wait) = async_wait.as_ref() {
+                Locker::try_lock_or_wait(cell_ref, async_wait, barrier)?
+            } else {
+                Locker::lock(cell_ref, barrier)
+            };
+            if let Some(locker) = lock_result {
+                if Self::evict_locked(&locker, policy, barrier).is_some() {
+                    drop(locker);
+                    self.release_entries(1);
+                    return Ok(true);
+                }
+            } else {
+                // The array has been replaced.
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Evicts the entry in the locked bucket that the policy prefers.
+    fn evict_locked(
+        locker: &Locker<K, V, false>,
+        policy: &dyn EvictionPolicy<K, V>,
+        barrier: &Barrier,
+    ) -> Option<(K, V)> {
+        let mut selected: Option<(usize, &(K, V))> = None;
+        let mut iterator = locker.cell().iter(barrier);
+        let mut position = 0;
+        while iterator.next().is_some() {
+            let candidate = iterator.get();
+            let preferred = match selected {
+                Some((_, current)) => {
+                    policy.prefer((&candidate.0, &candidate.1), (&current.0, &current.1))
+                }
+                None => true,
+            };
+            if preferred {
+                selected.replace((position, candidate));
+            }
+            position += 1;
+        }
+
+        let (position, _) = selected?;
+        let mut iterator = locker.cell().iter(barrier);
+        for _ in 0..=position {
+            iterator.next();
+        }
+        locker.erase(&mut iterator)
+    }
+
+    /// Scans the entries until the closure returns [`ControlFlow::Break`].
+    ///
+    /// The closure is given the [`Reader`] holding the [`Cell`] containing the entry.
+    ///
+    /// [`Cell`]: super::hash_table::cell::Cell
+    fn scan_entries<R, F: FnMut(&Reader<K, V, false>, &(K, V)) -> ControlFlow<R>>(
+        &self,
+        mut scanner: F,
+    ) -> Option<R> {
+        let barrier = Barrier::new();
+
+        // An acquire fence is required to correctly load the contents of the array.
+        let mut current_array_ptr = self.array.load(Acquire, &barrier);
+        while let Some(current_array_ref) = current_array_ptr.as_ref() {
+            while !current_array_ref.old_array(&barrier).is_null() {
+                if current_array_ref.partial_rehash::<_, _, _>(
+                    |key| self.hash(key),
+                    |_, _| None,
+                    None,
+                    &barrier,
+                ) == Ok(true)
+                {
+                    break;
+                }
+            }
+
+            for cell_index in 0..current_array_ref.num_cells() {
+                if let Some(reader) = Reader::lock(current_array_ref.cell(cell_index), &barrier) {
+                    for (entry_ref, _) in reader.cell().iter(&barrier) {
+                        if let ControlFlow::Break(result) = scanner(&reader, entry_ref) {
+                            return Some(result);
+                        }
+                    }
+                }
+            }
+
+            let new_current_array_ptr = self.array.load(Acquire, &barrier);
+            if current_array_ptr == new_current_array_ptr {
+                break;
+            }
+            current_array_ptr = new_current_array_ptr;
+        }
+        None
+    }
+
+    /// Scans the entries until the closure returns [`ControlFlow::Break`].
+    ///
+    /// The closure is given the [`Reader`] holding the [`Cell`] containing the entry.
+    ///
+    /// [`Cell`]: super::hash_table::cell::Cell
+    async fn scan_entries_async<R, F: FnMut(&Reader<K, V, false>, &(K, V)) -> ControlFlow<R>>(
+        &self,
+        mut scanner: F,
+    ) -> Option<R> {
+        // An acquire fence is required to correctly load the contents of the array.
+        let mut current_array_holder = self.array.get_arc(Acquire, &Barrier::new());
+        while let Some(current_array) = current_array_holder.take() {
+            while !current_array.old_array(&Barrier::new()).is_null() {
+                let mut async_wait = AsyncWait::default();
+                let mut async_wait_pinned = Pin::new(&mut async_wait);
+                if current_array.partial_rehash::<_, _, _>(
+                    |key| self.hash(key),
+                    |_, _| None,
+                    Some(async_wait_pinned.mut_ptr()),
+                    &Barrier::new(),
+                ) == Ok(true)
+                {
+                    break;
+                }
+                async_wait_pinned.await;
+            }
+
+            for cell_index in 0..current_array.num_cells() {
+                let killed = loop {
+                    let mut async_wait = AsyncWait::default();
+                    let mut async_wait_pinned = Pin::new(&mut async_wait);
+                    {
+                        let barrier = Barrier::new();
+                        if let Ok(result) = Reader::try_lock_or_wait(
+                            current_array.cell(cell_index),
+                            async_wait_pinned.mut_ptr(),
+                            &barrier,
+                        ) {
+                            if let Some(reader) = result {
+                                for (entry_ref, _) in reader.cell().iter(&barrier) {
+                                    if let ControlFlow::Break(result) = scanner(&reader, entry_ref)
+                                    {
+                                        return Some(result);
+                                    }
+                                }
+                                break false;
+                            }
+
+                            // The `Cell` having been killed means that a new array has been
+                            // allocated.
+                            break true;
+                        };
+                    }
+                    async_wait_pinned.await;
+                };
+                if killed {
+                    break;
+                }
+            }
+
+            if let Some(new_current_array) = self.array.get_arc(Acquire, &Barrier::new()) {
+                if new_current_array.as_ptr() == current_array.as_ptr() {
+                    break;
+                }
+                current_array_holder.replace(new_current_array);
+                continue;
+            }
+            break;
+        }
+        None
+    }
+
+    /// Shared-locks the [`Cell`] being scanned once more, and builds a [`RefGuard`] for the entry.
+    ///
+    /// The [`Cell`] cannot be killed while being scanned, therefore the [`Reader`] can outlive
+    /// the [`Barrier`]; see [`HashMap::acquire_entry`].
+    ///
+    /// [`Cell`]: super::hash_table::cell::Cell
+    fn ref_guard<'h>(reader: &Reader<K, V, false>, entry_ref: &(K, V)) -> RefGuard<'h, K, V> {
+        let entry_ptr: *const (K, V) = entry_ref;
+        let reader: Reader<'h, K, V, false> = unsafe { transmute(reader.clone()) };
+        RefGuard { reader, entry_ptr }
+    }
+
+    /// Acquires the [`Locker`] on the bucket that the key belongs to, and builds an [`Entry`].
+    ///
+    /// The [`Locker`] outlives the supplied [`Barrier`]; a locked [`Cell`] cannot be killed, and
+    /// a [`CellArray`] is only dropped after all its [`Cell`] instances have been killed or the
+    /// [`HashMap`] itself has been dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns the key if locking failed.
+    ///
+    /// [`Cell`]: super::hash_table::cell::Cell
+    fn acquire_entry<'h>(
+        &'h self,
+        key: K,
+        hash: u64,
+        partial_hash: u8,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<Entry<'h, K, V, H>, K> {
+        if let Ok((cell_index, locker, iterator)) =
+            self.acquire::<_>(&key, hash, partial_hash, async_wait, barrier)
+        {
+            let locker: Locker<'h, K, V, false> = unsafe { transmute(locker) };
+            if let Some(iterator) = iterator {
+                let entry_ptr: *const (K, V) = iterator.get();
+                return Ok(Entry::Occupied(OccupiedEntry {
+                    hashmap: self,
+                    cell_index,
+                    locker,
+                    entry_ptr,
+                    partial_hash,
+                }));
+            }
+            return Ok(Entry::Vacant(VacantEntry {
+                hashmap: self,
+                key,
+                cell_index,
+                locker,
+                partial_hash,
+            }));
+        }
+        Err(key)
+    }
+
+    /// Acquires a [`Reader`] on the bucket containing the key, and builds a [`RefGuard`].
+    ///
+    /// See [`HashMap::acquire_entry`] for why the [`Reader`] can outlive the [`Barrier`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if locking failed.
+    fn acquire_reader<'h, Q>(
+        &'h self,
+        key_ref: &Q,
+        hash: u64,
+        partial_hash: u8,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<Option<RefGuard<'h, K, V>>, ()>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        if let Some((Some(reader), entry_ref)) =
+            self.search_entry(key_ref, hash, partial_hash, async_wait, barrier)?
+        {
+            let entry_ptr: *const (K, V) = entry_ref;
+            let reader: Reader<'h, K, V, false> = unsafe { transmute(reader) };
+            return Ok(Some(RefGuard { reader, entry_ptr }));
+        }
+        Ok(None)
+    }
+
+    /// Acquires a [`Locker`] on the bucket containing the key, and builds a [`MutGuard`].
+    ///
+    /// See [`HashMap::acquire_entry`] for why the [`Locker`] can outlive the [`Barrier`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if locking failed.
+    fn acquire_locker<'h, Q>(
+        &'h self,
+        key_ref: &Q,
+        hash: u64,
+        partial_hash: u8,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<Option<MutGuard<'h, K, V>>, ()>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let (_, locker, iterator) =
+            self.acquire::<Q>(key_ref, hash, partial_hash, async_wait, barrier)?;
+        if let Some(iterator) = iterator {
+            let entry_ptr: *const (K, V) = iterator.get();
+            let locker: Locker<'h, K, V, false> = unsafe { transmute(locker) };
+            return Ok(Some(MutGuard { locker, entry_ptr }));
+        }
+        Ok(None)
+    }
+}
+
+impl<K, V> Default for HashMap<K, V, RandomState>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+{
+    /// Creates a [`HashMap`] with the default parameters.
+    ///
+    /// The default hash builder is [`RandomState`], and the default capacity is `64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// let result = hashmap.capacity();
+    /// assert_eq!(result, 64);
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        HashMap {
+            array: AtomicArc::new(CellArray::<K, V, false>::new(
+                Self::default_capacity(),
+                AtomicArc::null(),
+            )),
+            minimum_capacity: Self::default_capacity(),
+            additional_capacity: AtomicUsize::new(0),
+            resize_mutex: AtomicU8::new(0),
+            build_hasher: RandomState::new(),
+            limit: None,
+        }
+    }
+}
+
+impl<K, V, H> HashTable<K, V, H, false> for HashMap<K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    fn hasher(&self) -> &H {
+        &self.build_hasher
+    }
+    fn copier(_: &K, _: &V) -> Option<(K, V)> {
+        None
+    }
+    fn cell_array(&self) -> &AtomicArc<CellArray<K, V, false>> {
+        &self.array
+    }
+    fn minimum_capacity(&self) -> usize {
+        self.minimum_capacity + self.additional_capacity.load(Relaxed)
+    }
+    fn resize_mutex(&self) -> &AtomicU8 {
+        &self.resize_mutex
+    }
+}
+
+/// [`Entry`] represents a single entry in a [`HashMap`] that is either vacant or occupied.
+///
+/// The bucket containing the entry stays locked until the [`Entry`] is dropped.
+pub enum Entry<'h, K, V, H = RandomState>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'h, K, V, H>),
+
+    /// A vacant entry.
+    Vacant(VacantEntry<'h, K, V, H>),
+}
+
+/// [`OccupiedEntry`] is a view into an occupied entry in a [`HashMap`].
+pub struct OccupiedEntry<'h, K, V, H = RandomState>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    hashmap: &'h HashMap<K, V, H>,
+    cell_index: usize,
+    locker: Locker<'h, K, V, false>,
+    entry_ptr: *const (K, V),
+    partial_hash: u8,
+}
+
+/// [`VacantEntry`] is a view into a vacant entry in a [`HashMap`].
+pub struct VacantEntry<'h, K, V, H = RandomState>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    hashmap: &'h HashMap<K, V, H>,
+    key: K,
+    cell_index: usize,
+    locker: Locker<'h, K, V, false>,
+    partial_hash: u8,
+}
+
+impl<'h, K, V, H> Entry<'h, K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    /// Ensures a value is in the entry by inserting the supplied instance if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// hashmap.entry(3).or_insert(7);
+    /// assert_eq!(hashmap.read(&3, |_, v| *v), Some(7));
+    /// ```
+    #[inline]
+    pub fn or_insert(self, val: V) -> OccupiedEntry<'h, K, V, H> {
+        self.or_insert_with(|| val)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the supplied closure if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// hashmap.entry(19).or_insert_with(|| 5);
+    /// assert_eq!(hashmap.read(&19, |_, v| *v), Some(5));
+    /// ```
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, constructor: F) -> OccupiedEntry<'h, K, V, H> {
+        self.or_insert_with_key(|_| constructor())
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the supplied closure if empty.
+    ///
+    /// The reference to the moved key is provided, therefore cloning or copying the key is
+    /// unnecessary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u64> = HashMap::default();
+    ///
+    /// hashmap.entry(11).or_insert_with_key(|k| if *k == 11 { 7 } else { 3 });
+    /// assert_eq!(hashmap.read(&11, |_, v| *v), Some(7));
+    /// ```
+    #[inline]
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(
+        self,
+        constructor: F,
+    ) -> OccupiedEntry<'h, K, V, H> {
+        match self {
+            Self::Occupied(o) => o,
+            Self::Vacant(v) => {
+                let val = constructor(v.key());
+                v.insert_entry(val)
+            }
+        }
+    }
+
+    /// Returns a reference to the key of this entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// assert_eq!(hashmap.entry(31).key(), &31);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn key(&self) -> &K {
+        match self {
+            Self::Occupied(o) => o.key(),
+            Self::Vacant(v) => v.key(),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// hashmap.entry(37).and_modify(|v| { *v += 1 }).or_insert(47);
+    /// assert_eq!(hashmap.read(&37, |_, v| *v), Some(47));
+    ///
+    /// hashmap.entry(37).and_modify(|v| { *v += 1 }).or_insert(3);
+    /// assert_eq!(hashmap.read(&37, |_, v| *v), Some(48));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            Self::Occupied(mut o) => {
+                f(o.get_mut());
+                Self::Occupied(o)
+            }
+            Self::Vacant(_) => self,
+        }
+    }
+
+    /// Sets the value of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// let entry = hashmap.entry(11).insert_entry(17);
+    /// assert_eq!(entry.key(), &11);
+    /// ```
+    #[inline]
+    pub fn insert_entry(self, val: V) -> OccupiedEntry<'h, K, V, H> {
+        match self {
+            Self::Occupied(mut o) => {
+                o.insert(val);
+                o
+            }
+            Self::Vacant(v) => v.insert_entry(val),
+        }
+    }
+}
+
+impl<'h, K, V, H> Entry<'h, K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Default + Sync,
+    H: BuildHasher,
+{
+    /// Ensures a value is in the entry by inserting the default value if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// hashmap.entry(11).or_default();
+    /// assert_eq!(hashmap.read(&11, |_, v| *v), Some(0));
+    /// ```
+    #[inline]
+    pub fn or_default(self) -> OccupiedEntry<'h, K, V, H> {
+        self.or_insert_with(Default::default)
+    }
+}
+
+impl<K, V, H> Debug for Entry<'_, K, V, H>
+where
+    K: 'static + Debug + Eq + Hash + Sync,
+    V: 'static + Debug + Sync,
+    H: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Vacant(v) => f.debug_tuple("Entry").field(v).finish(),
+            Self::Occupied(o) => f.debug_tuple("Entry").field(o).finish(),
+        }
+    }
+}
+
+impl<'h, K, V, H> OccupiedEntry<'h, K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    /// Gets a reference to the key in the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert_eq!(hashmap.entry(29).or_default().key(), &29);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn key(&self) -> &K {
+        &self.entry_ref().0
+    }
+
+    /// Takes ownership of the key and value from the [`HashMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use scc::hash_map::Entry;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// hashmap.entry(11).or_insert(17);
+    ///
+    /// if let Entry::Occupied(o) = hashmap.entry(11) {
+    ///     assert_eq!(o.remove_entry(), (11, 17));
+    /// };
+    /// assert!(!hashmap.contains(&11));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn remove_entry(self) -> (K, V) {
+        let barrier = Barrier::new();
+        let mut iterator = self.iterator(&barrier);
+        let removed = self.locker.extract(&mut iterator);
+        self.hashmap.release_entries(1);
+        let OccupiedEntry {
+            hashmap,
+            cell_index,
+            locker,
+            ..
+        } = self;
+        if (cell_index % CELL_LEN) == 0 && locker.cell().num_entries() < CELL_LEN / 16 {
+            drop(locker);
+            if let Some(current_array_ref) = hashmap.array.load(Acquire, &barrier).as_ref() {
+                if current_array_ref.old_array(&barrier).is_null()
+                    && current_array_ref.num_entries() > hashmap.minimum_capacity()
+                {
+                    hashmap.try_shrink(current_array_ref, cell_index, &barrier);
+                }
+            }
+        }
+        removed
+    }
+
+    /// Gets a reference to the value in the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use scc::hash_map::Entry;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// hashmap.entry(19).or_insert(11);
+    ///
+    /// if let Entry::Occupied(o) = hashmap.entry(19) {
+    ///     assert_eq!(o.get(), &11);
+    /// };
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn get(&self) -> &V {
+        &self.entry_ref().1
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use scc::hash_map::Entry;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// hashmap.entry(37).or_insert(11);
+    ///
+    /// if let Entry::Occupied(mut o) = hashmap.entry(37) {
+    ///     *o.get_mut() += 18;
+    ///     assert_eq!(*o.get(), 29);
+    /// }
+    ///
+    /// assert_eq!(hashmap.read(&37, |_, v| *v), Some(29));
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        // The presence of `locker` prevents the entry from being modified outside it.
+        unsafe { &mut (*self.entry_ptr.cast_mut()).1 }
+    }
+
+    /// Sets the value of the entry, and returns the old value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use scc::hash_map::Entry;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// hashmap.entry(37).or_insert(11);
+    ///
+    /// if let Entry::Occupied(mut o) = hashmap.entry(37) {
+    ///     assert_eq!(o.insert(17), 11);
+    /// }
+    ///
+    /// assert_eq!(hashmap.read(&37, |_, v| *v), Some(17));
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, val: V) -> V {
+        std::mem::replace(self.get_mut(), val)
+    }
+
+    /// Takes the value out of the entry, and returns it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use scc::hash_map::Entry;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// hashmap.entry(11).or_insert(17);
+    ///
+    /// if let Entry::Occupied(o) = hashmap.entry(11) {
+    ///     assert_eq!(o.remove(), 17);
+    /// };
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Returns a reference to the key-value pair.
+    fn entry_ref(&self) -> &(K, V) {
+        unsafe { &*self.entry_ptr }
+    }
+
+    /// Gets an [`EntryIterator`] pointing to the key-value pair.
+    fn iterator<'b>(&self, barrier: &'b Barrier) -> EntryIterator<'b, K, V, false>
+    where
+        'h: 'b,
+    {
+        self.locker
+            .cell()
+            .get(&self.entry_ref().0, self.partial_hash, barrier)
+            .unwrap()
+    }
+}
+
+impl<K, V, H> Debug for OccupiedEntry<'_, K, V, H>
+where
+    K: 'static + Debug + Eq + Hash + Sync,
+    V: 'static + Debug + Sync,
+    H: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish_non_exhaustive()
+    }
+}
+
+// SAFETY: `entry_ptr` points to a key-value pair in the bucket locked by `locker`, and the
+// lock is held as long as the entry is alive; therefore it is safe to send and share the entry
+// as long as the key-value pair can be sent and shared.
+unsafe impl<K, V, H> Send for OccupiedEntry<'_, K, V, H>
+where
+    K: 'static + Eq + Hash + Send + Sync,
+    V: 'static + Send + Sync,
+    H: BuildHasher + Sync,
+{
+}
+
+unsafe impl<K, V, H> Sync for OccupiedEntry<'_, K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher + Sync,
+{
+}
+
+impl<'h, K, V, H> VacantEntry<'h, K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    /// Gets a reference to the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    /// assert_eq!(hashmap.entry(11).key(), &11);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Takes ownership of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use scc::hash_map::Entry;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// if let Entry::Vacant(v) = hashmap.entry(17) {
+    ///     assert_eq!(v.into_key(), 17);
+    /// };
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Sets the value of the entry with its key, and returns an [`OccupiedEntry`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`HashMap`] has reached its maximum number of entries and no entry in the
+    /// bucket can be evicted; use [`VacantEntry::try_insert_entry`] to handle the case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashMap;
+    /// use scc::hash_map::Entry;
+    ///
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// if let Entry::Vacant(o) = hashmap.entry(19) {
+    ///     o.insert_entry(29);
+    /// }
+    ///
+    /// assert_eq!(hashmap.read(&19, |_, v| *v), Some(29));
+    /// ```
+    #[inline]
+    pub fn insert_entry(self, val: V) -> OccupiedEntry<'h, K, V, H> {
+        let VacantEntry {
+            hashmap,
+            key,
+            cell_index,
+            locker,
+            partial_hash,
+        } = self;
+        let barrier = Barrier::new();
+        assert!(
+            hashmap.reserve_entry(&locker, &barrier),
+            "the HashMap is full"
+        );
+        let entry_ptr: *const (K, V) = locker.insert(key, val, partial_hash, &barrier);
+        OccupiedEntry {
+            hashmap,
+            cell_index,
+            locker,
+            entry_ptr,
+            partial_hash,
+        }
+    }
+
+    /// Sets the value of the entry with its key while respecting the maximum number of entries,
+    /// and returns an [`OccupiedEntry`].
+    ///
+    /// If the [`HashMap`] is full, only an entry in the same bucket can be evicted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InsertError::Full`] along with the key and the value if the [`HashMap`] has
+    /// reached its maximum number of entries and cannot evict any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::hash_map::{Entry, InsertError, Overflow};
+    /// use scc::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let hashmap: HashMap<u64, u32> =
+    ///     HashMap::with_limit(64, 1, Overflow::Reject, RandomState::new());
+    ///
+    /// if let Entry::Vacant(v) = hashmap.entry(19) {
+    ///     assert_eq!(v.try_insert_entry(29).unwrap().get(), &29);
+    /// }
+    /// if let Entry::Vacant(v) = hashmap.entry(23) {
+    ///     assert_eq!(v.try_insert_entry(31).err(), Some(InsertError::Full(23, 31)));
+    /// };
+    /// ```
+    #[inline]
+    pub fn try_insert_entry(self, val: V) -> Result<OccupiedEntry<'h, K, V, H>, InsertError<K, V>> {
+        let VacantEntry {
+            hashmap,
+            key,
+            cell_index,
+            locker,
+            partial_hash,
+        } = self;
+        let barrier = Barrier::new();
+        if !hashmap.reserve_entry(&locker, &barrier) {
+            return Err(InsertError::Full(key, val));
+        }
+        let entry_ptr: *const (K, V) = locker.insert(key, val, partial_hash, &barrier);
+        Ok(OccupiedEntry {
+            hashmap,
+            cell_index,
+            locker,
+            entry_ptr,
+            partial_hash,
+        })
+    }
+}
+
+impl<K, V, H> Debug for VacantEntry<'_, K, V, H>
+where
+    K: 'static + Debug + Eq + Hash + Sync,
+    V: 'static + Debug + Sync,
+    H: BuildHasher,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(self.key()).finish()
+    }
+}
+
+// SAFETY: the bucket is locked by `locker` as long as the entry is alive, and the entry owns
+// the key; therefore it is safe to send and share the entry under the same conditions as
+// [`OccupiedEntry`].
+unsafe impl<K, V, H> Send for VacantEntry<'_, K, V, H>
+where
+    K: 'static + Eq + Hash + Send + Sync,
+    V: 'static + Send + Sync,
+    H: BuildHasher + Sync,
+{
+}
+
+unsafe impl<K, V, H> Sync for VacantEntry<'_, K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher + Sync,
+{
+}
 
-            if let Some(new_current_array) = self.array.get_arc(Acquire, &Barrier::new()) {
-                if new_current_array.as_ptr() == current_array.as_ptr() {
-                    break;
-                }
-                num_retained = 0;
-                current_array_holder.replace(new_current_array);
-                continue;
-            }
-            break;
-        }
+/// [`RefGuard`] keeps the bucket containing the key-value pair shared-locked, and provides
+/// read access to the value.
+pub struct RefGuard<'h, K, V>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+{
+    reader: Reader<'h, K, V, false>,
+    entry_ptr: *const (K, V),
+}
 
-        if num_removed >= num_retained {
-            self.resize(&Barrier::new());
+impl<'h, K, V> RefGuard<'h, K, V>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+{
+    /// Creates a new [`RefGuard`] from a [`Reader`] protecting the entry.
+    pub(crate) fn new(reader: Reader<'h, K, V, false>, entry_ref: &(K, V)) -> RefGuard<'h, K, V> {
+        RefGuard {
+            reader,
+            entry_ptr: entry_ref,
         }
-
-        (num_retained, num_removed)
     }
 
-    /// Clears all the key-value pairs.
+    /// Returns a reference to the key.
     ///
     /// # Examples
     ///
@@ -999,17 +2754,16 @@ where
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
-    /// assert!(hashmap.insert(1, 0).is_ok());
-    /// assert_eq!(hashmap.clear(), 1);
+    /// assert!(hashmap.insert(1, 10).is_ok());
+    /// assert_eq!(*hashmap.get(&1).unwrap().key(), 1);
     /// ```
     #[inline]
-    pub fn clear(&self) -> usize {
-        self.retain(|_, _| false).1
+    #[must_use]
+    pub fn key(&self) -> &K {
+        &self.entry_ref().0
     }
 
-    /// Clears all the key-value pairs.
-    ///
-    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    /// Returns a reference to the value.
     ///
     /// # Examples
     ///
@@ -1018,18 +2772,79 @@ where
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
-    /// let future_insert = hashmap.insert_async(1, 0);
-    /// let future_clear = hashmap.clear_async();
+    /// assert!(hashmap.insert(1, 10).is_ok());
+    /// assert_eq!(*hashmap.get(&1).unwrap().get(), 10);
     /// ```
     #[inline]
-    pub async fn clear_async(&self) -> usize {
-        self.retain_async(|_, _| false).await.1
+    #[must_use]
+    pub fn get(&self) -> &V {
+        &self.entry_ref().1
     }
 
-    /// Returns the number of entries in the [`HashMap`].
-    ///
-    /// It scans the entire array to calculate the number of valid entries, making its time
-    /// complexity `O(N)`.
+    /// Returns a reference to the key-value pair.
+    fn entry_ref(&self) -> &(K, V) {
+        debug_assert!(!self.reader.cell().killed());
+        unsafe { &*self.entry_ptr }
+    }
+}
+
+impl<K, V> Deref for RefGuard<'_, K, V>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+{
+    type Target = V;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl<K, V> Debug for RefGuard<'_, K, V>
+where
+    K: 'static + Debug + Eq + Hash + Sync,
+    V: 'static + Debug + Sync,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RefGuard")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+unsafe impl<K, V> Send for RefGuard<'_, K, V>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+{
+}
+
+unsafe impl<K, V> Sync for RefGuard<'_, K, V>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+{
+}
+
+/// [`MutGuard`] keeps the bucket containing the key-value pair exclusively locked, and
+/// provides read and write access to the value.
+pub struct MutGuard<'h, K, V>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+{
+    locker: Locker<'h, K, V, false>,
+    entry_ptr: *const (K, V),
+}
+
+impl<K, V> MutGuard<'_, K, V>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+{
+    /// Returns a reference to the key.
     ///
     /// # Examples
     ///
@@ -1038,18 +2853,16 @@ where
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
-    /// assert!(hashmap.insert(1, 0).is_ok());
-    /// assert_eq!(hashmap.len(), 1);
+    /// assert!(hashmap.insert(1, 10).is_ok());
+    /// assert_eq!(*hashmap.get_mut(&1).unwrap().key(), 1);
     /// ```
     #[inline]
-    pub fn len(&self) -> usize {
-        self.num_entries(&Barrier::new())
+    #[must_use]
+    pub fn key(&self) -> &K {
+        &self.entry_ref().0
     }
 
-    /// Returns `true` if the [`HashMap`] is empty.
-    ///
-    /// It scans the entire array to calculate the number of valid entries, making its time
-    /// complexity `O(N)`.
+    /// Returns a reference to the value.
     ///
     /// # Examples
     ///
@@ -1058,87 +2871,218 @@ where
     ///
     /// let hashmap: HashMap<u64, u32> = HashMap::default();
     ///
-    /// assert!(hashmap.is_empty());
+    /// assert!(hashmap.insert(1, 10).is_ok());
+    /// assert_eq!(*hashmap.get_mut(&1).unwrap().get(), 10);
     /// ```
     #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    #[must_use]
+    pub fn get(&self) -> &V {
+        &self.entry_ref().1
     }
 
-    /// Returns the capacity of the [`HashMap`].
+    /// Returns a mutable reference to the value.
     ///
     /// # Examples
     ///
     /// ```
     /// use scc::HashMap;
-    /// use std::collections::hash_map::RandomState;
     ///
-    /// let hashmap: HashMap<u64, u32, RandomState> = HashMap::new(1000000, RandomState::new());
-    /// assert_eq!(hashmap.capacity(), 1048576);
+    /// let hashmap: HashMap<u64, u32> = HashMap::default();
+    ///
+    /// assert!(hashmap.insert(1, 10).is_ok());
+    /// *hashmap.get_mut(&1).unwrap().get_mut() = 11;
+    /// assert_eq!(hashmap.read(&1, |_, v| *v).unwrap(), 11);
     /// ```
     #[inline]
-    pub fn capacity(&self) -> usize {
-        self.num_slots(&Barrier::new())
+    pub fn get_mut(&mut self) -> &mut V {
+        debug_assert!(!self.locker.cell().killed());
+
+        // The presence of `locker` prevents the entry from being modified outside it.
+        unsafe { &mut (*self.entry_ptr.cast_mut()).1 }
+    }
+
+    /// Returns a reference to the key-value pair.
+    fn entry_ref(&self) -> &(K, V) {
+        debug_assert!(!self.locker.cell().killed());
+        unsafe { &*self.entry_ptr }
     }
 }
 
-impl<K, V> Default for HashMap<K, V, RandomState>
+impl<K, V> Deref for MutGuard<'_, K, V>
 where
     K: 'static + Eq + Hash + Sync,
     V: 'static + Sync,
 {
-    /// Creates a [`HashMap`] with the default parameters.
-    ///
-    /// The default hash builder is [`RandomState`], and the default capacity is `64`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use scc::HashMap;
-    ///
-    /// let hashmap: HashMap<u64, u32> = HashMap::default();
-    ///
-    /// let result = hashmap.capacity();
-    /// assert_eq!(result, 64);
-    /// ```
+    type Target = V;
+
     #[inline]
-    fn default() -> Self {
-        HashMap {
-            array: AtomicArc::new(CellArray::<K, V, false>::new(
-                Self::default_capacity(),
-                AtomicArc::null(),
-            )),
-            minimum_capacity: Self::default_capacity(),
-            additional_capacity: AtomicUsize::new(0),
-            resize_mutex: AtomicU8::new(0),
-            build_hasher: RandomState::new(),
-        }
+    fn deref(&self) -> &Self::Target {
+        self.get()
     }
 }
 
-impl<K, V, H> HashTable<K, V, H, false> for HashMap<K, V, H>
+impl<K, V> DerefMut for MutGuard<'_, K, V>
 where
     K: 'static + Eq + Hash + Sync,
     V: 'static + Sync,
-    H: BuildHasher,
 {
-    fn hasher(&self) -> &H {
-        &self.build_hasher
-    }
-    fn copier(_: &K, _: &V) -> Option<(K, V)> {
-        None
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.get_mut()
     }
-    fn cell_array(&self) -> &AtomicArc<CellArray<K, V, false>> {
-        &self.array
+}
+
+impl<K, V> Debug for MutGuard<'_, K, V>
+where
+    K: 'static + Debug + Eq + Hash + Sync,
+    V: 'static + Debug + Sync,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MutGuard")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish()
     }
-    fn minimum_capacity(&self) -> usize {
-        self.minimum_capacity + self.additional_capacity.load(Relaxed)
+}
+
+unsafe impl<K, V> Send for MutGuard<'_, K, V>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Send + Sync,
+{
+}
+
+unsafe impl<K, V> Sync for MutGuard<'_, K, V>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+{
+}
+
+/// [`Visitor`] traverses all the key-value pairs in the [`HashMap`].
+///
+/// It implements the [`Iterator`] trait yielding a [`RefGuard`] for each key-value pair.
+pub struct Visitor<'h, 'b, K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    hash_map: &'h HashMap<K, V, H>,
+    current_array_ptr: Ptr<'b, CellArray<K, V, false>>,
+    current_index: usize,
+    current_entry_iterator: Option<EntryIterator<'b, K, V, false>>,
+    current_reader: Option<Reader<'b, K, V, false>>,
+    barrier_ref: &'b Barrier,
+}
+
+impl<K, V, H> Visitor<'_, '_, K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    /// Shared-locks the [`Cell`] at the current index, and starts iterating over it.
+    ///
+    /// The [`Cell`] being killed means that its entries have been moved to the new array.
+    ///
+    /// [`Cell`]: super::hash_table::cell::Cell
+    fn lock_current_cell(&mut self) {
+        self.current_entry_iterator.take();
+        self.current_reader.take();
+        if let Some(array_ref) = self.current_array_ptr.as_ref() {
+            if let Some(reader) = Reader::lock(array_ref.cell(self.current_index), self.barrier_ref)
+            {
+                self.current_entry_iterator
+                    .replace(reader.cell().iter(self.barrier_ref));
+                self.current_reader.replace(reader);
+            }
+        }
     }
-    fn resize_mutex(&self) -> &AtomicU8 {
-        &self.resize_mutex
+}
+
+impl<'h, K, V, H> Iterator for Visitor<'h, '_, K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+    type Item = RefGuard<'h, K, V>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_index == usize::MAX {
+            // Fused.
+            return None;
+        }
+        if self.current_array_ptr.is_null() {
+            // Start scanning.
+            let current_array_ptr = self.hash_map.array.load(Acquire, self.barrier_ref);
+            let current_array_ref = current_array_ptr.as_ref().unwrap();
+            let old_array_ptr = current_array_ref.old_array(self.barrier_ref);
+            self.current_array_ptr = if old_array_ptr.is_null() {
+                current_array_ptr
+            } else {
+                old_array_ptr
+            };
+            self.lock_current_cell();
+        }
+        loop {
+            if let Some(iterator) = self.current_entry_iterator.as_mut() {
+                // Go to the next entry in the Cell.
+                if let Some((entry_ref, _)) = iterator.next() {
+                    // The `Cell` cannot be killed as the `Visitor` is holding a `Reader`,
+                    // therefore sharing the `Reader` always succeeds, and the `Reader` can
+                    // outlive the `Barrier` for the same reason as in `HashMap::get`.
+                    let reader = self.current_reader.as_ref().unwrap().clone();
+                    let reader: Reader<'h, K, V, false> = unsafe { transmute(reader) };
+                    let entry_ptr: *const (K, V) = entry_ref;
+                    return Some(RefGuard { reader, entry_ptr });
+                }
+            }
+            // Go to the next Cell.
+            let array_ref = self.current_array_ptr.as_ref().unwrap();
+            self.current_index += 1;
+            if self.current_index == array_ref.num_cells() {
+                self.current_entry_iterator.take();
+                self.current_reader.take();
+                let current_array_ptr = self.hash_map.array.load(Acquire, self.barrier_ref);
+                if self.current_array_ptr == current_array_ptr {
+                    // Finished scanning the entire array.
+                    self.current_index = usize::MAX;
+                    break;
+                }
+                let current_array_ref = current_array_ptr.as_ref().unwrap();
+                let old_array_ptr = current_array_ref.old_array(self.barrier_ref);
+                if self.current_array_ptr == old_array_ptr {
+                    // Starts scanning the current array.
+                    self.current_array_ptr = current_array_ptr;
+                    self.current_index = 0;
+                    self.lock_current_cell();
+                    continue;
+                }
+                // Start from the very beginning.
+                self.current_array_ptr = if old_array_ptr.is_null() {
+                    current_array_ptr
+                } else {
+                    old_array_ptr
+                };
+                self.current_index = 0;
+                self.lock_current_cell();
+                continue;
+            }
+            self.lock_current_cell();
+        }
+        None
     }
 }
 
+impl<K, V, H> FusedIterator for Visitor<'_, '_, K, V, H>
+where
+    K: 'static + Eq + Hash + Sync,
+    V: 'static + Sync,
+    H: BuildHasher,
+{
+}
+
 /// [`Ticket`] keeps the increased minimum capacity of the [`HashMap`] during its lifetime.
 ///
 /// The minimum capacity is lowered when the [`Ticket`] is dropped, thereby allowing unused
@@ -1168,3 +3112,75 @@ where
         debug_assert!(result >= self.increment);
     }
 }
+
+/// [`EvictionPolicy`] chooses the entry to evict when a bounded [`HashMap`] is full.
+///
+/// It is implemented for closures taking the candidate and the currently selected entry.
+pub trait EvictionPolicy<K, V>: Send + Sync {
+    /// Returns `true` if `candidate` should be evicted rather than `selected`.
+    fn prefer(&self, candidate: (&K, &V), selected: (&K, &V)) -> bool;
+}
+
+impl<K, V, F> EvictionPolicy<K, V> for F
+where
+    F: Fn((&K, &V), (&K, &V)) -> bool + Send + Sync,
+{
+    #[inline]
+    fn prefer(&self, candidate: (&K, &V), selected: (&K, &V)) -> bool {
+        self(candidate, selected)
+    }
+}
+
+/// [`Overflow`] determines what a bounded [`HashMap`] does when it is full.
+pub enum Overflow<K, V> {
+    /// Rejects the new entry.
+    Reject,
+
+    /// Evicts an entry chosen by the [`EvictionPolicy`] to make room for the new entry.
+    Evict(Box<dyn EvictionPolicy<K, V>>),
+}
+
+impl<K, V> Debug for Overflow<K, V> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reject => f.write_str("Reject"),
+            Self::Evict(_) => f.write_str("Evict"),
+        }
+    }
+}
+
+/// [`InsertError`] is the error type of [`HashMap::try_insert`].
+#[derive(Debug, Eq, PartialEq)]
+pub enum InsertError<K, V> {
+    /// The key exists.
+    Occupied(K, V),
+
+    /// The [`HashMap`] has reached its maximum number of entries.
+    Full(K, V),
+}
+
+impl<K, V> InsertError<K, V> {
+    /// Returns the key-value pair that could not be inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::hash_map::InsertError;
+    ///
+    /// assert_eq!(InsertError::Full(1, 2).into_inner(), (1, 2));
+    /// ```
+    #[inline]
+    pub fn into_inner(self) -> (K, V) {
+        match self {
+            Self::Occupied(key, val) | Self::Full(key, val) => (key, val),
+        }
+    }
+}
+
+/// The maximum number of entries of a bounded [`HashMap`].
+struct Limit<K, V> {
+    max_entries: usize,
+    num_entries: AtomicUsize,
+    overflow: Overflow<K, V>,
+}
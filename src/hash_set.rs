@@ -1,10 +1,12 @@
 //! [`HashSet`] is a concurrent and asynchronous hash set.
 
+use super::ebr::Barrier;
 use super::HashMap;
 
 use std::borrow::Borrow;
 use std::collections::hash_map::RandomState;
 use std::hash::{BuildHasher, Hash};
+use std::ops::ControlFlow;
 
 /// Scalable concurrent hash set.
 ///
@@ -318,6 +320,33 @@ where
         self.map.contains_async(key).await
     }
 
+    /// Returns a [`Visitor`] that iterates over all the keys in the [`HashSet`].
+    ///
+    /// It is guaranteed to go through all the keys pertaining in the [`HashSet`] at the moment,
+    /// however the same key can be visited more than once if the [`HashSet`] is being resized.
+    ///
+    /// Each [`RefGuard`] returned by the [`Visitor`] keeps the bucket containing the key
+    /// shared-locked until it is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::ebr::Barrier;
+    /// use scc::HashSet;
+    ///
+    /// let hashset: HashSet<u64> = HashSet::default();
+    ///
+    /// assert!(hashset.insert(1).is_ok());
+    /// assert!(hashset.insert(2).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert_eq!(hashset.iter(&barrier).map(|k| *k.key()).sum::<u64>(), 3);
+    /// ```
+    #[inline]
+    pub fn iter<'h, 'b>(&'h self, barrier: &'b Barrier) -> Visitor<'h, 'b, K, H> {
+        self.map.iter(barrier)
+    }
+
     /// Scans all the keys.
     ///
     /// Keys that have existed since the invocation of the method are guaranteed to be visited if
@@ -364,6 +393,150 @@ where
         self.map.scan_async(|k, _| scanner(k)).await;
     }
 
+    /// Scans the keys until the closure returns [`ControlFlow::Break`].
+    ///
+    /// Returns the value carried by [`ControlFlow::Break`], or `None` if all the keys have been
+    /// visited. The same guarantees as [`HashSet::scan`] apply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let hashset: HashSet<usize> = HashSet::default();
+    ///
+    /// assert!(hashset.insert(1).is_ok());
+    /// assert!(hashset.insert(2).is_ok());
+    ///
+    /// let result = hashset.scan_while(|k| {
+    ///     if *k == 2 {
+    ///         ControlFlow::Break(*k)
+    ///     } else {
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// });
+    /// assert_eq!(result, Some(2));
+    /// ```
+    #[inline]
+    pub fn scan_while<R, F: FnMut(&K) -> ControlFlow<R>>(&self, mut scanner: F) -> Option<R> {
+        self.map.scan_while(|k, ()| scanner(k))
+    }
+
+    /// Scans the keys until the closure returns [`ControlFlow::Break`].
+    ///
+    /// Returns the value carried by [`ControlFlow::Break`], or `None` if all the keys have been
+    /// visited. The same guarantees as [`HashSet::scan_async`] apply.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    /// use std::ops::ControlFlow;
+    ///
+    /// let hashset: HashSet<usize> = HashSet::default();
+    ///
+    /// let future_insert = hashset.insert_async(1);
+    /// let future_scan_while = hashset.scan_while_async(|k| ControlFlow::Break(*k));
+    /// ```
+    #[inline]
+    pub async fn scan_while_async<R, F: FnMut(&K) -> ControlFlow<R>>(
+        &self,
+        mut scanner: F,
+    ) -> Option<R> {
+        self.map.scan_while_async(|k, ()| scanner(k)).await
+    }
+
+    /// Checks if any key satisfies the given predicate.
+    ///
+    /// The scan stops as soon as a matching key is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let hashset: HashSet<u64> = HashSet::default();
+    ///
+    /// assert!(hashset.insert(1).is_ok());
+    /// assert!(hashset.insert(2).is_ok());
+    ///
+    /// assert!(hashset.any(|k| *k % 2 == 0));
+    /// assert!(!hashset.any(|k| *k == 3));
+    /// ```
+    #[inline]
+    pub fn any<P: FnMut(&K) -> bool>(&self, mut pred: P) -> bool {
+        self.map.any(|k, ()| pred(k))
+    }
+
+    /// Checks if any key satisfies the given predicate.
+    ///
+    /// The scan stops as soon as a matching key is found.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let hashset: HashSet<u64> = HashSet::default();
+    ///
+    /// let future_insert = hashset.insert_async(1);
+    /// let future_any = hashset.any_async(|k| *k == 1);
+    /// ```
+    #[inline]
+    pub async fn any_async<P: FnMut(&K) -> bool>(&self, mut pred: P) -> bool {
+        self.map.any_async(|k, ()| pred(k)).await
+    }
+
+    /// Finds a key satisfying the given predicate.
+    ///
+    /// The scan stops as soon as a matching key is found, and the returned [`RefGuard`] keeps
+    /// the bucket containing it shared-locked until dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let hashset: HashSet<u64> = HashSet::default();
+    ///
+    /// assert!(hashset.insert(1).is_ok());
+    /// assert!(hashset.insert(2).is_ok());
+    ///
+    /// assert_eq!(*hashset.find(|k| *k % 2 == 0).unwrap().key(), 2);
+    /// assert!(hashset.find(|k| *k == 3).is_none());
+    /// ```
+    #[inline]
+    pub fn find<P: FnMut(&K) -> bool>(&self, mut pred: P) -> Option<RefGuard<'_, K>> {
+        self.map.find(|k, ()| pred(k))
+    }
+
+    /// Finds a key satisfying the given predicate.
+    ///
+    /// The scan stops as soon as a matching key is found, and the returned [`RefGuard`] keeps
+    /// the bucket containing it shared-locked until dropped.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let hashset: HashSet<u64> = HashSet::default();
+    ///
+    /// let future_insert = hashset.insert_async(1);
+    /// let future_find = hashset.find_async(|k| *k == 1);
+    /// ```
+    #[inline]
+    pub async fn find_async<P: FnMut(&K) -> bool>(&self, mut pred: P) -> Option<RefGuard<'_, K>> {
+        self.map.find_async(|k, ()| pred(k)).await
+    }
+
     /// Iterates over all the keys in the [`HashSet`].
     ///
     /// Keys that have existed since the invocation of the method are guaranteed to be visited if
@@ -465,6 +638,187 @@ where
         self.map.retain_async(|k, _| filter(k)).await
     }
 
+    /// Extends the [`HashSet`] with the supplied keys.
+    ///
+    /// Returns the number of newly inserted keys; keys that already exist are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let hashset: HashSet<u64> = HashSet::default();
+    ///
+    /// assert!(hashset.insert(1).is_ok());
+    /// assert_eq!(hashset.extend([1, 2, 3]), 2);
+    /// assert_eq!(hashset.len(), 3);
+    /// ```
+    #[inline]
+    pub fn extend<I: IntoIterator<Item = K>>(&self, keys: I) -> usize {
+        keys.into_iter().filter_map(|k| self.insert(k).ok()).count()
+    }
+
+    /// Extends the [`HashSet`] with the supplied keys.
+    ///
+    /// Returns the number of newly inserted keys. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let hashset: HashSet<u64> = HashSet::default();
+    ///
+    /// let future_extend = hashset.extend_async([1, 2, 3]);
+    /// ```
+    #[inline]
+    pub async fn extend_async<I: IntoIterator<Item = K>>(&self, keys: I) -> usize {
+        let mut num_inserted = 0;
+        for key in keys {
+            if self.insert_async(key).await.is_ok() {
+                num_inserted += 1;
+            }
+        }
+        num_inserted
+    }
+
+    /// Retains keys that are also contained in `other`.
+    ///
+    /// Keys to remove are determined while `self` is scanned, and they are removed afterwards
+    /// without holding any lock on `other`; therefore, it is safe to invoke the method on two
+    /// [`HashSet`] instances against each other concurrently. A key that is inserted into `other`
+    /// after having been examined may still be removed.
+    ///
+    /// It returns the number of keys remaining and removed, and a key can be counted more than
+    /// once if the [`HashSet`] gets resized by another thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let a: HashSet<u64> = HashSet::default();
+    /// let b: HashSet<u64> = HashSet::default();
+    ///
+    /// assert_eq!(a.extend([1, 2, 3]), 3);
+    /// assert_eq!(b.extend([2, 3, 4]), 3);
+    ///
+    /// assert_eq!(a.retain_in(&b), (2, 1));
+    /// assert!(!a.contains(&1));
+    /// ```
+    #[inline]
+    pub fn retain_in<S: BuildHasher>(&self, other: &HashSet<K, S>) -> (usize, usize)
+    where
+        K: Clone,
+    {
+        let mut num_retained = 0;
+        let mut to_remove = Vec::new();
+        self.scan(|k| {
+            if other.contains(k) {
+                num_retained += 1;
+            } else {
+                to_remove.push(k.clone());
+            }
+        });
+        let num_removed = to_remove
+            .iter()
+            .filter(|k| self.remove(*k).is_some())
+            .count();
+        (num_retained, num_removed)
+    }
+
+    /// Returns `true` if all the keys in `self` are contained in `other`.
+    ///
+    /// Each key in `self` is looked up in `other` while the bucket containing the key is
+    /// shared-locked, therefore keys concurrently inserted into or removed from either of the
+    /// sets may or may not be taken into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let a: HashSet<u64> = HashSet::default();
+    /// let b: HashSet<u64> = HashSet::default();
+    ///
+    /// assert_eq!(a.extend([1, 2]), 2);
+    /// assert_eq!(b.extend([1, 2, 3]), 3);
+    ///
+    /// assert!(a.is_subset(&b));
+    /// assert!(!b.is_subset(&a));
+    /// ```
+    #[inline]
+    pub fn is_subset<S: BuildHasher>(&self, other: &HashSet<K, S>) -> bool {
+        self.scan_while(|k| {
+            if other.contains(k) {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        })
+        .is_none()
+    }
+
+    /// Returns `true` if `self` and `other` have no keys in common.
+    ///
+    /// The same guarantees as [`HashSet::is_subset`] apply.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let a: HashSet<u64> = HashSet::default();
+    /// let b: HashSet<u64> = HashSet::default();
+    ///
+    /// assert_eq!(a.extend([1, 2]), 2);
+    /// assert_eq!(b.extend([3, 4]), 2);
+    /// assert!(a.is_disjoint(&b));
+    ///
+    /// assert!(b.insert(2).is_ok());
+    /// assert!(!a.is_disjoint(&b));
+    /// ```
+    #[inline]
+    pub fn is_disjoint<S: BuildHasher>(&self, other: &HashSet<K, S>) -> bool {
+        self.scan_while(|k| {
+            if other.contains(k) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .is_none()
+    }
+
+    /// Returns the number of keys in both `self` and `other`.
+    ///
+    /// The same guarantees as [`HashSet::is_subset`] apply, and a key can be counted more than
+    /// once if `self` gets resized by another thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::HashSet;
+    ///
+    /// let a: HashSet<u64> = HashSet::default();
+    /// let b: HashSet<u64> = HashSet::default();
+    ///
+    /// assert_eq!(a.extend([1, 2, 3]), 3);
+    /// assert_eq!(b.extend([2, 3, 4]), 3);
+    /// assert_eq!(a.intersection_count(&b), 2);
+    /// ```
+    #[inline]
+    pub fn intersection_count<S: BuildHasher>(&self, other: &HashSet<K, S>) -> usize {
+        let mut count = 0;
+        self.scan(|k| {
+            if other.contains(k) {
+                count += 1;
+            }
+        });
+        count
+    }
+
     /// Clears all the keys.
     ///
     /// # Examples
@@ -585,3 +939,11 @@ impl<K: 'static + Eq + Hash + Sync> Default for HashSet<K, RandomState> {
 /// The minimum capacity is lowered when the [`Ticket`] is dropped, thereby allowing unused
 /// memory to be reclaimed.
 pub type Ticket<'h, K, H> = super::hash_map::Ticket<'h, K, (), H>;
+
+/// [`RefGuard`] keeps the bucket containing a key shared-locked during its lifetime.
+pub type RefGuard<'h, K> = super::hash_map::RefGuard<'h, K, ()>;
+
+/// [`Visitor`] traverses all the keys in the [`HashSet`].
+///
+/// It implements the [`Iterator`] trait yielding a [`RefGuard`] for each key.
+pub type Visitor<'h, 'b, K, H> = super::hash_map::Visitor<'h, 'b, K, (), H>;
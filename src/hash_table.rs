@@ -13,6 +13,10 @@ use std::hash::{BuildHasher, Hash, Hasher};
 use std::sync::atomic::AtomicU8;
 use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
 
+/// `LockedEntry` is an entry along with the [`Reader`] keeping the bucket shared-locked.
+type LockedEntry<'b, K, V, const LOCK_FREE: bool> =
+    (Option<Reader<'b, K, V, LOCK_FREE>>, &'b (K, V));
+
 /// `HashTable` defines common functions for hash table implementations.
 pub(super) trait HashTable<K, V, H, const LOCK_FREE: bool>
 where
@@ -130,6 +134,31 @@ where
         async_wait: Option<*mut AsyncWait>,
         barrier: &'b Barrier,
     ) -> Result<Option<R>, ()>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        if let Some((_locker, (key, value))) =
+            self.search_entry(key_ref, hash, partial_hash, async_wait, barrier)?
+        {
+            return Ok(Some(reader(key, value)));
+        }
+        Ok(None)
+    }
+
+    /// Searches for an entry in the [`HashTable`].
+    ///
+    /// It returns the entry along with the [`Reader`] keeping the bucket shared-locked, and the
+    /// [`Reader`] is `None` if the [`HashTable`] is lock-free.
+    #[inline]
+    fn search_entry<'b, Q>(
+        &self,
+        key_ref: &Q,
+        hash: u64,
+        partial_hash: u8,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &'b Barrier,
+    ) -> Result<Option<LockedEntry<'b, K, V, LOCK_FREE>>, ()>
     where
         K: Borrow<Q>,
         Q: Eq + Hash + ?Sized,
@@ -148,7 +177,7 @@ where
                     if LOCK_FREE {
                         let cell_ref = old_array_ref.cell(cell_index);
                         if let Some(entry) = cell_ref.search(key_ref, partial_hash, barrier) {
-                            return Ok(Some(reader(&entry.0, &entry.1)));
+                            return Ok(Some((None, entry)));
                         }
                     } else {
                         let lock_result = if let Some(&async_wait) = async_wait.as_ref() {
@@ -161,10 +190,10 @@ where
                             Reader::lock(old_array_ref.cell(cell_index), barrier)
                         };
                         if let Some(locker) = lock_result {
-                            if let Some((key, value)) =
+                            if let Some(entry) =
                                 locker.cell().search(key_ref, partial_hash, barrier)
                             {
-                                return Ok(Some(reader(key, value)));
+                                return Ok(Some((Some(locker), entry)));
                             }
                         }
                     }
@@ -174,7 +203,7 @@ where
             if LOCK_FREE {
                 let cell_ref = current_array_ref.cell(cell_index);
                 if let Some(entry) = cell_ref.search(key_ref, partial_hash, barrier) {
-                    return Ok(Some(reader(&entry.0, &entry.1)));
+                    return Ok(Some((None, entry)));
                 }
             } else {
                 let lock_result = if let Some(&async_wait) = async_wait.as_ref() {
@@ -187,9 +216,8 @@ where
                     Reader::lock(current_array_ref.cell(cell_index), barrier)
                 };
                 if let Some(locker) = lock_result {
-                    if let Some((key, value)) = locker.cell().search(key_ref, partial_hash, barrier)
-                    {
-                        return Ok(Some(reader(key, value)));
+                    if let Some(entry) = locker.cell().search(key_ref, partial_hash, barrier) {
+                        return Ok(Some((Some(locker), entry)));
                     }
                 }
             }
@@ -1,5 +1,6 @@
 use crate::ebr::{Arc, AtomicArc, Barrier, Ptr, Tag};
-use crate::wait_queue::{AsyncWait, WaitQueue};
+use crate::lock::Lock;
+use crate::wait_queue::AsyncWait;
 
 use std::borrow::Borrow;
 use std::mem::MaybeUninit;
@@ -16,36 +17,25 @@ pub const CELL_LEN: usize = 32;
 /// The fixed size of the linked [`DataArray`].
 const LINKED_LEN: usize = CELL_LEN / 4;
 
-/// State bits.
-const KILLED: u32 = 1_u32 << 31;
-const WAITING: u32 = 1_u32 << 30;
-const LOCK: u32 = 1_u32 << 29;
-const SLOCK_MAX: u32 = LOCK - 1;
-const LOCK_MASK: u32 = LOCK | SLOCK_MAX;
-
 /// [`Cell`] is a small fixed-size hash table that resolves hash conflicts using a linked list
 /// of entry arrays.
 pub(crate) struct Cell<K: 'static + Eq, V: 'static, const LOCK_FREE: bool> {
     /// An array of key-value pairs and their metadata.
     data_array: DataArray<K, V, CELL_LEN>,
 
-    /// The state of the [`Cell`].
-    state: AtomicU32,
+    /// The lock of the [`Cell`].
+    lock: Lock,
 
     /// The number of valid entries in the [`Cell`].
     num_entries: u32,
-
-    /// The wait queue of the [`Cell`].
-    wait_queue: WaitQueue,
 }
 
 impl<K: 'static + Eq, V: 'static, const LOCK_FREE: bool> Default for Cell<K, V, LOCK_FREE> {
     fn default() -> Self {
         Cell::<K, V, LOCK_FREE> {
             data_array: DataArray::new(),
-            state: AtomicU32::new(0),
+            lock: Lock::default(),
             num_entries: 0,
-            wait_queue: WaitQueue::default(),
         }
     }
 }
@@ -54,7 +44,7 @@ impl<K: 'static + Eq, V: 'static, const LOCK_FREE: bool> Cell<K, V, LOCK_FREE> {
     /// Returns true if the [`Cell`] has been killed.
     #[inline]
     pub(crate) fn killed(&self) -> bool {
-        (self.state.load(Relaxed) & KILLED) == KILLED
+        self.lock.killed()
     }
 
     /// Returns the number of entries in the [`Cell`].
@@ -101,6 +91,35 @@ impl<K: 'static + Eq, V: 'static, const LOCK_FREE: bool> Cell<K, V, LOCK_FREE> {
         None
     }
 
+    /// Searches for an entry associated with the given key, and marks it recently accessed.
+    ///
+    /// Only entries in the main [`DataArray`] are tracked, therefore entries in the linked list
+    /// are never marked.
+    #[inline]
+    pub(crate) fn search_and_mark<'b, Q>(
+        &'b self,
+        key_ref: &Q,
+        partial_hash: u8,
+        barrier: &'b Barrier,
+    ) -> Option<&'b (K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        if self.num_entries == 0 {
+            return None;
+        }
+
+        if let Some((index, entry_ref)) =
+            Self::search_array(&self.data_array, key_ref, partial_hash)
+        {
+            self.data_array.accessed.fetch_or(1_u32 << index, Relaxed);
+            return Some(entry_ref);
+        }
+
+        self.search(key_ref, partial_hash, barrier)
+    }
+
     /// Gets an [`EntryIterator`] pointing to an entry associated with the given key.
     ///
     /// The returned [`EntryIterator`] always points to a valid entry.
@@ -156,7 +175,7 @@ impl<K: 'static + Eq, V: 'static, const LOCK_FREE: bool> Cell<K, V, LOCK_FREE> {
                 barrier.reclaim(data_array);
             }
         }
-        self.state.store(KILLED, Relaxed);
+        self.lock.kill();
         ptr::read(self);
     }
 
@@ -292,6 +311,31 @@ impl<'b, K: 'static + Eq, V: 'static, const LOCK_FREE: bool> EntryIterator<'b, K
         unsafe { &(*entry_ptr) }
     }
 
+    /// Gets a mutable reference to the key-value pair.
+    ///
+    /// The [`Locker`] proves that no other thread can access the entry.
+    #[inline]
+    pub(crate) fn get_mut(&mut self, _locker: &Locker<K, V, LOCK_FREE>) -> &mut (K, V) {
+        let entry_ptr = if let Some(data_array_ref) = self.current_array_ptr.as_ref() {
+            data_array_ref.data[self.current_index].as_ptr()
+        } else {
+            self.cell.as_ref().unwrap().data_array.data[self.current_index].as_ptr()
+        };
+        unsafe { &mut *(entry_ptr.cast_mut()) }
+    }
+
+    /// Marks the current entry recently accessed if it is in the main [`DataArray`].
+    #[inline]
+    pub(crate) fn mark_accessed(&self) {
+        if self.current_array_ptr.is_null() {
+            if let Some(cell) = self.cell.as_ref() {
+                cell.data_array
+                    .accessed
+                    .fetch_or(1_u32 << self.current_index, Relaxed);
+            }
+        }
+    }
+
     /// Tries to remove the current data array from the linked list.
     ///
     /// It should only be invoked when the caller is holding a [`Locker`] on the [`Cell`].
@@ -346,6 +390,8 @@ impl<'b, K: 'static + Eq, V: 'static, const LOCK_FREE: bool> EntryIterator<'b, K
             return Some((entry_ref, hash));
         }
 
+        // Remember the current data array so that the next one can be unlinked.
+        self.prev_array_ptr = self.current_array_ptr;
         self.current_array_ptr = data_array_ref.link.load(Acquire, self.barrier_ref);
         self.current_index = usize::MAX;
 
@@ -385,20 +431,9 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
     #[inline]
     pub(crate) fn lock(
         cell: &'b Cell<K, V, LOCK_FREE>,
-        barrier: &'b Barrier,
+        _barrier: &'b Barrier,
     ) -> Option<Locker<'b, K, V, LOCK_FREE>> {
-        loop {
-            if let Ok(locker) = Self::try_lock(cell, barrier) {
-                return locker;
-            }
-            if let Ok(locker) = cell.wait_queue.wait_sync(|| {
-                // Mark that there is a waiting thread.
-                cell.state.fetch_or(WAITING, Release);
-                Self::try_lock(cell, barrier)
-            }) {
-                return locker;
-            }
-        }
+        cell.lock.lock::<true>().then(|| Locker { cell })
     }
 
     /// Tries to lock the [`Cell`], and if it fails, pushes an [`AsyncWait`].
@@ -406,16 +441,11 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
     pub(crate) fn try_lock_or_wait(
         cell: &'b Cell<K, V, LOCK_FREE>,
         async_wait: *mut AsyncWait,
-        barrier: &'b Barrier,
+        _barrier: &'b Barrier,
     ) -> Result<Option<Locker<'b, K, V, LOCK_FREE>>, ()> {
-        if let Ok(locker) = Self::try_lock(cell, barrier) {
-            return Ok(locker);
-        }
-        cell.wait_queue.push_async_entry(async_wait, || {
-            // Mark that there is a waiting thread.
-            cell.state.fetch_or(WAITING, Release);
-            Self::try_lock(cell, barrier)
-        })
+        cell.lock
+            .try_lock_or_wait::<true>(async_wait)
+            .map(|locked| locked.then(|| Locker { cell }))
     }
 
     /// Returns a reference to the [`Cell`].
@@ -425,31 +455,37 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
     }
 
     /// Inserts a new key-value pair into the [`Cell`] without a uniqueness check.
+    ///
+    /// It returns a reference to the newly inserted key-value pair.
     #[inline]
-    pub(crate) fn insert(&'b self, key: K, value: V, partial_hash: u8, barrier: &'b Barrier) {
+    pub(crate) fn insert<'g>(
+        &'g self,
+        key: K,
+        value: V,
+        partial_hash: u8,
+        barrier: &'g Barrier,
+    ) -> &'g (K, V) {
         assert!(self.cell.num_entries != u32::MAX, "array overflow");
 
         let preferred_index = partial_hash as usize % CELL_LEN;
         if (self.cell.data_array.occupied & (1_u32 << preferred_index)) == 0 {
-            self.insert_entry(
+            return self.insert_entry(
                 &mut self.cell_mut().data_array,
                 preferred_index,
                 key,
                 value,
                 partial_hash,
             );
-            return;
         }
         let free_index = self.cell.data_array.occupied.trailing_ones() as usize;
         if free_index < CELL_LEN {
-            self.insert_entry(
+            return self.insert_entry(
                 &mut self.cell_mut().data_array,
                 free_index,
                 key,
                 value,
                 partial_hash,
             );
-            return;
         }
 
         let preferred_index = partial_hash as usize % LINKED_LEN;
@@ -457,13 +493,11 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
             as *mut DataArray<K, V, LINKED_LEN>;
         while let Some(data_array_mut) = unsafe { data_array_ptr.as_mut() } {
             if (data_array_mut.occupied & (1_u32 << preferred_index)) == 0 {
-                self.insert_entry(data_array_mut, preferred_index, key, value, partial_hash);
-                return;
+                return self.insert_entry(data_array_mut, preferred_index, key, value, partial_hash);
             }
             let free_index = data_array_mut.occupied.trailing_ones() as usize;
             if free_index < LINKED_LEN {
-                self.insert_entry(data_array_mut, free_index, key, value, partial_hash);
-                return;
+                return self.insert_entry(data_array_mut, free_index, key, value, partial_hash);
             }
 
             data_array_ptr = data_array_mut.link.load(Acquire, barrier).as_raw()
@@ -472,7 +506,7 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
 
         // Insert a new `DataArray` at the linked list head.
         let mut new_data_array = Arc::new(DataArray::new());
-        self.insert_entry(
+        let entry_ptr: *const (K, V) = self.insert_entry(
             unsafe { new_data_array.get_mut().unwrap() },
             preferred_index,
             key,
@@ -490,6 +524,41 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
             .data_array
             .link
             .swap((Some(new_data_array), Tag::None), Release);
+
+        // The new `DataArray` is now owned by the `Cell` which outlives `self`.
+        unsafe { &*entry_ptr }
+    }
+
+    /// Evicts an entry in the main [`DataArray`] that has not been accessed recently.
+    ///
+    /// It implements the CLOCK algorithm: the hand sweeps the slots from `start_index`, giving
+    /// marked entries a second chance by clearing their marks, and evicts the first unmarked
+    /// entry.
+    #[inline]
+    pub(crate) fn evict_unaccessed(&self, start_index: usize) -> Option<(K, V)> {
+        debug_assert!(!LOCK_FREE);
+
+        let occupied = self.cell.data_array.occupied;
+        if occupied == 0 {
+            return None;
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let start_index = (start_index % CELL_LEN) as u32;
+        let accessed = self.cell.data_array.accessed.load(Relaxed);
+        let unaccessed = (occupied & !accessed).rotate_right(start_index);
+        let offset = if unaccessed == 0 {
+            // Every entry has been accessed; the hand clears all the marks in a full sweep.
+            self.cell.data_array.accessed.store(0, Relaxed);
+            occupied.rotate_right(start_index).trailing_zeros()
+        } else {
+            let offset = unaccessed.trailing_zeros();
+            let passed = ((1_u32 << offset) - 1).rotate_left(start_index);
+            self.cell.data_array.accessed.fetch_and(!passed, Relaxed);
+            offset
+        };
+        let index = (start_index + offset) as usize % CELL_LEN;
+        self.erase_entry(&mut self.cell_mut().data_array, index)
     }
 
     /// Removes a key-value pair being pointed by the given [`EntryIterator`].
@@ -515,6 +584,53 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
         }
     }
 
+    /// Replaces the key-value pair being pointed by the given [`EntryIterator`] with a new one.
+    ///
+    /// The new key-value pair is inserted into a linked [`DataArray`] that readers visit after
+    /// the one containing the old key-value pair, and then the old one is marked removed;
+    /// therefore, readers always observe either of them.
+    #[inline]
+    pub(crate) fn replace(
+        &self,
+        iterator: &mut EntryIterator<K, V, LOCK_FREE>,
+        key: K,
+        value: V,
+        partial_hash: u8,
+    ) {
+        debug_assert!(LOCK_FREE);
+        debug_assert!(iterator.current_index != usize::MAX);
+
+        let barrier = iterator.barrier_ref;
+        let mut last_link = if let Some(data_array_ref) = iterator.current_array_ptr.as_ref() {
+            &data_array_ref.link
+        } else {
+            &self.cell.data_array.link
+        };
+        let mut data_array_ptr = last_link.load(Acquire, barrier).as_raw().cast_mut();
+        while let Some(data_array_mut) = unsafe { data_array_ptr.as_mut() } {
+            let free_index = data_array_mut.occupied.trailing_ones() as usize;
+            if free_index < LINKED_LEN {
+                self.insert_entry(data_array_mut, free_index, key, value, partial_hash);
+                self.erase(iterator);
+                return;
+            }
+            last_link = &data_array_mut.link;
+            data_array_ptr = last_link.load(Acquire, barrier).as_raw().cast_mut();
+        }
+
+        // Append a new `DataArray` at the linked list tail.
+        let mut new_data_array = Arc::new(DataArray::new());
+        self.insert_entry(
+            unsafe { new_data_array.get_mut().unwrap() },
+            partial_hash as usize % LINKED_LEN,
+            key,
+            value,
+            partial_hash,
+        );
+        last_link.swap((Some(new_data_array), Tag::None), Release);
+        self.erase(iterator);
+    }
+
     /// Extracts the key-value pair being pointed by `self`.
     #[inline]
     pub(crate) fn extract(&self, iterator: &mut EntryIterator<K, V, LOCK_FREE>) -> (K, V) {
@@ -539,7 +655,7 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
         if LOCK_FREE {
             self.cell_mut().data_array.removed = self.cell.data_array.occupied;
         }
-        self.cell.state.fetch_or(KILLED, Release);
+        self.cell.lock.kill();
         self.num_entries_updated(0);
         if !self.cell.data_array.link.load(Acquire, barrier).is_null() {
             if let Some(data_array) = self.cell.data_array.link.swap((None, Tag::None), Relaxed).0 {
@@ -548,15 +664,18 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
         }
     }
 
-    /// Removes a key-value pair in the slot.
-    fn insert_entry<const LEN: usize>(
+    /// Inserts a key-value pair in the slot.
+    ///
+    /// The returned reference borrows `data_array_mut`, therefore it cannot outlive the
+    /// [`DataArray`].
+    fn insert_entry<'g, const LEN: usize>(
         &self,
-        data_array_mut: &mut DataArray<K, V, LEN>,
+        data_array_mut: &'g mut DataArray<K, V, LEN>,
         index: usize,
         key: K,
         value: V,
         partial_hash: u8,
-    ) {
+    ) -> &'g (K, V) {
         debug_assert!(index < LEN);
 
         unsafe {
@@ -569,7 +688,9 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
 
             data_array_mut.occupied |= 1_u32 << index;
         }
+        *data_array_mut.accessed.get_mut() &= !(1_u32 << index);
         self.num_entries_updated(self.cell.num_entries + 1);
+        unsafe { &*data_array_mut.data[index].as_ptr() }
     }
 
     /// Removes a key-value pair in the slot.
@@ -627,49 +748,12 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Locker<'b, K, V, LOCK_FREE> {
             &mut *(self.cell as *const _ as *mut Cell<K, V, LOCK_FREE>)
         }
     }
-
-    /// Tries to lock the [`Cell`].
-    fn try_lock(
-        cell: &'b Cell<K, V, LOCK_FREE>,
-        _barrier: &'b Barrier,
-    ) -> Result<Option<Locker<'b, K, V, LOCK_FREE>>, ()> {
-        let current = cell.state.load(Relaxed) & (!LOCK_MASK);
-        if (current & KILLED) == KILLED {
-            return Ok(None);
-        }
-        if cell
-            .state
-            .compare_exchange(current, current | LOCK, Acquire, Relaxed)
-            .is_ok()
-        {
-            Ok(Some(Locker { cell }))
-        } else {
-            Err(())
-        }
-    }
 }
 
 impl<'b, K: 'static + Eq, V: 'static, const LOCK_FREE: bool> Drop for Locker<'b, K, V, LOCK_FREE> {
     #[inline]
     fn drop(&mut self) {
-        let mut current = self.cell.state.load(Relaxed);
-        loop {
-            let wakeup = (current & WAITING) == WAITING;
-            match self.cell.state.compare_exchange(
-                current,
-                current & (!(WAITING | LOCK)),
-                Release,
-                Relaxed,
-            ) {
-                Ok(_) => {
-                    if wakeup {
-                        self.cell.wait_queue.signal();
-                    }
-                    break;
-                }
-                Err(result) => current = result,
-            }
-        }
+        self.cell.lock.unlock::<true>();
     }
 }
 
@@ -682,20 +766,9 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Reader<'b, K, V, LOCK_FREE> {
     #[inline]
     pub(crate) fn lock(
         cell: &'b Cell<K, V, LOCK_FREE>,
-        barrier: &'b Barrier,
+        _barrier: &'b Barrier,
     ) -> Option<Reader<'b, K, V, LOCK_FREE>> {
-        loop {
-            if let Ok(reader) = Self::try_lock(cell, barrier) {
-                return reader;
-            }
-            if let Ok(reader) = cell.wait_queue.wait_sync(|| {
-                // Mark that there is a waiting thread.
-                cell.state.fetch_or(WAITING, Release);
-                Self::try_lock(cell, barrier)
-            }) {
-                return reader;
-            }
-        }
+        cell.lock.lock::<false>().then(|| Reader { cell })
     }
 
     /// Tries to lock the [`Cell`], and if it fails, pushes an [`AsyncWait`].
@@ -703,16 +776,11 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Reader<'b, K, V, LOCK_FREE> {
     pub(crate) fn try_lock_or_wait(
         cell: &'b Cell<K, V, LOCK_FREE>,
         async_wait: *mut AsyncWait,
-        barrier: &'b Barrier,
+        _barrier: &'b Barrier,
     ) -> Result<Option<Reader<'b, K, V, LOCK_FREE>>, ()> {
-        if let Ok(reader) = Self::try_lock(cell, barrier) {
-            return Ok(reader);
-        }
-        cell.wait_queue.push_async_entry(async_wait, || {
-            // Mark that there is a waiting thread.
-            cell.state.fetch_or(WAITING, Release);
-            Self::try_lock(cell, barrier)
-        })
+        cell.lock
+            .try_lock_or_wait::<false>(async_wait)
+            .map(|locked| locked.then(|| Reader { cell }))
     }
 
     /// Returns a reference to the [`Cell`].
@@ -720,52 +788,24 @@ impl<'b, K: Eq, V, const LOCK_FREE: bool> Reader<'b, K, V, LOCK_FREE> {
     pub(crate) fn cell(&self) -> &'b Cell<K, V, LOCK_FREE> {
         self.cell
     }
+}
 
-    /// Tries to lock the [`Cell`].
-    fn try_lock(
-        cell: &'b Cell<K, V, LOCK_FREE>,
-        _barrier: &'b Barrier,
-    ) -> Result<Option<Reader<'b, K, V, LOCK_FREE>>, ()> {
-        let current = cell.state.load(Relaxed);
-        if (current & LOCK_MASK) >= SLOCK_MAX {
-            return Err(());
-        }
-        if (current & KILLED) >= KILLED {
-            return Ok(None);
-        }
-        if cell
-            .state
-            .compare_exchange(current, current + 1, Acquire, Relaxed)
-            .is_ok()
-        {
-            Ok(Some(Reader { cell }))
-        } else {
-            Err(())
-        }
+impl<K: 'static + Eq, V: 'static, const LOCK_FREE: bool> Clone for Reader<'_, K, V, LOCK_FREE> {
+    /// Shared-locks the [`Cell`] once more.
+    ///
+    /// It never waits as the [`Cell`] cannot be killed or exclusively locked while `self` is
+    /// alive.
+    #[inline]
+    fn clone(&self) -> Self {
+        self.cell.lock.lock_shared_again();
+        Reader { cell: self.cell }
     }
 }
 
 impl<'b, K: 'static + Eq, V: 'static, const LOCK_FREE: bool> Drop for Reader<'b, K, V, LOCK_FREE> {
     #[inline]
     fn drop(&mut self) {
-        let mut current = self.cell.state.load(Relaxed);
-        loop {
-            let wakeup = (current & WAITING) == WAITING;
-            let next = (current - 1) & !(WAITING);
-            match self
-                .cell
-                .state
-                .compare_exchange(current, next, Relaxed, Relaxed)
-            {
-                Ok(_) => {
-                    if wakeup {
-                        self.cell.wait_queue.signal();
-                    }
-                    break;
-                }
-                Err(result) => current = result,
-            }
-        }
+        self.cell.lock.unlock::<false>();
     }
 }
 
@@ -774,6 +814,7 @@ pub struct DataArray<K: 'static + Eq, V: 'static, const LEN: usize> {
     link: AtomicArc<DataArray<K, V, LINKED_LEN>>,
     occupied: u32,
     removed: u32,
+    accessed: AtomicU32,
     partial_hash_array: [u8; LEN],
     data: [MaybeUninit<(K, V)>; LEN],
 }
@@ -784,6 +825,7 @@ impl<K: 'static + Eq, V: 'static, const LEN: usize> DataArray<K, V, LEN> {
             link: AtomicArc::null(),
             occupied: 0,
             removed: 0,
+            accessed: AtomicU32::new(0),
             partial_hash_array: [0_u8; LEN],
             data: unsafe { MaybeUninit::uninit().assume_init() },
         }
@@ -906,4 +948,32 @@ mod test {
         assert_eq!(cell.num_entries(), 0);
         assert!(Locker::lock(&*cell, &epoch_barrier).is_none());
     }
+
+    #[test]
+    fn erase_linked() {
+        let num_entries = CELL_LEN + LINKED_LEN * 2;
+        let barrier = Barrier::new();
+        let cell: Cell<usize, usize, false> = Cell::default();
+        let mut locker = Locker::lock(&cell, &barrier).unwrap();
+        for key in 0..num_entries {
+            locker.insert(key, key, (key % CELL_LEN).try_into().unwrap(), &barrier);
+        }
+
+        // Empties the last linked data array through an iterator.
+        let mut erased = 0;
+        let mut iterator = locker.cell().iter(&barrier);
+        let mut position = 0;
+        while iterator.next().is_some() {
+            if position >= CELL_LEN + LINKED_LEN {
+                assert!(locker.erase(&mut iterator).is_some());
+                erased += 1;
+            }
+            position += 1;
+        }
+        assert_eq!(erased, LINKED_LEN);
+        assert_eq!(cell.num_entries(), num_entries - LINKED_LEN);
+        assert_eq!(cell.iter(&barrier).count(), cell.num_entries());
+
+        locker.purge(&barrier);
+    }
 }
@@ -4,8 +4,16 @@
 //!
 //! * [`HashMap`]: concurrent and asynchronous hash map.
 //! * [`HashIndex`]: concurrent and asynchronous hash map optimized for read.
+//! * [`HashIndexSet`]: concurrent and asynchronous hash set optimized for read.
+//! * [`HashCache`]: concurrent and asynchronous cache with approximate LRU eviction.
 //! * [`TreeIndex`]: concurrent and asynchronous B+ tree optimized for read.
+//! * [`TreeMap`]: concurrent and asynchronous B+ tree optimized for write.
+//! * [`TreeSet`]: concurrent and asynchronous ordered set based on [`TreeIndex`].
 //! * [`Queue`]: lock-free concurrent queue.
+//! * [`BoundedQueue`]: lock-free concurrent queue with a fixed capacity.
+//! * [`Stack`]: lock-free concurrent stack.
+//! * [`Deque`]: lock-free concurrent double-ended queue.
+//! * [`channel`]: closable multi-producer multi-consumer channel based on [`Queue`].
 //!
 //! Utilities.
 //!
@@ -21,18 +29,39 @@ pub use hash_index::HashIndex;
 pub mod hash_set;
 pub use hash_set::HashSet;
 
+pub mod hash_index_set;
+pub use hash_index_set::HashIndexSet;
+
+pub mod hash_cache;
+pub use hash_cache::HashCache;
+
 mod linked_list;
 pub use linked_list::LinkedList;
 
 mod queue;
-pub use queue::Queue;
+pub use queue::{BoundedQueue, Queue};
+
+mod stack;
+pub use stack::Stack;
+
+mod deque;
+pub use deque::Deque;
+
+pub mod channel;
 
 pub mod tree_index;
-pub use tree_index::TreeIndex;
+pub use tree_index::{SharedTreeIndex, TreeIndex};
+
+pub mod tree_map;
+pub use tree_map::TreeMap;
+
+pub mod tree_set;
+pub use tree_set::TreeSet;
 
 pub mod ebr;
 
 mod hash_table;
+mod lock;
 mod wait_queue;
 
 mod tests;
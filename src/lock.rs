@@ -0,0 +1,169 @@
+use crate::wait_queue::{AsyncWait, WaitQueue};
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+
+/// State bits.
+const KILLED: u32 = 1_u32 << 31;
+const WAITING: u32 = 1_u32 << 30;
+const LOCK: u32 = 1_u32 << 29;
+const SLOCK_MAX: u32 = LOCK - 1;
+const LOCK_MASK: u32 = LOCK | SLOCK_MAX;
+
+/// [`Lock`] is a shared-exclusive lock that can be killed.
+///
+/// Once killed, the [`Lock`] can no longer be acquired, and threads waiting for it are woken up.
+#[derive(Debug, Default)]
+pub(crate) struct Lock {
+    /// The state of the [`Lock`].
+    state: AtomicU32,
+
+    /// The wait queue of the [`Lock`].
+    wait_queue: WaitQueue,
+}
+
+impl Lock {
+    /// Creates a new [`Lock`] that is exclusively locked by the caller.
+    #[inline]
+    pub(crate) fn new_locked() -> Lock {
+        Lock {
+            state: AtomicU32::new(LOCK),
+            wait_queue: WaitQueue::default(),
+        }
+    }
+
+    /// Returns `true` if the [`Lock`] has been killed.
+    #[inline]
+    pub(crate) fn killed(&self) -> bool {
+        (self.state.load(Relaxed) & KILLED) == KILLED
+    }
+
+    /// Kills the [`Lock`].
+    ///
+    /// The caller must exclusively own the [`Lock`], and the [`Lock`] stays killed after it is
+    /// released.
+    #[inline]
+    pub(crate) fn kill(&self) {
+        self.state.fetch_or(KILLED, Release);
+    }
+
+    /// Acquires the [`Lock`], waiting for it to be released by others.
+    ///
+    /// Returns `false` if the [`Lock`] has been killed.
+    #[inline]
+    pub(crate) fn lock<const EXCLUSIVE: bool>(&self) -> bool {
+        loop {
+            if let Ok(locked) = self.try_lock::<EXCLUSIVE>() {
+                return locked;
+            }
+            if let Ok(locked) = self.wait_queue.wait_sync(|| {
+                // Mark that there is a waiting thread.
+                self.state.fetch_or(WAITING, Release);
+                self.try_lock::<EXCLUSIVE>()
+            }) {
+                return locked;
+            }
+        }
+    }
+
+    /// Tries to acquire the [`Lock`], and if it fails, pushes an [`AsyncWait`].
+    ///
+    /// Returns `Ok(false)` if the [`Lock`] has been killed.
+    #[inline]
+    pub(crate) fn try_lock_or_wait<const EXCLUSIVE: bool>(
+        &self,
+        async_wait: *mut AsyncWait,
+    ) -> Result<bool, ()> {
+        if let Ok(locked) = self.try_lock::<EXCLUSIVE>() {
+            return Ok(locked);
+        }
+        self.wait_queue.push_async_entry(async_wait, || {
+            // Mark that there is a waiting thread.
+            self.state.fetch_or(WAITING, Release);
+            self.try_lock::<EXCLUSIVE>()
+        })
+    }
+
+    /// Shared-locks the [`Lock`] once more.
+    ///
+    /// It never waits as the [`Lock`] cannot be killed or exclusively locked while the caller
+    /// holds a shared lock.
+    #[inline]
+    pub(crate) fn lock_shared_again(&self) {
+        let mut current = self.state.load(Relaxed);
+        loop {
+            assert!((current & LOCK_MASK) < SLOCK_MAX, "shared lock overflow");
+            match self
+                .state
+                .compare_exchange(current, current + 1, Acquire, Relaxed)
+            {
+                Ok(_) => return,
+                Err(result) => current = result,
+            }
+        }
+    }
+
+    /// Releases the [`Lock`].
+    #[inline]
+    pub(crate) fn unlock<const EXCLUSIVE: bool>(&self) {
+        self.release::<EXCLUSIVE>(0);
+    }
+
+    /// Kills and releases the exclusively owned [`Lock`].
+    #[inline]
+    pub(crate) fn kill_and_unlock(&self) {
+        self.release::<true>(KILLED);
+    }
+
+    /// Tries to acquire the [`Lock`].
+    ///
+    /// Returns `Ok(false)` if the [`Lock`] has been killed, and an error if it would block.
+    fn try_lock<const EXCLUSIVE: bool>(&self) -> Result<bool, ()> {
+        let mut current = self.state.load(Relaxed);
+        loop {
+            if EXCLUSIVE && (current & KILLED) == KILLED {
+                return Ok(false);
+            }
+            let next = if EXCLUSIVE {
+                if (current & LOCK_MASK) != 0 {
+                    return Err(());
+                }
+                current | LOCK
+            } else {
+                if (current & LOCK_MASK) >= SLOCK_MAX {
+                    return Err(());
+                }
+                if (current & KILLED) == KILLED {
+                    return Ok(false);
+                }
+                current + 1
+            };
+            match self.state.compare_exchange(current, next, Acquire, Relaxed) {
+                Ok(_) => return Ok(true),
+                Err(result) => current = result,
+            }
+        }
+    }
+
+    /// Releases the [`Lock`] while setting the given state bits, and wakes up waiting threads.
+    fn release<const EXCLUSIVE: bool>(&self, bits: u32) {
+        let mut current = self.state.load(Relaxed);
+        loop {
+            let wakeup = (current & WAITING) == WAITING;
+            let next = if EXCLUSIVE {
+                (current | bits) & !(WAITING | LOCK)
+            } else {
+                (current - 1) & !WAITING
+            };
+            match self.state.compare_exchange(current, next, Release, Relaxed) {
+                Ok(_) => {
+                    if wakeup {
+                        self.wait_queue.signal();
+                    }
+                    break;
+                }
+                Err(result) => current = result,
+            }
+        }
+    }
+}
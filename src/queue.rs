@@ -1,9 +1,12 @@
 //! [`Queue`] is a lock-free concurrent first-in-first-out queue.
 
 use super::ebr::{Arc, AtomicArc, Barrier, Ptr, Tag};
+use super::wait_queue::{AsyncWait, WaitQueue};
 
 use std::fmt::{Debug, Display};
 use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
 
 /// [`Queue`] is a lock-free concurrent first-in-first-out queue.
@@ -14,6 +17,22 @@ pub struct Queue<T: 'static> {
 
     /// `newest` *eventually* points to the newest entry in the [`Queue`].
     newest: AtomicArc<Entry<T>>,
+
+    /// `num_entries` is the number of linked entries if the [`Queue`] is bounded.
+    ///
+    /// An entry is counted from when a slot is reserved for it until it is unlinked from
+    /// `oldest`, therefore logically removed entries are counted until they are cleaned up.
+    num_entries: AtomicUsize,
+
+    /// `capacity` is the maximum number of entries, or `usize::MAX` if the [`Queue`] is
+    /// unbounded.
+    capacity: usize,
+
+    /// `pop_waiters` holds threads and tasks waiting for an entry to be pushed.
+    pop_waiters: WaitQueue,
+
+    /// `push_waiters` holds threads and tasks waiting for an entry to be popped.
+    push_waiters: WaitQueue,
 }
 
 impl<T: 'static> Queue<T> {
@@ -95,6 +114,69 @@ impl<T: 'static> Queue<T> {
         }
     }
 
+    /// Pops the oldest entry, waiting for an entry to be pushed if the [`Queue`] is empty.
+    ///
+    /// The calling thread is blocked until an entry becomes available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Queue;
+    /// use std::sync::Arc;
+    /// use std::thread;
+    ///
+    /// let queue: Arc<Queue<usize>> = Arc::new(Queue::default());
+    /// let queue_cloned = queue.clone();
+    ///
+    /// let thread = thread::spawn(move || **queue_cloned.pop_wait());
+    /// queue.push(7);
+    ///
+    /// assert_eq!(thread.join().unwrap(), 7);
+    /// ```
+    #[inline]
+    pub fn pop_wait(&self) -> Arc<Entry<T>> {
+        loop {
+            if let Some(entry) = self.pop() {
+                return entry;
+            }
+            // Entries are only popped outside the closure so that none of them gets lost.
+            let _ = self.pop_waiters.wait_sync(|| self.check_non_empty());
+        }
+    }
+
+    /// Pops the oldest entry, waiting for an entry to be pushed if the [`Queue`] is empty.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Queue;
+    ///
+    /// let queue: Queue<usize> = Queue::default();
+    ///
+    /// let future_pop = queue.pop_async();
+    /// queue.push(7);
+    /// ```
+    #[inline]
+    pub async fn pop_async(&self) -> Arc<Entry<T>> {
+        loop {
+            if let Some(entry) = self.pop() {
+                return entry;
+            }
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if self
+                .pop_waiters
+                .push_async_entry(async_wait_pinned.mut_ptr(), || self.check_non_empty())
+                .is_ok()
+            {
+                continue;
+            }
+            async_wait_pinned.await;
+        }
+    }
+
     /// Pops the oldest entry if the oldest entry satisfies the given condition.
     ///
     /// Returns `None` if the [`Queue`] is empty.
@@ -189,8 +271,112 @@ impl<T: 'static> Queue<T> {
         self.newest.is_null(Acquire)
     }
 
-    /// Pushes an entry into the [`Queue`].
+    /// Creates an empty bounded [`Queue`] that holds at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is `0`.
+    pub(crate) fn with_capacity(capacity: usize) -> Queue<T> {
+        assert_ne!(capacity, 0, "the capacity of a Queue must not be zero");
+        Self {
+            capacity: capacity.min(usize::MAX - 1),
+            ..Self::default()
+        }
+    }
+
+    /// Pushes a new instance of `T` if the [`Queue`] is not full.
+    pub(crate) fn try_push(&self, val: T) -> Result<Arc<Entry<T>>, T> {
+        self.push_if_internal(val, |_| true, &Barrier::new())
+    }
+
+    /// Returns the [`WaitQueue`] of threads and tasks waiting for an entry to be pushed.
+    pub(crate) fn pop_waiters(&self) -> &WaitQueue {
+        &self.pop_waiters
+    }
+
+    /// Returns the [`WaitQueue`] of threads and tasks waiting for an entry to be popped.
+    pub(crate) fn push_waiters(&self) -> &WaitQueue {
+        &self.push_waiters
+    }
+
+    /// Returns `Ok` if the [`Queue`] has an entry that is not removed.
+    pub(crate) fn check_non_empty(&self) -> Result<(), ()> {
+        self.peek(|_| ()).ok_or(())
+    }
+
+    /// Returns `Ok` if the [`Queue`] has room for a new entry.
+    pub(crate) fn check_not_full(&self) -> Result<(), ()> {
+        if self.num_entries.load(Relaxed) < self.capacity || self.cleanup_removed() {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Reserves room for a new entry.
+    ///
+    /// Returns `false` if the [`Queue`] is bounded and full.
+    pub(crate) fn reserve_entry(&self) -> bool {
+        if self.capacity == usize::MAX {
+            return true;
+        }
+        loop {
+            if self
+                .num_entries
+                .fetch_update(Relaxed, Relaxed, |n| {
+                    if n < self.capacity {
+                        Some(n + 1)
+                    } else {
+                        None
+                    }
+                })
+                .is_ok()
+            {
+                return true;
+            }
+
+            // Logically removed entries keep their slots until they are unlinked.
+            if !self.cleanup_removed() {
+                return false;
+            }
+        }
+    }
+
+    /// Pushes a new instance of `T` into the room reserved by [`Queue::reserve_entry`].
+    pub(crate) fn push_reserved(&self, val: T) -> Arc<Entry<T>> {
+        match self.link_entry(val, |_| true, &Barrier::new()) {
+            Ok(entry) => entry,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Gives back the slot of an entry if the [`Queue`] is bounded.
+    pub(crate) fn release_entry(&self) {
+        if self.capacity != usize::MAX {
+            self.num_entries.fetch_sub(1, Relaxed);
+            self.push_waiters.signal_if_waiting();
+        }
+    }
+
+    /// Pushes an entry into the [`Queue`] if there is room for it.
     fn push_if_internal<F: FnMut(Option<&Entry<T>>) -> bool>(
+        &self,
+        val: T,
+        cond: F,
+        barrier: &Barrier,
+    ) -> Result<Arc<Entry<T>>, T> {
+        if !self.reserve_entry() {
+            return Err(val);
+        }
+        let result = self.link_entry(val, cond, barrier);
+        if result.is_err() {
+            self.release_entry();
+        }
+        result
+    }
+
+    /// Links a new entry to the newest entry in the [`Queue`].
+    fn link_entry<F: FnMut(Option<&Entry<T>>) -> bool>(
         &self,
         val: T,
         mut cond: F,
@@ -235,6 +421,7 @@ impl<T: 'static> Queue<T> {
                         // The `Queue` was emptied in the meantime.
                         self.newest.swap((None, Tag::None), Release);
                     }
+                    self.pop_waiters.signal_if_waiting();
                     return Ok(new_entry);
                 }
                 Err((_, actual_ptr)) => {
@@ -276,6 +463,7 @@ impl<T: 'static> Queue<T> {
                             // Reset `newest`.
                             self.newest.swap((None, Tag::None), Relaxed);
                         }
+                        self.release_entry();
                         return new_ptr;
                     }
                     Err((_, actual_ptr)) => {
@@ -287,6 +475,20 @@ impl<T: 'static> Queue<T> {
         oldest_ptr
     }
 
+    /// Unlinks the oldest entry if it has been logically removed.
+    ///
+    /// Returns `true` if the oldest entry was logically removed.
+    fn cleanup_removed(&self) -> bool {
+        let barrier = Barrier::new();
+        if let Some(oldest_entry) = self.oldest.load(Acquire, &barrier).as_ref() {
+            if oldest_entry.is_removed() {
+                self.cleanup_oldest(&barrier);
+                return true;
+            }
+        }
+        false
+    }
+
     /// Traverses the linked list to the end.
     fn traverse<'b>(start: Ptr<'b, Entry<T>>, barrier: &'b Barrier) -> Ptr<'b, Entry<T>> {
         let mut current = start;
@@ -306,10 +508,323 @@ impl<T: 'static> Default for Queue<T> {
         Self {
             oldest: AtomicArc::default(),
             newest: AtomicArc::default(),
+            num_entries: AtomicUsize::new(0),
+            capacity: usize::MAX,
+            pop_waiters: WaitQueue::default(),
+            push_waiters: WaitQueue::default(),
         }
     }
 }
 
+/// [`BoundedQueue`] is a lock-free concurrent first-in-first-out queue that holds a limited
+/// number of entries.
+///
+/// [`BoundedQueue::try_push`] fails if the [`BoundedQueue`] is full, whereas
+/// [`BoundedQueue::push_wait`] and [`BoundedQueue::push_async`] wait for an entry to be popped.
+#[derive(Debug)]
+pub struct BoundedQueue<T: 'static> {
+    queue: Queue<T>,
+}
+
+impl<T: 'static> BoundedQueue<T> {
+    /// Creates an empty [`BoundedQueue`] that holds at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+    ///
+    /// assert!(queue.try_push(11).is_ok());
+    /// assert_eq!(queue.try_push(17).unwrap_err(), 17);
+    ///
+    /// assert_eq!(queue.pop().map(|e| **e), Some(11));
+    /// assert!(queue.try_push(17).is_ok());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(capacity: usize) -> BoundedQueue<T> {
+        BoundedQueue {
+            queue: Queue::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the maximum number of entries that the [`BoundedQueue`] can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(16);
+    /// assert_eq!(queue.capacity(), 16);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity
+    }
+
+    /// Pushes a new instance of `T` if the [`BoundedQueue`] is not full.
+    ///
+    /// Logically removed entries keep their slots until they are unlinked as the oldest entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied instance if the [`BoundedQueue`] is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+    ///
+    /// let entry = queue.try_push(11).unwrap();
+    /// assert!(queue.try_push(17).is_err());
+    ///
+    /// assert!(entry.remove());
+    /// assert!(queue.try_push(17).is_ok());
+    /// ```
+    #[inline]
+    pub fn try_push(&self, val: T) -> Result<Arc<Entry<T>>, T> {
+        self.queue.try_push(val)
+    }
+
+    /// Pushes a new instance of `T` if the [`BoundedQueue`] is not full and the newest entry
+    /// satisfies the given condition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied instance if the condition is not met, or if the
+    /// [`BoundedQueue`] is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(2);
+    ///
+    /// assert!(queue.try_push(11).is_ok());
+    ///
+    /// assert!(queue.push_if(17, |e| e.map_or(false, |x| **x == 17)).is_err());
+    /// assert!(queue.push_if(17, |e| e.map_or(false, |x| **x == 11)).is_ok());
+    /// assert!(queue.push_if(29, |_| true).is_err());
+    /// ```
+    #[inline]
+    pub fn push_if<F: FnMut(Option<&Entry<T>>) -> bool>(
+        &self,
+        val: T,
+        cond: F,
+    ) -> Result<Arc<Entry<T>>, T> {
+        self.queue.push_if_internal(val, cond, &Barrier::new())
+    }
+
+    /// Pushes a new instance of `T`, waiting for an entry to be popped if the [`BoundedQueue`] is
+    /// full.
+    ///
+    /// The calling thread is blocked until there is room for the new entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    /// use std::sync::Arc;
+    /// use std::thread;
+    ///
+    /// let queue: Arc<BoundedQueue<usize>> = Arc::new(BoundedQueue::new(1));
+    /// assert!(queue.try_push(7).is_ok());
+    ///
+    /// let queue_cloned = queue.clone();
+    /// let thread = thread::spawn(move || **queue_cloned.push_wait(11));
+    /// assert_eq!(**queue.pop_wait(), 7);
+    ///
+    /// assert_eq!(thread.join().unwrap(), 11);
+    /// ```
+    #[inline]
+    pub fn push_wait(&self, mut val: T) -> Arc<Entry<T>> {
+        loop {
+            match self.queue.try_push(val) {
+                Ok(entry) => return entry,
+                Err(returned) => val = returned,
+            }
+            let _ = self
+                .queue
+                .push_waiters
+                .wait_sync(|| self.queue.check_not_full());
+        }
+    }
+
+    /// Pushes a new instance of `T`, waiting for an entry to be popped if the [`BoundedQueue`] is
+    /// full.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+    ///
+    /// assert!(queue.try_push(7).is_ok());
+    /// let future_push = queue.push_async(11);
+    /// ```
+    #[inline]
+    pub async fn push_async(&self, mut val: T) -> Arc<Entry<T>> {
+        loop {
+            match self.queue.try_push(val) {
+                Ok(entry) => return entry,
+                Err(returned) => val = returned,
+            }
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            if self
+                .queue
+                .push_waiters
+                .push_async_entry(async_wait_pinned.mut_ptr(), || self.queue.check_not_full())
+                .is_ok()
+            {
+                continue;
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Pops the oldest entry.
+    ///
+    /// Returns `None` if the [`BoundedQueue`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(2);
+    ///
+    /// assert!(queue.try_push(37).is_ok());
+    /// assert!(queue.try_push(3).is_ok());
+    ///
+    /// assert_eq!(queue.pop().map(|e| **e), Some(37));
+    /// assert_eq!(queue.pop().map(|e| **e), Some(3));
+    /// assert!(queue.pop().is_none());
+    /// ```
+    #[inline]
+    pub fn pop(&self) -> Option<Arc<Entry<T>>> {
+        self.queue.pop()
+    }
+
+    /// Pops the oldest entry, waiting for an entry to be pushed if the [`BoundedQueue`] is empty.
+    ///
+    /// The calling thread is blocked until an entry becomes available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+    ///
+    /// assert!(queue.try_push(7).is_ok());
+    /// assert_eq!(**queue.pop_wait(), 7);
+    /// ```
+    #[inline]
+    pub fn pop_wait(&self) -> Arc<Entry<T>> {
+        self.queue.pop_wait()
+    }
+
+    /// Pops the oldest entry, waiting for an entry to be pushed if the [`BoundedQueue`] is empty.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+    ///
+    /// let future_pop = queue.pop_async();
+    /// ```
+    #[inline]
+    pub async fn pop_async(&self) -> Arc<Entry<T>> {
+        self.queue.pop_async().await
+    }
+
+    /// Pops the oldest entry if the oldest entry satisfies the given condition.
+    ///
+    /// Returns `None` if the [`BoundedQueue`] is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the oldest entry if the given condition is not met.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+    ///
+    /// assert!(queue.try_push(3).is_ok());
+    ///
+    /// assert!(queue.pop_if(|v| **v == 1).is_err());
+    /// assert_eq!(queue.pop_if(|v| **v == 3).ok().and_then(|e| e).map(|e| **e), Some(3));
+    /// ```
+    #[inline]
+    pub fn pop_if<F: FnMut(&Entry<T>) -> bool>(
+        &self,
+        cond: F,
+    ) -> Result<Option<Arc<Entry<T>>>, Arc<Entry<T>>> {
+        self.queue.pop_if(cond)
+    }
+
+    /// Peeks the oldest entry.
+    ///
+    /// Returns `None` if the [`BoundedQueue`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+    ///
+    /// assert!(queue.peek(|v| **v).is_none());
+    ///
+    /// assert!(queue.try_push(37).is_ok());
+    /// assert_eq!(queue.peek(|v| **v), Some(37));
+    /// ```
+    #[inline]
+    pub fn peek<R, F: FnOnce(&Entry<T>) -> R>(&self, reader: F) -> Option<R> {
+        self.queue.peek(reader)
+    }
+
+    /// Returns `true` if the [`BoundedQueue`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::BoundedQueue;
+    ///
+    /// let queue: BoundedQueue<usize> = BoundedQueue::new(1);
+    /// assert!(queue.is_empty());
+    ///
+    /// assert!(queue.try_push(7).is_ok());
+    /// assert!(!queue.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
 /// [`Entry`] stores an instance of `T` and a link to the next entry.
 pub struct Entry<T: 'static> {
     /// `instance` is always `Some` until [`Self::into_inner`] is called.
@@ -320,11 +835,12 @@ pub struct Entry<T: 'static> {
 }
 
 impl<T: 'static> Entry<T> {
-    /// Tries to remove the entry from its associated [`Queue`].
+    /// Tries to remove the entry from its associated [`Queue`] or [`Stack`](crate::Stack).
     ///
     /// The entry is only logically removed from the [`Queue`] and it will be popped from the
     /// [`Queue`] on a subsequent call to [`Queue::pop`] or [`Queue::peek`] when the entry becomes
-    /// the oldest one in the [`Queue`]. `false` is returned if the entry has already been removed.
+    /// the oldest one in the [`Queue`], or the top one in the [`Stack`](crate::Stack). `false` is
+    /// returned if the entry has already been removed.
     ///
     /// # Examples
     ///
@@ -367,13 +883,18 @@ impl<T: 'static> Entry<T> {
         self.next.tag(Relaxed) == Tag::First
     }
 
+    /// Returns a reference to the link to the next entry.
+    pub(crate) fn link_ref(&self) -> &AtomicArc<Self> {
+        &self.next
+    }
+
     /// Extracts the inner instance of `T`.
-    unsafe fn take_inner(&mut self) -> T {
+    pub(crate) unsafe fn take_inner(&mut self) -> T {
         self.instance.take().unwrap()
     }
 
     /// Creates a new [`Entry`].
-    fn new(val: T) -> Entry<T> {
+    pub(crate) fn new(val: T) -> Entry<T> {
         Entry {
             instance: Some(val),
             next: AtomicArc::default(),
@@ -0,0 +1,273 @@
+//! [`Stack`] is a lock-free concurrent last-in-first-out container.
+
+use super::ebr::{Arc, AtomicArc, Barrier, Ptr, Tag};
+use super::queue::Entry;
+
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
+
+/// [`Stack`] is a lock-free concurrent last-in-first-out container.
+///
+/// [`Stack`] is a Treiber stack of the same entries as [`Queue`](crate::Queue); entries are
+/// reclaimed through [`ebr`](crate::ebr), therefore an entry cannot be reused while a thread
+/// may still be referring to it.
+#[derive(Debug, Default)]
+pub struct Stack<T: 'static> {
+    /// `top` points to the newest entry in the [`Stack`].
+    top: AtomicArc<Entry<T>>,
+}
+
+impl<T: 'static> Stack<T> {
+    /// Pushes a new instance of `T`.
+    ///
+    /// Returns an [`Arc`] holding a strong reference to the newly pushed entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let stack: Stack<usize> = Stack::default();
+    ///
+    /// assert_eq!(**stack.push(11), 11);
+    /// ```
+    #[inline]
+    pub fn push(&self, val: T) -> Arc<Entry<T>> {
+        match self.push_if_internal(val, |_| true, &Barrier::new()) {
+            Ok(entry) => entry,
+            Err(_) => {
+                unreachable!();
+            }
+        }
+    }
+
+    /// Pushes a new instance of `T` if the newest entry satisfies the given condition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied instance if the condition is not met.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let stack: Stack<usize> = Stack::default();
+    ///
+    /// stack.push(11);
+    ///
+    /// assert!(stack.push_if(17, |e| e.map_or(false, |x| **x == 11)).is_ok());
+    /// assert!(stack.push_if(29, |e| e.map_or(false, |x| **x == 11)).is_err());
+    /// ```
+    #[inline]
+    pub fn push_if<F: FnMut(Option<&Entry<T>>) -> bool>(
+        &self,
+        val: T,
+        cond: F,
+    ) -> Result<Arc<Entry<T>>, T> {
+        self.push_if_internal(val, cond, &Barrier::new())
+    }
+
+    /// Pops the newest entry.
+    ///
+    /// Returns `None` if the [`Stack`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let stack: Stack<usize> = Stack::default();
+    ///
+    /// stack.push(37);
+    /// stack.push(3);
+    /// stack.push(1);
+    ///
+    /// assert_eq!(stack.pop().map(|e| **e), Some(1));
+    /// assert_eq!(stack.pop().map(|e| **e), Some(3));
+    /// assert_eq!(stack.pop().map(|e| **e), Some(37));
+    /// assert!(stack.pop().is_none());
+    /// ```
+    #[inline]
+    pub fn pop(&self) -> Option<Arc<Entry<T>>> {
+        match self.pop_if(|_| true) {
+            Ok(result) => result,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Pops the newest entry if the newest entry satisfies the given condition.
+    ///
+    /// Returns `None` if the [`Stack`] is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the newest entry if the given condition is not met.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let stack: Stack<usize> = Stack::default();
+    ///
+    /// stack.push(3);
+    /// stack.push(1);
+    ///
+    /// assert!(stack.pop_if(|v| **v == 3).is_err());
+    /// assert_eq!(stack.pop().map(|e| **e), Some(1));
+    /// assert_eq!(stack.pop_if(|v| **v == 3).ok().and_then(|e| e).map(|e| **e), Some(3));
+    /// ```
+    #[inline]
+    pub fn pop_if<F: FnMut(&Entry<T>) -> bool>(
+        &self,
+        mut cond: F,
+    ) -> Result<Option<Arc<Entry<T>>>, Arc<Entry<T>>> {
+        let barrier = Barrier::new();
+        let mut current = self.top.load(Acquire, &barrier);
+        while !current.is_null() {
+            if let Some(top_entry) = current.get_arc() {
+                if !top_entry.is_removed() && !cond(&*top_entry) {
+                    return Err(top_entry);
+                }
+                match self.top.compare_exchange(
+                    current,
+                    (top_entry.link_ref().get_arc(Acquire, &barrier), Tag::None),
+                    AcqRel,
+                    Acquire,
+                    &barrier,
+                ) {
+                    Ok((_, new_ptr)) => {
+                        // The entry may have been removed in the meantime.
+                        let removed = top_entry.remove();
+                        Self::unlink(&top_entry);
+                        if removed {
+                            return Ok(Some(top_entry));
+                        }
+                        current = new_ptr;
+                    }
+                    Err((_, actual_ptr)) => current = actual_ptr,
+                }
+                continue;
+            }
+            current = self.top.load(Acquire, &barrier);
+        }
+        Ok(None)
+    }
+
+    /// Peeks the newest entry.
+    ///
+    /// Returns `None` if the [`Stack`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let stack: Stack<usize> = Stack::default();
+    ///
+    /// assert!(stack.peek(|v| **v).is_none());
+    ///
+    /// stack.push(37);
+    /// stack.push(3);
+    ///
+    /// assert_eq!(stack.peek(|v| **v), Some(3));
+    /// ```
+    #[inline]
+    pub fn peek<R, F: FnOnce(&Entry<T>) -> R>(&self, reader: F) -> Option<R> {
+        let barrier = Barrier::new();
+        self.cleanup_top(&barrier).as_ref().map(reader)
+    }
+
+    /// Returns `true` if the [`Stack`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::Stack;
+    ///
+    /// let stack: Stack<usize> = Stack::default();
+    /// assert!(stack.is_empty());
+    ///
+    /// stack.push(7);
+    /// assert!(!stack.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.top.is_null(Acquire)
+    }
+
+    /// Pushes an entry into the [`Stack`].
+    fn push_if_internal<F: FnMut(Option<&Entry<T>>) -> bool>(
+        &self,
+        val: T,
+        mut cond: F,
+        barrier: &Barrier,
+    ) -> Result<Arc<Entry<T>>, T> {
+        let mut top_ptr = self.cleanup_top(barrier);
+        if !cond(top_ptr.as_ref()) {
+            // The condition is not met.
+            return Err(val);
+        }
+
+        let mut new_entry = Arc::new(Entry::new(val));
+        loop {
+            // The new entry is not reachable by other threads until it is pushed.
+            new_entry
+                .link_ref()
+                .swap((top_ptr.get_arc(), Tag::None), Relaxed);
+            if self
+                .top
+                .compare_exchange(
+                    top_ptr,
+                    (Some(new_entry.clone()), Tag::None),
+                    AcqRel,
+                    Acquire,
+                    barrier,
+                )
+                .is_ok()
+            {
+                return Ok(new_entry);
+            }
+            top_ptr = self.cleanup_top(barrier);
+            if !cond(top_ptr.as_ref()) {
+                // The condition is not met.
+                break;
+            }
+        }
+
+        // Extract the instance from the temporary entry.
+        new_entry.link_ref().swap((None, Tag::None), Relaxed);
+        Err(unsafe { new_entry.get_mut().unwrap().take_inner() })
+    }
+
+    /// Unlinks logically removed entries from `top`, and returns the newest entry that is not
+    /// removed.
+    fn cleanup_top<'b>(&self, barrier: &'b Barrier) -> Ptr<'b, Entry<T>> {
+        let mut top_ptr = self.top.load(Acquire, barrier);
+        while let Some(top_entry) = top_ptr.as_ref() {
+            if !top_entry.is_removed() {
+                break;
+            }
+            top_ptr = match self.top.compare_exchange(
+                top_ptr,
+                (top_entry.link_ref().get_arc(Acquire, barrier), Tag::None),
+                AcqRel,
+                Acquire,
+                barrier,
+            ) {
+                Ok((_, new_ptr)) => {
+                    Self::unlink(top_entry);
+                    new_ptr
+                }
+                Err((_, actual_ptr)) => actual_ptr,
+            };
+        }
+        top_ptr
+    }
+
+    /// Clears the link of an entry that has been popped so that the entry does not keep the
+    /// remaining entries alive.
+    fn unlink(entry: &Entry<T>) {
+        entry.link_ref().swap((None, Tag::First), Relaxed);
+    }
+}
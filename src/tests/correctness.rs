@@ -1,12 +1,15 @@
 #[cfg(test)]
 mod hashmap_test {
     use crate::ebr;
-    use crate::{HashIndex, HashMap};
+    use crate::hash_map::{Entry, InsertError, Overflow};
+    use crate::{HashCache, HashIndex, HashIndexSet, HashMap, HashSet};
 
+    use std::collections::hash_map::RandomState;
     use std::collections::BTreeSet;
     use std::hash::{Hash, Hasher};
+    use std::ops::ControlFlow;
     use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
-    use std::sync::atomic::{AtomicU64, AtomicUsize};
+    use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize};
     use std::sync::{Arc, Barrier};
     use std::thread;
 
@@ -194,6 +197,98 @@ mod hashmap_test {
         assert_eq!(hashmap.len(), 0);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn hashmap_entry() {
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::new(HashMap::default());
+
+        let num_tasks = 8;
+        let workload_size = 256;
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        for task_id in 0..num_tasks {
+            let barrier_cloned = barrier.clone();
+            let hashmap_cloned = hashmap.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_cloned.wait().await;
+                for id in 0..workload_size {
+                    let entry = hashmap_cloned.entry_async(id).await;
+                    entry.and_modify(|v| *v += 1).or_insert(1);
+                }
+                let range = (task_id * workload_size)..((task_id + 1) * workload_size);
+                for id in range.clone() {
+                    match hashmap_cloned.entry(id + num_tasks * workload_size) {
+                        Entry::Occupied(_) => unreachable!(),
+                        Entry::Vacant(v) => {
+                            let mut o = v.insert_entry(id);
+                            assert_eq!(*o.get(), id);
+                            *o.get_mut() += 1;
+                            assert_eq!(o.insert(id + 2), id + 1);
+                        }
+                    }
+                }
+                for id in range {
+                    let key = id + num_tasks * workload_size;
+                    if let Entry::Occupied(o) = hashmap_cloned.entry_async(key).await {
+                        assert_eq!(o.remove_entry(), (key, id + 2));
+                    } else {
+                        unreachable!();
+                    }
+                    assert!(!hashmap_cloned.contains(&key));
+                }
+            }));
+        }
+
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+
+        assert_eq!(hashmap.len(), workload_size);
+        hashmap.for_each(|_, v| assert_eq!(*v, num_tasks));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn hashmap_get() {
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::new(HashMap::default());
+        let workload_size = 256;
+        for k in 0..workload_size {
+            assert!(hashmap.insert(k, 0).is_ok());
+        }
+
+        let num_tasks = 8;
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        for task_id in 0..num_tasks {
+            let barrier_cloned = barrier.clone();
+            let hashmap_cloned = hashmap.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_cloned.wait().await;
+                for k in 0..workload_size {
+                    if task_id % 2 == 0 {
+                        let mut guard = hashmap_cloned.get_mut_async(&k).await.unwrap();
+                        assert_eq!(*guard.key(), k);
+                        *guard += 1;
+                    } else {
+                        let mut guard = hashmap_cloned.get_mut(&k).unwrap();
+                        *guard.get_mut() += 1;
+                    }
+                    let guard = hashmap_cloned.get_async(&k).await.unwrap();
+                    assert_eq!(*guard.key(), k);
+                    assert!(*guard >= 1);
+                    drop(guard);
+                    assert!(hashmap_cloned.get(&(k + workload_size)).is_none());
+                }
+            }));
+        }
+
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+
+        for k in 0..workload_size {
+            assert_eq!(*hashmap.get(&k).unwrap(), num_tasks);
+        }
+    }
+
     #[test]
     fn hashmap_string_key() {
         let hashmap1: HashMap<String, u32> = HashMap::default();
@@ -290,6 +385,403 @@ mod hashmap_test {
         }
     }
 
+    #[test]
+    fn hashmap_visitor() {
+        let data_size = 4096;
+        for _ in 0..16 {
+            let hashmap: Arc<HashMap<u64, u64>> = Arc::new(HashMap::default());
+            let hashmap_copied = hashmap.clone();
+            let barrier = Arc::new(Barrier::new(2));
+            let barrier_copied = barrier.clone();
+            let inserted = Arc::new(AtomicU64::new(0));
+            let inserted_copied = inserted.clone();
+            let removed = Arc::new(AtomicU64::new(data_size));
+            let removed_copied = removed.clone();
+            let thread_handle = thread::spawn(move || {
+                // test insert
+                for _ in 0..2 {
+                    barrier_copied.wait();
+                    let mut checker = BTreeSet::new();
+                    let max = inserted_copied.load(Acquire);
+                    for entry in hashmap_copied.iter(&ebr::Barrier::new()) {
+                        assert_eq!(*entry.key(), *entry);
+                        checker.insert(*entry.key());
+                    }
+                    for key in 0..max {
+                        assert!(checker.contains(&key));
+                    }
+                }
+                // test remove
+                for _ in 0..2 {
+                    barrier_copied.wait();
+                    let max = removed_copied.load(Acquire);
+                    for entry in hashmap_copied.iter(&ebr::Barrier::new()) {
+                        assert!(*entry.key() < max);
+                    }
+                }
+            });
+            // insert
+            barrier.wait();
+            for i in 0..data_size {
+                if i == data_size / 2 {
+                    barrier.wait();
+                }
+                assert!(hashmap.insert(i, i).is_ok());
+                inserted.store(i, Release);
+            }
+            // remove
+            barrier.wait();
+            for i in (0..data_size).rev() {
+                if i == data_size / 2 {
+                    barrier.wait();
+                }
+                assert!(hashmap.remove(&i).is_some());
+                removed.store(i, Release);
+            }
+            thread_handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn hashmap_visitor_guards() {
+        let hashmap: HashMap<u64, u64> = HashMap::default();
+        for i in 0..256 {
+            assert!(hashmap.insert(i, i).is_ok());
+        }
+        let guards: Vec<_> = hashmap.iter(&ebr::Barrier::new()).collect();
+        let keys: BTreeSet<u64> = guards.iter().map(|e| *e.key()).collect();
+        assert_eq!(guards.len(), 256);
+        assert_eq!(keys.len(), 256);
+        drop(guards);
+        assert!(hashmap.insert(256, 256).is_ok());
+        assert_eq!(hashmap.len(), 257);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn hashmap_scan_while() {
+        let num_tasks = 4;
+        let workload_size = 1024;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::new(HashMap::default());
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        for task_id in 0..num_tasks {
+            let barrier_copied = barrier.clone();
+            let hashmap_copied = hashmap.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_copied.wait().await;
+                let range = (task_id * workload_size)..((task_id + 1) * workload_size);
+                for id in range.clone() {
+                    assert!(hashmap_copied.insert_async(id, id).await.is_ok());
+                    if id % 16 == 0 {
+                        let first = range.start;
+                        assert!(hashmap_copied.any(|k, _| *k == first));
+                        assert!(hashmap_copied.any_async(|k, _| *k == id).await);
+                        let found = hashmap_copied.find_async(|k, _| *k == id).await.unwrap();
+                        assert_eq!(*found.get(), id);
+                        drop(found);
+                        let result = hashmap_copied.scan_while(|k, v| {
+                            if *k == id {
+                                ControlFlow::Break(*v)
+                            } else {
+                                ControlFlow::Continue(())
+                            }
+                        });
+                        assert_eq!(result, Some(id));
+                    }
+                }
+                barrier_copied.wait().await;
+                assert!(!hashmap_copied.any(|k, _| *k >= workload_size * num_tasks));
+                let result = hashmap_copied
+                    .scan_while_async(|k, _| {
+                        if *k == range.end - 1 {
+                            ControlFlow::Break(*k)
+                        } else {
+                            ControlFlow::Continue(())
+                        }
+                    })
+                    .await;
+                assert_eq!(result, Some(range.end - 1));
+            }));
+        }
+
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+
+        let hashset: HashSet<usize> = HashSet::default();
+        for i in 0..workload_size {
+            assert!(hashset.insert(i).is_ok());
+        }
+        let mut visited = 0;
+        assert_eq!(
+            hashset.scan_while(|k| {
+                visited += 1;
+                if *k % 7 == 6 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            }),
+            Some(())
+        );
+        assert!(visited < workload_size);
+        assert!(hashset.any(|k| *k == 7));
+        assert!(hashset.find_async(|k| *k == workload_size).await.is_none());
+        assert_eq!(*hashset.find(|k| *k == 7).unwrap().key(), 7);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn hashmap_limit() {
+        let num_tasks = 4;
+        let workload_size = 1024;
+        let max_entries = 1000;
+        let hashmap: Arc<HashMap<usize, usize>> = Arc::new(HashMap::with_limit(
+            0,
+            max_entries,
+            Overflow::Reject,
+            RandomState::new(),
+        ));
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        for task_id in 0..num_tasks {
+            let barrier_copied = barrier.clone();
+            let hashmap_copied = hashmap.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_copied.wait().await;
+                let mut num_inserted = 0;
+                for id in (task_id * workload_size)..((task_id + 1) * workload_size) {
+                    match hashmap_copied.try_insert_async(id, id).await {
+                        Ok(()) => num_inserted += 1,
+                        Err(InsertError::Full(k, v)) => assert_eq!(k, v),
+                        Err(InsertError::Occupied(..)) => unreachable!(),
+                    }
+                }
+                num_inserted
+            }));
+        }
+        let mut num_inserted = 0;
+        for r in futures::future::join_all(task_handles).await {
+            num_inserted += r.unwrap();
+        }
+        assert_eq!(num_inserted, max_entries);
+        assert_eq!(hashmap.len(), max_entries);
+        assert!(hashmap.insert(usize::MAX, 0).is_err());
+        let (_, num_removed) = hashmap.retain(|k, _| k % 2 == 0);
+        let num_reinserted = (0..workload_size * num_tasks)
+            .filter(|k| k % 2 != 0)
+            .filter_map(|k| hashmap.insert(k, k).ok())
+            .count();
+        assert_eq!(num_reinserted, num_removed);
+        assert_eq!(hashmap.len(), max_entries);
+
+        let hashmap: HashMap<usize, usize> = HashMap::with_limit(
+            0,
+            max_entries,
+            Overflow::Evict(Box::new(|c: (&usize, &usize), s: (&usize, &usize)| {
+                c.1 < s.1
+            })),
+            RandomState::new(),
+        );
+        for id in 0..workload_size * num_tasks {
+            assert!(hashmap.insert(id, id).is_ok());
+            assert!(hashmap.len() <= max_entries);
+        }
+        assert_eq!(hashmap.len(), max_entries);
+        assert!(hashmap.contains(&(workload_size * num_tasks - 1)));
+        assert!(hashmap.remove(&(workload_size * num_tasks - 1)).is_some());
+        hashmap.upsert(usize::MAX, || 0, |_, _| unreachable!());
+        assert_eq!(hashmap.len(), max_entries);
+        hashmap.upsert(usize::MAX - 1, || 0, |_, _| unreachable!());
+        assert!(hashmap.len() <= max_entries + 1);
+        let len = hashmap.len();
+        assert_eq!(hashmap.clear(), len);
+        for id in 0..max_entries {
+            assert!(hashmap.insert(id, id).is_ok());
+        }
+        assert_eq!(hashmap.len(), max_entries);
+    }
+
+    #[test]
+    fn hashmap_limit_accounting() {
+        let workload_size = 4096;
+        let max_entries = 1000;
+        let hashmap: HashMap<usize, usize> = HashMap::with_limit(
+            0,
+            max_entries,
+            Overflow::Evict(Box::new(|c: (&usize, &usize), s: (&usize, &usize)| {
+                c.1 < s.1
+            })),
+            RandomState::new(),
+        );
+        for id in 0..workload_size {
+            assert!(hashmap.insert(id, id).is_ok());
+            let mut num_scanned = 0;
+            hashmap.scan(|_, _| num_scanned += 1);
+            assert_eq!(hashmap.len(), num_scanned);
+        }
+        assert_eq!(hashmap.len(), max_entries);
+        assert_eq!(hashmap.clear(), max_entries);
+        for id in 0..max_entries {
+            assert!(hashmap.try_upsert(id, || id, |_, _| unreachable!()).is_ok());
+        }
+        assert_eq!(hashmap.len(), max_entries);
+
+        let hashmap: HashMap<usize, usize> =
+            HashMap::with_limit(0, max_entries, Overflow::Reject, RandomState::new());
+        for id in 0..max_entries {
+            assert!(hashmap.try_upsert(id, || id, |_, _| unreachable!()).is_ok());
+        }
+        assert!(hashmap.try_upsert(0, || 1, |_, v| *v = 1).is_ok());
+        assert_eq!(
+            hashmap.try_upsert(max_entries, || 0, |_, _| unreachable!()),
+            Err(InsertError::Full(max_entries, 0))
+        );
+        if let Entry::Vacant(v) = hashmap.entry(max_entries) {
+            assert_eq!(
+                v.try_insert_entry(0).err(),
+                Some(InsertError::Full(max_entries, 0))
+            );
+        }
+        assert_eq!(hashmap.len(), max_entries);
+
+        // `upsert` and `insert_entry` never exceed the limit.
+        let hashmap_ref = std::panic::AssertUnwindSafe(&hashmap);
+        assert!(std::panic::catch_unwind(|| {
+            hashmap_ref.upsert(max_entries, || 0, |_, _| unreachable!());
+        })
+        .is_err());
+        assert!(std::panic::catch_unwind(|| {
+            if let Entry::Vacant(v) = hashmap_ref.entry(max_entries) {
+                v.insert_entry(0);
+            }
+        })
+        .is_err());
+        hashmap.upsert(1, || 0, |_, v| *v = 1);
+        assert_eq!(hashmap.len(), max_entries);
+        assert!(hashmap.remove(&0).is_some());
+        if let Entry::Vacant(v) = hashmap.entry(0) {
+            assert_eq!(v.try_insert_entry(0).unwrap().get(), &0);
+        }
+        assert_eq!(
+            hashmap.try_insert(max_entries, 0),
+            Err(InsertError::Full(max_entries, 0))
+        );
+        assert_eq!(hashmap.len(), max_entries);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn hashcache() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+        let num_tasks = 4;
+        let workload_size = 4096;
+        let hashcache: Arc<HashCache<usize, R>> =
+            Arc::new(HashCache::new(1024, RandomState::new()));
+        let capacity = hashcache.capacity();
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        for task_id in 0..num_tasks {
+            let barrier_copied = barrier.clone();
+            let hashcache_copied = hashcache.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_copied.wait().await;
+                let mut num_evicted = 0;
+                for id in (task_id * workload_size)..((task_id + 1) * workload_size) {
+                    let result = hashcache_copied.put_async(id, R::new(&INST_CNT)).await;
+                    if let Some((k, _)) = result {
+                        assert_ne!(k, id);
+                        num_evicted += 1;
+                    }
+                    if let Some(entry) = hashcache_copied.get_async(&id).await {
+                        assert_eq!(*entry.key(), id);
+                    }
+                }
+                num_evicted
+            }));
+        }
+        let mut num_evicted = 0;
+        for r in futures::future::join_all(task_handles).await {
+            num_evicted += r.unwrap();
+        }
+        assert!(hashcache.len() <= capacity);
+        assert_eq!(hashcache.len() + num_evicted, workload_size * num_tasks);
+        assert_eq!(INST_CNT.load(Relaxed), hashcache.len());
+        let len = hashcache.len();
+        assert_eq!(hashcache.clear(), len);
+        assert_eq!(INST_CNT.load(Relaxed), 0);
+
+        // A key that is read between puts is never evicted.
+        let hot = usize::MAX;
+        assert!(hashcache.put(hot, R::new(&INST_CNT)).is_none());
+        for id in 0..workload_size * num_tasks {
+            if let Some((k, _)) = hashcache.put(id, R::new(&INST_CNT)) {
+                assert_ne!(k, hot);
+            }
+            assert!(hashcache.read(&hot, |k, _| *k).is_some());
+        }
+        assert_eq!(INST_CNT.load(Relaxed), hashcache.len());
+
+        // Putting an existing key replaces the value and keeps the entry.
+        let len = hashcache.len();
+        let (k, _) = hashcache.put(hot, R::new(&INST_CNT)).unwrap();
+        assert_eq!(k, hot);
+        assert_eq!(hashcache.len(), len);
+        assert_eq!(INST_CNT.load(Relaxed), len);
+        assert!(hashcache.remove(&hot).is_some());
+        assert!(!hashcache.contains(&hot));
+    }
+
+    #[test]
+    fn hashset_algebra() {
+        let workload_size = 1024;
+        let evens: Arc<HashSet<usize>> = Arc::new(HashSet::default());
+        let triples: Arc<HashSet<usize>> = Arc::new(HashSet::default());
+        assert_eq!(evens.extend((0..workload_size).filter(|k| k % 2 == 0)), 512);
+        assert_eq!(
+            triples.extend((0..workload_size).filter(|k| k % 3 == 0)),
+            342
+        );
+        let others: HashSet<usize> = HashSet::default();
+        assert_eq!(
+            others.extend((0..workload_size).filter(|k| k % 6 == 5)),
+            170
+        );
+
+        // Keys that are congruent to 1 modulo 6 are in neither of the stable parts of the sets.
+        let done = Arc::new(AtomicBool::new(false));
+        let triples_cloned = triples.clone();
+        let done_cloned = done.clone();
+        let churn = thread::spawn(move || {
+            while !done_cloned.load(Acquire) {
+                triples_cloned.extend((0..workload_size).filter(|k| k % 6 == 1));
+                triples_cloned.retain(|k| k % 6 != 1);
+            }
+        });
+        for _ in 0..16 {
+            assert_eq!(evens.intersection_count(&triples), 171);
+            assert!(!evens.is_subset(&triples));
+            assert!(others.is_disjoint(&triples));
+            assert!(others.is_disjoint(&evens));
+            let barrier = ebr::Barrier::new();
+            assert_eq!(
+                evens.iter(&barrier).filter(|k| *k.key() % 3 == 0).count(),
+                171
+            );
+        }
+        done.store(true, Release);
+        assert!(churn.join().is_ok());
+
+        // Retaining keys against each other concurrently leaves the intersection in both.
+        let evens_cloned = evens.clone();
+        let triples_cloned = triples.clone();
+        let retain = thread::spawn(move || evens_cloned.retain_in(&triples_cloned));
+        let (num_retained, _) = triples.retain_in(&evens);
+        assert_eq!(num_retained, 171);
+        assert_eq!(retain.join().unwrap(), (171, 341));
+        assert!(evens.is_subset(&triples));
+        assert!(triples.is_subset(&evens));
+        assert_eq!(evens.len(), 171);
+    }
+
     struct Data {
         data: usize,
         checker: Arc<AtomicUsize>,
@@ -409,6 +901,59 @@ mod hashmap_test {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn hashindex_update() {
+        let num_tasks = 4;
+        let num_keys = 64;
+        let num_updates = 256;
+        let hashindex: Arc<HashIndex<usize, usize>> = Arc::new(HashIndex::default());
+        for k in 0..num_keys {
+            assert!(hashindex.insert(k, 0).is_ok());
+        }
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        for task_id in 0..num_tasks {
+            let barrier_copied = barrier.clone();
+            let hashindex_copied = hashindex.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_copied.wait().await;
+                if task_id % 2 == 0 {
+                    for _ in 0..num_updates {
+                        for k in 0..num_keys {
+                            if task_id == 0 {
+                                assert!(hashindex_copied.update(&k, |v| *v + 1));
+                            } else {
+                                assert!(hashindex_copied.update_async(&k, |v| *v + 1).await);
+                            }
+                        }
+                    }
+                } else {
+                    let mut last = vec![0; num_keys];
+                    for _ in 0..num_updates {
+                        for (k, last) in last.iter_mut().enumerate() {
+                            let v = hashindex_copied.read(&k, |_, v| *v).unwrap();
+                            assert!(v >= *last);
+                            *last = v;
+                        }
+                    }
+                }
+            }));
+        }
+
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+
+        assert_eq!(hashindex.len(), num_keys);
+        for k in 0..num_keys {
+            assert_eq!(
+                hashindex.read(&k, |_, v| *v),
+                Some(num_updates * num_tasks / 2)
+            );
+        }
+        assert!(!hashindex.update(&num_keys, |v| *v + 1));
+    }
+
     #[test]
     fn hashindex_string_key() {
         let hashindex1: HashIndex<String, u32> = HashIndex::default();
@@ -498,16 +1043,61 @@ mod hashmap_test {
             thread_handle.join().unwrap();
         }
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn hashindexset() {
+        let hashindexset: Arc<HashIndexSet<usize>> = Arc::new(HashIndexSet::default());
+        let num_tasks = 4;
+        let workload_size = 256;
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        for task_id in 0..num_tasks {
+            let barrier_cloned = barrier.clone();
+            let hashindexset_cloned = hashindexset.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_cloned.wait().await;
+                let range = (task_id * workload_size)..((task_id + 1) * workload_size);
+                for id in range.clone() {
+                    assert!(hashindexset_cloned.insert_async(id).await.is_ok());
+                    assert_eq!(hashindexset_cloned.insert(id), Err(id));
+                }
+                for id in range.clone() {
+                    assert!(hashindexset_cloned.contains(&id));
+                    assert_eq!(hashindexset_cloned.read(&id, |k| *k), Some(id));
+                }
+                for id in range.clone().filter(|id| id % 2 == 0) {
+                    assert!(hashindexset_cloned.remove_async(&id).await);
+                    assert!(!hashindexset_cloned.remove_if(&(id + 1), || false));
+                }
+                for id in range {
+                    assert_eq!(hashindexset_cloned.contains(&id), id % 2 == 1);
+                }
+            }));
+        }
+
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+
+        let barrier = ebr::Barrier::new();
+        let keys: BTreeSet<usize> = hashindexset.iter(&barrier).copied().collect();
+        assert_eq!(keys.len(), num_tasks * workload_size / 2);
+        assert!(keys.iter().all(|k| k % 2 == 1));
+        assert_eq!(hashindexset.len(), keys.len());
+        assert_eq!(hashindexset.clear_async().await, keys.len());
+        assert!(hashindexset.is_empty());
+    }
 }
 
 #[cfg(test)]
 mod treeindex_test {
     use crate::ebr;
-    use crate::TreeIndex;
+    use crate::{SharedTreeIndex, TreeIndex, TreeSet};
 
     use proptest::strategy::{Strategy, ValueTree};
     use proptest::test_runner::TestRunner;
     use std::collections::BTreeSet;
+    use std::ops::Bound::Excluded;
     use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
     use std::sync::atomic::{AtomicBool, AtomicUsize};
     use std::sync::{Arc, Barrier};
@@ -594,11 +1184,61 @@ mod treeindex_test {
         }
     }
 
-    #[tokio::test(flavor = "multi_thread", worker_threads = 16)]
-    async fn integer_key() {
-        let num_tasks = 8;
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn update() {
+        let num_tasks = 4;
         let workload_size = 256;
-        for _ in 0..256 {
+        for _ in 0..16 {
+            let tree: Arc<TreeIndex<usize, usize>> = Arc::new(TreeIndex::default());
+            for k in 0..workload_size {
+                assert!(tree.insert(k, 0).is_ok());
+            }
+            let mut task_handles = Vec::with_capacity(num_tasks);
+            let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+            for task_id in 0..num_tasks {
+                let barrier_cloned = barrier.clone();
+                let tree_cloned = tree.clone();
+                task_handles.push(tokio::task::spawn(async move {
+                    barrier_cloned.wait().await;
+                    if task_id % 2 == 0 {
+                        for k in 0..workload_size {
+                            if task_id == 0 {
+                                assert!(tree_cloned.update(&k, |v| *v + 1));
+                            } else {
+                                assert!(tree_cloned.update_async(&k, |v| *v + 1).await);
+                            }
+                            let new_key = workload_size * (task_id + 1) + k;
+                            tree_cloned.upsert_async(new_key, 0).await;
+                            tree_cloned.upsert(new_key, 1);
+                        }
+                    } else {
+                        let mut last_values = vec![0; workload_size];
+                        for _ in 0..4 {
+                            for (k, last) in last_values.iter_mut().enumerate() {
+                                let value = tree_cloned.read(&k, |_, v| *v).unwrap();
+                                assert!(value >= *last);
+                                *last = value;
+                            }
+                        }
+                    }
+                }));
+            }
+            for r in futures::future::join_all(task_handles).await {
+                assert!(r.is_ok());
+            }
+            for k in 0..workload_size {
+                assert_eq!(tree.read(&k, |_, v| *v), Some(num_tasks / 2));
+            }
+            assert_eq!(tree.len(), workload_size * (num_tasks / 2 + 1));
+            assert!(!tree.update(&(workload_size * 16), |v| *v + 1));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 16)]
+    async fn integer_key() {
+        let num_tasks = 8;
+        let workload_size = 256;
+        for _ in 0..256 {
             let tree: Arc<TreeIndex<usize, usize>> = Arc::new(TreeIndex::default());
             let mut task_handles = Vec::with_capacity(num_tasks);
             let barrier = Arc::new(AsyncBarrier::new(num_tasks));
@@ -991,15 +1631,850 @@ mod treeindex_test {
             thread_handles.into_iter().for_each(|t| t.join().unwrap());
         }
     }
+
+    #[test]
+    fn scanner_rev() {
+        let data_size = 4096;
+        let tree: TreeIndex<usize, usize> = TreeIndex::default();
+        for k in 0..data_size {
+            assert!(tree.insert(k, k).is_ok());
+        }
+        let barrier = ebr::Barrier::new();
+        assert!(tree
+            .iter_rev(&barrier)
+            .map(|(k, _)| *k)
+            .eq((0..data_size).rev()));
+        assert!(tree
+            .range(..data_size / 2, &barrier)
+            .rev()
+            .map(|(k, _)| *k)
+            .eq((0..data_size / 2).rev()));
+        assert!(tree
+            .range(7..=data_size / 3, &barrier)
+            .rev()
+            .map(|(k, _)| *k)
+            .eq((7..=data_size / 3).rev()));
+        assert!(tree.range(data_size.., &barrier).next_back().is_none());
+
+        // Both ends meet without visiting a key twice.
+        let mut visitor = tree.iter(&barrier);
+        let mut visited = BTreeSet::new();
+        for i in 0.. {
+            let entry = if i % 3 == 0 {
+                visitor.next_back()
+            } else {
+                visitor.next()
+            };
+            if let Some((k, _)) = entry {
+                assert!(visited.insert(*k));
+            } else {
+                break;
+            }
+        }
+        assert_eq!(visited.len(), data_size);
+        assert!(visitor.next().is_none());
+        assert!(visitor.next_back().is_none());
+        assert!(visitor.next().is_none());
+
+        // Drained iterators do not start over.
+        let mut visitor = tree.iter(&barrier);
+        assert_eq!(visitor.by_ref().count(), data_size);
+        assert!(visitor.next().is_none());
+        assert!(visitor.next_back().is_none());
+        let mut range = tree.range(..data_size / 2, &barrier);
+        assert_eq!(range.by_ref().rev().count(), data_size / 2);
+        assert!(range.next_back().is_none());
+        assert!(range.next().is_none());
+        drop(barrier);
+
+        // Keys that are not modified are always visited in descending order.
+        let tree = Arc::new(tree);
+        let finished = Arc::new(AtomicBool::new(false));
+        let tree_cloned = tree.clone();
+        let finished_cloned = finished.clone();
+        let thread_handle = thread::spawn(move || {
+            for k in (0..data_size).filter(|k| k % 2 == 1) {
+                assert!(tree_cloned.remove(&k));
+            }
+            for k in data_size..data_size * 2 {
+                assert!(tree_cloned.insert(k, k).is_ok());
+            }
+            finished_cloned.store(true, Release);
+        });
+        let mut done = false;
+        while !done {
+            done = finished.load(Acquire);
+            let barrier = ebr::Barrier::new();
+            let mut prev = usize::MAX;
+            let mut num_even_keys = 0;
+            for (k, _) in tree.range(..data_size, &barrier).rev() {
+                assert!(*k < prev);
+                prev = *k;
+                if k % 2 == 0 {
+                    num_even_keys += 1;
+                }
+            }
+            assert_eq!(num_even_keys, data_size / 2);
+        }
+        thread_handle.join().unwrap();
+    }
+
+    #[test]
+    fn navigation() {
+        let data_size = 4096;
+        let tree: TreeIndex<usize, usize> = TreeIndex::default();
+        let barrier = ebr::Barrier::new();
+        assert!(tree.first(&barrier).is_none());
+        assert!(tree.last(&barrier).is_none());
+        assert!(tree.floor(&0, &barrier).is_none());
+        assert!(tree.ceiling(&0, &barrier).is_none());
+
+        // Only multiples of three are inserted.
+        for k in (0..data_size).filter(|k| k % 3 == 0) {
+            assert!(tree.insert(k, k).is_ok());
+        }
+        let max_key = (data_size - 1) / 3 * 3;
+        assert_eq!(tree.first(&barrier), Some((&0, &0)));
+        assert_eq!(tree.last(&barrier), Some((&max_key, &max_key)));
+        for k in 0..=data_size {
+            let floor = (k / 3 * 3).min(max_key);
+            let ceiling = k.div_ceil(3) * 3;
+            assert_eq!(tree.floor(&k, &barrier).map(|(k, _)| *k), Some(floor));
+            assert_eq!(
+                tree.ceiling(&k, &barrier).map(|(k, _)| *k),
+                Some(ceiling).filter(|c| *c <= max_key)
+            );
+            assert_eq!(
+                tree.lower_bound(Excluded(&k), &barrier).map(|(k, _)| *k),
+                Some((k / 3 + 1) * 3).filter(|c| *c <= max_key)
+            );
+            assert_eq!(
+                tree.upper_bound(Excluded(&k), &barrier).map(|(k, _)| *k),
+                (k > 0).then(|| ((k - 1) / 3 * 3).min(max_key))
+            );
+        }
+
+        // The keys in between are found in a leaf after the leaves that no longer have them.
+        for k in (300..3900).filter(|k| k % 3 == 0) {
+            assert!(tree.remove(&k));
+        }
+        for k in 298..3900 {
+            assert_eq!(tree.ceiling(&k, &barrier).map(|(k, _)| *k), Some(3900));
+        }
+        for k in (300..3900).filter(|k| k % 3 == 0) {
+            assert!(tree.insert(k, k).is_ok());
+        }
+        drop(barrier);
+
+        // Keys that are not modified are always found.
+        let tree = Arc::new(tree);
+        let finished = Arc::new(AtomicBool::new(false));
+        let tree_cloned = tree.clone();
+        let finished_cloned = finished.clone();
+        let thread_handle = thread::spawn(move || {
+            for k in (0..data_size).filter(|k| k % 3 != 0) {
+                assert!(tree_cloned.insert(k, k).is_ok());
+            }
+            for k in (0..data_size).filter(|k| k % 3 != 0) {
+                assert!(tree_cloned.remove(&k));
+            }
+            finished_cloned.store(true, Release);
+        });
+        let mut done = false;
+        while !done {
+            done = finished.load(Acquire);
+            let barrier = ebr::Barrier::new();
+            for k in (0..data_size).filter(|k| k % 3 == 1) {
+                let floor = *tree.floor(&k, &barrier).unwrap().0;
+                assert!(floor == k || floor == k - 1);
+                let ceiling = tree.ceiling(&k, &barrier).map(|(k, _)| *k);
+                assert!(ceiling.map_or(k + 2 > max_key, |c| c == k || c == k + 1 || c == k + 2));
+            }
+        }
+        thread_handle.join().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn pop() {
+        let num_tasks = 4;
+        let workload_size = 1024;
+        for _ in 0..16 {
+            let tree: Arc<TreeIndex<usize, usize>> = Arc::new(TreeIndex::default());
+            for k in 0..workload_size {
+                assert!(tree.insert(k, k).is_ok());
+            }
+            let mut task_handles = Vec::with_capacity(num_tasks);
+            let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+            for task_id in 0..num_tasks {
+                let barrier_cloned = barrier.clone();
+                let tree_cloned = tree.clone();
+                task_handles.push(tokio::task::spawn(async move {
+                    barrier_cloned.wait().await;
+                    let mut popped = Vec::new();
+                    loop {
+                        let entry = match task_id {
+                            0 => tree_cloned.pop_first(),
+                            1 => tree_cloned.pop_first_async().await,
+                            2 => tree_cloned.pop_last(),
+                            _ => tree_cloned.pop_last_async().await,
+                        };
+                        if let Some((k, v)) = entry {
+                            assert_eq!(k, v);
+                            if let Some(prev) = popped.last() {
+                                assert!(if task_id < 2 { *prev < k } else { *prev > k });
+                            }
+                            popped.push(k);
+                        } else {
+                            break;
+                        }
+                    }
+                    popped
+                }));
+            }
+            let mut popped = BTreeSet::new();
+            for r in futures::future::join_all(task_handles).await {
+                for k in r.unwrap() {
+                    assert!(popped.insert(k));
+                }
+            }
+            assert_eq!(popped.len(), workload_size);
+            assert!(tree.is_empty());
+            assert!(tree.insert(0, 0).is_ok());
+            assert_eq!(tree.pop_last(), Some((0, 0)));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn remove_range() {
+        let num_tasks = 4;
+        let workload_size = 1024;
+        for _ in 0..16 {
+            let tree: Arc<TreeIndex<usize, usize>> = Arc::new(TreeIndex::default());
+            let mut task_handles = Vec::with_capacity(num_tasks);
+            let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+            for task_id in 0..num_tasks {
+                let barrier_cloned = barrier.clone();
+                let tree_cloned = tree.clone();
+                task_handles.push(tokio::task::spawn(async move {
+                    barrier_cloned.wait().await;
+                    let start = task_id * workload_size;
+                    let end = start + workload_size;
+                    for k in start..end {
+                        assert!(tree_cloned.insert(k, k).is_ok());
+                    }
+                    let mid = start + workload_size / 2;
+                    if task_id % 2 == 0 {
+                        assert_eq!(tree_cloned.remove_range(start + 1..mid), mid - start - 1);
+                        assert_eq!(tree_cloned.remove_range_async(start..=mid).await, 2);
+                    } else {
+                        assert_eq!(
+                            tree_cloned.remove_range_async(start + 1..mid).await,
+                            mid - start - 1
+                        );
+                        assert_eq!(tree_cloned.remove_range(start..=mid), 2);
+                    }
+                    let barrier = ebr::Barrier::new();
+                    assert_eq!(
+                        tree_cloned.range(start..end, &barrier).count(),
+                        end - mid - 1
+                    );
+                    assert_eq!(
+                        tree_cloned.ceiling(&start, &barrier).map(|(k, _)| *k),
+                        Some(mid + 1)
+                    );
+                    drop(barrier);
+                    assert_eq!(tree_cloned.remove_range(mid + 1..end), end - mid - 1);
+                }));
+            }
+            for r in futures::future::join_all(task_handles).await {
+                assert!(r.is_ok());
+            }
+            assert!(tree.is_empty());
+            assert_eq!(tree.remove_range(..), 0);
+        }
+
+        let tree: TreeIndex<usize, usize> = TreeIndex::default();
+        for k in 0..workload_size * 4 {
+            assert!(tree.insert(k, k).is_ok());
+        }
+        assert_eq!(
+            tree.remove_range(100..workload_size * 4 - 100),
+            workload_size * 4 - 200
+        );
+        assert_eq!(tree.remove_range((Excluded(50), Excluded(150))), 49);
+        let barrier = ebr::Barrier::new();
+        assert!(tree
+            .iter(&barrier)
+            .map(|(k, _)| *k)
+            .eq((0..=50).chain(workload_size * 4 - 100..workload_size * 4)));
+        drop(barrier);
+        assert_eq!(tree.remove_range(..), 151);
+        assert!(tree.is_empty());
+        assert!(tree.insert(0, 0).is_ok());
+        assert_eq!(tree.len(), 1);
+    }
+
+    #[test]
+    fn from_sorted_iter() {
+        for workload_size in [0, 1, 14, 15, 16, 210, 225, 226, 4096, 65536] {
+            let tree: TreeIndex<usize, usize> =
+                TreeIndex::from_sorted_iter((0..workload_size).map(|k| (k * 2, k)));
+            assert_eq!(tree.len(), workload_size);
+            let barrier = ebr::Barrier::new();
+            assert!(tree
+                .iter(&barrier)
+                .map(|(k, v)| (*k, *v))
+                .eq((0..workload_size).map(|k| (k * 2, k))));
+            assert!(tree
+                .iter_rev(&barrier)
+                .map(|(k, _)| *k)
+                .eq((0..workload_size).rev().map(|k| k * 2)));
+            assert_eq!(
+                tree.range(workload_size / 2..workload_size, &barrier)
+                    .count(),
+                (workload_size / 2..workload_size)
+                    .filter(|k| k % 2 == 0)
+                    .count()
+            );
+            drop(barrier);
+
+            // Fully packed leaves are split by concurrent insertions.
+            let tree = Arc::new(tree);
+            let num_threads = 4;
+            let mut thread_handles = Vec::with_capacity(num_threads);
+            for thread_id in 0..num_threads {
+                let tree_cloned = tree.clone();
+                thread_handles.push(thread::spawn(move || {
+                    for k in (0..workload_size).filter(|k| k % num_threads == thread_id) {
+                        assert!(tree_cloned.insert(k * 2 + 1, k).is_ok());
+                        assert!(tree_cloned.remove(&(k * 2)));
+                    }
+                }));
+            }
+            for handle in thread_handles {
+                handle.join().unwrap();
+            }
+            assert_eq!(tree.len(), workload_size);
+            let barrier = ebr::Barrier::new();
+            assert!(tree
+                .iter(&barrier)
+                .map(|(k, _)| *k)
+                .eq((0..workload_size).map(|k| k * 2 + 1)));
+        }
+
+        let tree: TreeIndex<usize, usize> =
+            TreeIndex::from_sorted_iter([(1, 0), (3, 0), (2, 0), (3, 1), (4, 0)]);
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.read(&3, |_, v| *v), Some(0));
+
+        let tree: TreeIndex<usize, usize> =
+            (0..4096).rev().chain(1024..2048).map(|k| (k, k)).collect();
+        assert_eq!(tree.len(), 4096);
+        let barrier = ebr::Barrier::new();
+        assert!(tree.iter(&barrier).all(|(k, v)| *k == *v));
+        assert!(tree.iter(&barrier).map(|(k, _)| *k).eq(0..4096));
+    }
+
+    #[test]
+    fn config() {
+        let workload_size = 4096;
+        for (leaf_capacity, fan_out) in [(2, 3), (3, 4), (4, 8), (7, 5), (14, 15)] {
+            let tree: Arc<TreeIndex<usize, usize>> =
+                Arc::new(TreeIndex::with_config(leaf_capacity, fan_out));
+            assert_eq!(tree.leaf_capacity(), leaf_capacity);
+            assert_eq!(tree.fan_out(), fan_out);
+            let num_threads = 4;
+            let mut thread_handles = Vec::with_capacity(num_threads);
+            for thread_id in 0..num_threads {
+                let tree_cloned = tree.clone();
+                thread_handles.push(thread::spawn(move || {
+                    for k in (0..workload_size).filter(|k| k % num_threads == thread_id) {
+                        assert!(tree_cloned.insert(k, k).is_ok());
+                    }
+                    for k in (0..workload_size).filter(|k| k % num_threads == thread_id) {
+                        if k % 2 == 0 {
+                            assert!(tree_cloned.remove(&k));
+                        }
+                    }
+                }));
+            }
+            for handle in thread_handles {
+                handle.join().unwrap();
+            }
+            assert_eq!(tree.len(), workload_size / 2);
+            let barrier = ebr::Barrier::new();
+            assert!(tree
+                .iter(&barrier)
+                .map(|(k, _)| *k)
+                .eq((0..workload_size).filter(|k| k % 2 == 1)));
+
+            let packed: TreeIndex<usize, usize> = TreeIndex::from_sorted_iter_with_config(
+                (0..workload_size).map(|k| (k, k)),
+                leaf_capacity,
+                fan_out,
+            );
+            let depth = packed.depth();
+            let mut num_children = workload_size.div_ceil(leaf_capacity);
+            let mut expected_depth = 1;
+            while num_children > fan_out {
+                num_children = num_children.div_ceil(fan_out);
+                expected_depth += 1;
+            }
+            assert_eq!(depth, expected_depth);
+            for k in workload_size..workload_size * 2 {
+                assert!(packed.insert(k, k).is_ok());
+            }
+            assert!(packed
+                .iter(&barrier)
+                .map(|(k, _)| *k)
+                .eq(0..workload_size * 2));
+        }
+
+        let default_tree: TreeIndex<usize, usize> = TreeIndex::default();
+        let (max_leaf_capacity, max_fan_out) =
+            (default_tree.leaf_capacity(), default_tree.fan_out());
+        for (leaf_capacity, fan_out) in [
+            (1, 3),
+            (max_leaf_capacity + 1, 3),
+            (2, 2),
+            (2, max_fan_out + 1),
+        ] {
+            assert!(std::panic::catch_unwind(|| {
+                TreeIndex::<usize, usize>::with_config(leaf_capacity, fan_out)
+            })
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn split_off_append() {
+        let workload_size = 1024;
+        for (leaf_capacity, fan_out) in [(2, 3), (4, 5), (14, 15)] {
+            for split_key in [0, 1, 7, workload_size / 3, workload_size - 1, workload_size] {
+                let mut tree: TreeIndex<usize, usize> =
+                    TreeIndex::with_config(leaf_capacity, fan_out);
+                for k in 0..workload_size {
+                    assert!(tree.insert(k, k).is_ok());
+                }
+                let mut high_key_tree = tree.split_off(&split_key);
+                let barrier = ebr::Barrier::new();
+                assert!(tree.iter(&barrier).map(|(k, _)| *k).eq(0..split_key));
+                assert!(tree
+                    .iter(&barrier)
+                    .rev()
+                    .map(|(k, _)| *k)
+                    .eq((0..split_key).rev()));
+                assert!(high_key_tree
+                    .iter(&barrier)
+                    .map(|(k, _)| *k)
+                    .eq(split_key..workload_size));
+                assert!(high_key_tree
+                    .range(split_key / 2..split_key + 8, &barrier)
+                    .map(|(k, _)| *k)
+                    .eq(split_key..(split_key + 8).min(workload_size)));
+                for k in 0..workload_size {
+                    assert_eq!(tree.read(&k, |_, v| *v).is_some(), k < split_key);
+                    assert_eq!(high_key_tree.read(&k, |_, v| *v).is_some(), k >= split_key);
+                }
+
+                // Both trees remain fully functional after the split.
+                for k in (0..split_key).filter(|k| k % 3 == 0) {
+                    assert!(tree.remove(&k));
+                    assert!(tree.insert(k, k).is_ok());
+                }
+                for k in (split_key..workload_size).filter(|k| k % 3 == 0) {
+                    assert!(high_key_tree.remove(&k));
+                    assert!(high_key_tree.insert(k, k).is_ok());
+                }
+
+                tree.append(&mut high_key_tree);
+                assert!(high_key_tree.is_empty());
+                assert!(tree.iter(&barrier).map(|(k, _)| *k).eq(0..workload_size));
+                assert!(tree
+                    .iter(&barrier)
+                    .rev()
+                    .map(|(k, _)| *k)
+                    .eq((0..workload_size).rev()));
+                for k in 0..workload_size {
+                    assert_eq!(tree.read(&k, |_, v| *v), Some(k));
+                }
+            }
+
+            // Trees of different heights are appended in both directions.
+            for small_size in [1, 3, 64] {
+                let mut large: TreeIndex<usize, usize> = TreeIndex::from_sorted_iter_with_config(
+                    (0..workload_size).map(|k| (k, k)),
+                    leaf_capacity,
+                    fan_out,
+                );
+                let mut small: TreeIndex<usize, usize> = TreeIndex::from_sorted_iter_with_config(
+                    (workload_size..workload_size + small_size).map(|k| (k, k)),
+                    leaf_capacity,
+                    fan_out,
+                );
+                large.append(&mut small);
+                let barrier = ebr::Barrier::new();
+                assert!(large
+                    .iter(&barrier)
+                    .map(|(k, _)| *k)
+                    .eq(0..workload_size + small_size));
+
+                let mut small: TreeIndex<usize, usize> = (0..small_size).map(|k| (k, k)).collect();
+                let mut large = large.split_off(&small_size);
+                small.append(&mut large);
+                assert!(large.is_empty());
+                assert!(small
+                    .iter(&barrier)
+                    .map(|(k, _)| *k)
+                    .eq(0..workload_size + small_size));
+            }
+        }
+    }
+
+    #[test]
+    fn append_overlapping() {
+        let workload_size = 1024;
+
+        // Overlapping keys are copied, and the values in the appended tree take precedence.
+        let mut tree: TreeIndex<usize, usize> = (0..64).map(|k| (k, k)).collect();
+        let mut other: TreeIndex<usize, usize> = (32..96).map(|k| (k, k + 1)).collect();
+        tree.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(tree.len(), 96);
+        assert_eq!(tree.read(&16, |_, v| *v), Some(16));
+        assert_eq!(tree.read(&48, |_, v| *v), Some(49));
+
+        // The appended tree is safe to be shared among threads.
+        let tree = Arc::new(tree);
+        let num_threads = 4;
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for thread_id in 0..num_threads {
+            let tree_cloned = tree.clone();
+            thread_handles.push(thread::spawn(move || {
+                for k in (0..96).filter(|k| k % num_threads == thread_id) {
+                    assert!(tree_cloned.remove(&k));
+                }
+                for k in (96..workload_size).filter(|k| k % num_threads == thread_id) {
+                    assert!(tree_cloned.insert(k, k).is_ok());
+                }
+            }));
+        }
+        for handle in thread_handles {
+            handle.join().unwrap();
+        }
+        let barrier = ebr::Barrier::new();
+        assert!(tree.iter(&barrier).map(|(k, _)| *k).eq(96..workload_size));
+    }
+
+    #[test]
+    fn shared_tree_index() {
+        static CNT: AtomicUsize = AtomicUsize::new(0);
+        #[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+        struct Key(usize);
+        struct NonClone(usize, &'static AtomicUsize);
+        impl NonClone {
+            fn new(value: usize, cnt: &'static AtomicUsize) -> NonClone {
+                cnt.fetch_add(1, Relaxed);
+                NonClone(value, cnt)
+            }
+        }
+        impl Drop for NonClone {
+            fn drop(&mut self) {
+                self.1.fetch_sub(1, Relaxed);
+            }
+        }
+
+        let workload_size = 4096;
+        let tree: Arc<SharedTreeIndex<Key, NonClone>> =
+            Arc::new(SharedTreeIndex::with_config(4, 5));
+        let num_threads = 4;
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for thread_id in 0..num_threads {
+            let tree_cloned = tree.clone();
+            thread_handles.push(thread::spawn(move || {
+                for k in 0..workload_size {
+                    if let Err((key, value)) = tree_cloned.insert(Key(k), NonClone::new(k, &CNT)) {
+                        assert_eq!(key.0, value.0);
+                    }
+                }
+                for k in (0..workload_size).filter(|k| k % num_threads == thread_id) {
+                    assert!(tree_cloned.read(&Key(k), |_, v| v.0 == k).unwrap());
+                    if k % 2 == 0 {
+                        assert!(tree_cloned.remove(&Key(k)));
+                    } else {
+                        assert!(tree_cloned.update(&Key(k), |v| NonClone::new(v.0 * 2, &CNT)));
+                    }
+                }
+            }));
+        }
+        for handle in thread_handles {
+            handle.join().unwrap();
+        }
+
+        let barrier = ebr::Barrier::new();
+        assert_eq!(tree.len(), workload_size / 2);
+        assert!(tree
+            .iter(&barrier)
+            .all(|(k, v)| k.0 % 2 == 1 && v.0 == k.0 * 2));
+        assert!(tree
+            .range(..Key(16), &barrier)
+            .rev()
+            .map(|(k, _)| k.0)
+            .eq((0..16).filter(|k| k % 2 == 1).rev()));
+        let (key, value) = tree.pop_first().unwrap();
+        assert_eq!((key.0, value.0), (1, 2));
+        drop(value);
+        drop(barrier);
+
+        // Every value has been moved into the tree exactly once without being cloned.
+        drop(tree);
+        while CNT.load(Relaxed) != 0 {
+            drop(ebr::Barrier::new());
+            thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn tree_set() {
+        let workload_size = 1024;
+        let evens: TreeSet<usize> = (0..workload_size).filter(|k| k % 2 == 0).collect();
+        let triples: Arc<TreeSet<usize>> = Arc::new(TreeSet::with_config(4, 5));
+        for k in (0..workload_size).filter(|k| k % 3 == 0) {
+            assert!(triples.insert(k).is_ok());
+        }
+
+        // Keys that are congruent to 1 modulo 6 are in neither of the stable parts of the sets.
+        let done = Arc::new(AtomicBool::new(false));
+        let triples_cloned = triples.clone();
+        let done_cloned = done.clone();
+        let churn = thread::spawn(move || {
+            while !done_cloned.load(Acquire) {
+                for k in (0..workload_size).filter(|k| k % 6 == 1) {
+                    assert!(triples_cloned.insert(k).is_ok());
+                }
+                for k in (0..workload_size).filter(|k| k % 6 == 1) {
+                    assert!(triples_cloned.remove(&k));
+                }
+            }
+        });
+
+        for _ in 0..16 {
+            let barrier = ebr::Barrier::new();
+            assert!(evens
+                .intersection(&triples, &barrier)
+                .copied()
+                .eq((0..workload_size).filter(|k| k % 6 == 0)));
+            assert!(evens
+                .difference(&triples, &barrier)
+                .copied()
+                .eq((0..workload_size).filter(|k| k % 2 == 0 && k % 3 != 0)));
+            let mut expected = (0..workload_size).filter(|k| k % 2 == 0 || k % 3 == 0);
+            let mut prev = None;
+            for k in evens.union(&triples, &barrier) {
+                assert!(prev < Some(*k));
+                prev.replace(*k);
+                if k % 6 != 1 {
+                    assert_eq!(expected.next(), Some(*k));
+                }
+            }
+            assert!(expected.next().is_none());
+        }
+        done.store(true, Release);
+        churn.join().unwrap();
+
+        let barrier = ebr::Barrier::new();
+        assert_eq!(triples.first(&barrier), Some(&0));
+        assert_eq!(triples.last(&barrier), Some(&1023));
+        assert!(triples.range(..10, &barrier).copied().eq([0, 3, 6, 9]));
+        assert!(triples.contains(&999));
+        assert!(triples.remove(&999));
+        assert_eq!(
+            triples.len(),
+            (0..workload_size).filter(|k| k % 3 == 0).count() - 1
+        );
+
+        // Exhausted set operations do not start over.
+        let left: TreeSet<usize> = TreeSet::default();
+        let right: TreeSet<usize> = TreeSet::default();
+        for k in 1..=3 {
+            assert!(left.insert(k).is_ok());
+        }
+        for k in [2, 4] {
+            assert!(right.insert(k).is_ok());
+        }
+        let mut union = left.union(&right, &barrier);
+        assert!(union.by_ref().copied().eq([1, 2, 3, 4]));
+        assert!(union.next().is_none());
+        assert!(union.next().is_none());
+        let mut intersection = left.intersection(&right, &barrier);
+        assert!(intersection.by_ref().copied().eq([2]));
+        assert!(intersection.next().is_none());
+        let mut difference = left.difference(&right, &barrier);
+        assert!(difference.by_ref().copied().eq([1, 3]));
+        assert!(difference.next().is_none());
+    }
+}
+
+#[cfg(test)]
+mod treemap_test {
+    use crate::ebr;
+    use crate::tree_map::Entry;
+    use crate::TreeMap;
+
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    use tokio::sync::Barrier as AsyncBarrier;
+
+    struct R(&'static AtomicUsize);
+    impl R {
+        fn new(cnt: &'static AtomicUsize) -> R {
+            cnt.fetch_add(1, Relaxed);
+            R(cnt)
+        }
+    }
+    impl Drop for R {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Relaxed);
+        }
+    }
+
+    #[tokio::test]
+    async fn insert_drop() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+        let treemap: TreeMap<usize, R> = TreeMap::default();
+
+        let workload_size = 1024;
+        for k in 0..workload_size {
+            assert!(treemap.insert_async(k, R::new(&INST_CNT)).await.is_ok());
+        }
+        assert_eq!(INST_CNT.load(Relaxed), workload_size);
+        assert_eq!(treemap.len(), workload_size);
+        for k in 0..workload_size / 2 {
+            assert!(treemap.remove_async(&k).await.is_some());
+        }
+        assert_eq!(INST_CNT.load(Relaxed), workload_size / 2);
+        drop(treemap);
+
+        while INST_CNT.load(Relaxed) != 0 {
+            drop(ebr::Barrier::new());
+            thread::yield_now();
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn entry() {
+        let treemap: Arc<TreeMap<usize, usize>> = Arc::new(TreeMap::default());
+
+        let num_tasks = 8;
+        let workload_size = 256;
+        let mut task_handles = Vec::with_capacity(num_tasks);
+        let barrier = Arc::new(AsyncBarrier::new(num_tasks));
+        for task_id in 0..num_tasks {
+            let barrier_cloned = barrier.clone();
+            let treemap_cloned = treemap.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                barrier_cloned.wait().await;
+                for id in 0..workload_size {
+                    let entry = treemap_cloned.entry_async(id).await;
+                    entry.and_modify(|v| *v += 1).or_insert(1);
+                }
+                let range = (task_id * workload_size)..((task_id + 1) * workload_size);
+                for id in range.clone() {
+                    match treemap_cloned.entry(id + num_tasks * workload_size) {
+                        Entry::Occupied(_) => unreachable!(),
+                        Entry::Vacant(v) => {
+                            let mut o = v.insert_entry(id);
+                            assert_eq!(*o.get(), id);
+                            *o.get_mut() += 1;
+                            assert_eq!(o.insert(id + 2), id + 1);
+                        }
+                    }
+                }
+                for id in range {
+                    let key = id + num_tasks * workload_size;
+                    if let Entry::Occupied(o) = treemap_cloned.entry_async(key).await {
+                        assert_eq!(o.remove_entry(), (key, id + 2));
+                    } else {
+                        unreachable!();
+                    }
+                    assert!(!treemap_cloned.contains(&key));
+                    if let Entry::Vacant(v) = treemap_cloned.entry_async(key).await {
+                        assert_eq!(v.into_key(), key);
+                    } else {
+                        unreachable!();
+                    }
+                }
+            }));
+        }
+
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+
+        assert_eq!(treemap.len(), workload_size);
+        let mut expected = 0;
+        treemap.scan(|k, v| {
+            assert_eq!(*k, expected);
+            assert_eq!(*v, num_tasks);
+            expected += 1;
+        });
+        assert_eq!(expected, workload_size);
+    }
+
+    #[test]
+    fn update_remove() {
+        let treemap: Arc<TreeMap<usize, Vec<usize>>> = Arc::new(TreeMap::default());
+
+        let num_threads = 8;
+        let workload_size = 64;
+        let barrier = Arc::new(Barrier::new(num_threads));
+        let mut thread_handles = Vec::with_capacity(num_threads);
+        for thread_id in 0..num_threads {
+            let barrier_cloned = barrier.clone();
+            let treemap_cloned = treemap.clone();
+            thread_handles.push(thread::spawn(move || {
+                barrier_cloned.wait();
+                for _ in 0..4 {
+                    for k in 0..workload_size {
+                        treemap_cloned.upsert(k, || vec![thread_id], |_, v| v.push(thread_id));
+                    }
+                    treemap_cloned.for_each_range(..workload_size / 2, |_, v| v.push(thread_id));
+                    for k in 0..workload_size {
+                        treemap_cloned.remove_if(&k, |v| v.len() > num_threads * 4);
+                        let len = treemap_cloned.read(&k, |_, v| v.len()).unwrap_or(0);
+                        assert!(len <= num_threads * 8);
+                    }
+                }
+            }));
+        }
+        for handle in thread_handles {
+            assert!(handle.join().is_ok());
+        }
+
+        let mut len = 0;
+        treemap.scan(|_, _| len += 1);
+        assert_eq!(treemap.len(), len);
+        for k in 0..workload_size {
+            if let Some((_, v)) = treemap.remove(&k) {
+                assert!(!v.is_empty());
+            }
+            assert!(treemap.update(&k, |_, v| v.len()).is_none());
+        }
+        assert!(treemap.is_empty());
+    }
 }
 
 #[cfg(test)]
 mod queue_test {
-    use crate::Queue;
+    use crate::{BoundedQueue, Queue};
 
     use std::sync::atomic::AtomicUsize;
     use std::sync::atomic::Ordering::Relaxed;
     use std::sync::Arc;
+    use std::thread;
 
     use tokio::sync::Barrier as AsyncBarrier;
 
@@ -1056,4 +2531,583 @@ mod queue_test {
             assert!(queue.is_empty());
         }
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn pop_wait() {
+        const NUM_TASKS: usize = 4;
+        let workload_size = 1024;
+        let queue: Arc<Queue<R>> = Arc::new(Queue::default());
+
+        let mut thread_handles = Vec::with_capacity(NUM_TASKS);
+        for _ in 0..NUM_TASKS {
+            let queue_cloned = queue.clone();
+            thread_handles.push(thread::spawn(move || {
+                let mut popped_acc: [usize; NUM_TASKS] = Default::default();
+                for _ in 0..workload_size {
+                    let popped = queue_cloned.pop_wait();
+                    assert!(popped_acc[popped.0] < popped.1);
+                    popped_acc[popped.0] = popped.1;
+                }
+            }));
+        }
+        let mut task_handles = Vec::with_capacity(NUM_TASKS);
+        for _ in 0..NUM_TASKS {
+            let queue_cloned = queue.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                let mut popped_acc: [usize; NUM_TASKS] = Default::default();
+                for _ in 0..workload_size {
+                    let popped = queue_cloned.pop_async().await;
+                    assert!(popped_acc[popped.0] < popped.1);
+                    popped_acc[popped.0] = popped.1;
+                }
+            }));
+        }
+
+        for task_id in 0..NUM_TASKS {
+            let queue_cloned = queue.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                for seq in 1..=workload_size * 2 {
+                    queue_cloned.push(R::new(task_id, seq));
+                    if seq % 64 == 0 {
+                        tokio::task::yield_now().await;
+                    }
+                }
+            }));
+        }
+
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+        for handle in thread_handles {
+            handle.join().unwrap();
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn bounded() {
+        const NUM_TASKS: usize = 4;
+        let workload_size = 1024;
+        let capacity = 16;
+        let queue: Arc<BoundedQueue<R>> = Arc::new(BoundedQueue::new(capacity));
+        assert!(std::panic::catch_unwind(|| BoundedQueue::<R>::new(0)).is_err());
+        assert_eq!(queue.capacity(), capacity);
+
+        let mut removed = None;
+        for seq in 0..capacity {
+            let entry = queue.try_push(R::new(0, seq)).ok().unwrap();
+            if seq == 1 {
+                removed.replace(entry);
+            }
+        }
+        assert!(queue.try_push(R::new(0, capacity)).is_err());
+        assert!(queue.push_if(R::new(0, capacity), |_| true).is_err());
+
+        // A logically removed entry occupies its slot until it becomes the oldest one.
+        assert!(removed.unwrap().remove());
+        assert!(queue.try_push(R::new(0, capacity)).is_err());
+        assert_eq!(queue.pop().map(|e| e.1), Some(0));
+        assert!(queue.try_push(R::new(0, capacity)).is_ok());
+        assert!(queue.try_push(R::new(0, capacity + 1)).is_ok());
+        assert!(queue.try_push(R::new(0, capacity + 2)).is_err());
+        assert_eq!(queue.peek(|e| e.1), Some(2));
+        while queue.pop().is_some() {}
+
+        // The slot of a removed oldest entry is reclaimed by the next push.
+        let single: BoundedQueue<usize> = BoundedQueue::new(1);
+        assert!(single.try_push(1).unwrap().remove());
+        assert!(single.try_push(2).is_ok());
+        assert!(single.try_push(3).is_err());
+
+        let mut thread_handles = Vec::with_capacity(NUM_TASKS);
+        for task_id in 0..NUM_TASKS {
+            let queue_cloned = queue.clone();
+            thread_handles.push(thread::spawn(move || {
+                for seq in 1..=workload_size {
+                    assert_eq!(queue_cloned.push_wait(R::new(task_id, seq)).1, seq);
+                }
+            }));
+        }
+        let mut task_handles = Vec::with_capacity(NUM_TASKS * 2);
+        for task_id in NUM_TASKS..NUM_TASKS * 2 {
+            let queue_cloned = queue.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                for seq in 1..=workload_size {
+                    assert_eq!(queue_cloned.push_async(R::new(task_id, seq)).await.1, seq);
+                }
+            }));
+        }
+        for _ in 0..NUM_TASKS {
+            let queue_cloned = queue.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                let mut popped_acc: [usize; NUM_TASKS * 2] = Default::default();
+                for _ in 0..workload_size * 2 {
+                    let popped = queue_cloned.pop_async().await;
+                    assert!(popped_acc[popped.0] < popped.1);
+                    popped_acc[popped.0] = popped.1;
+                }
+            }));
+        }
+
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+        for handle in thread_handles {
+            handle.join().unwrap();
+        }
+        assert!(queue.is_empty());
+        assert!(queue.try_push(R::new(0, 0)).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod stack_test {
+    use crate::Stack;
+
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[test]
+    fn lifo() {
+        let stack: Stack<usize> = Stack::default();
+        let entries: Vec<_> = (0..16).map(|i| stack.push(i)).collect();
+        assert!(stack.push_if(16, |e| e.map(|x| **x) == Some(14)).is_err());
+
+        // Logically removed entries are skipped.
+        assert!(entries[15].remove());
+        assert!(entries[13].remove());
+        assert!(!entries[13].remove());
+        assert_eq!(stack.peek(|e| **e), Some(14));
+        assert!(stack.push_if(16, |e| e.map(|x| **x) == Some(14)).is_ok());
+        assert!(stack.pop_if(|e| **e == 14).is_err());
+        assert_eq!(stack.pop().map(|e| **e), Some(16));
+        assert_eq!(
+            stack.pop_if(|e| **e == 14).ok().flatten().map(|e| **e),
+            Some(14)
+        );
+        for i in (0..13).rev() {
+            assert_eq!(stack.pop().map(|e| **e), Some(i));
+            assert!(entries[i].is_removed());
+        }
+        assert!(stack.pop().is_none());
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn mpmc() {
+        const NUM_THREADS: usize = 12;
+        const NUM_ENTRIES: usize = 4096;
+
+        let stack: Arc<Stack<usize>> = Arc::new(Stack::default());
+        let barrier = Arc::new(Barrier::new(NUM_THREADS));
+        let sum = Arc::new(AtomicUsize::new(0));
+        let mut threads = Vec::with_capacity(NUM_THREADS);
+        for thread_id in 0..NUM_THREADS {
+            let stack = stack.clone();
+            let barrier = barrier.clone();
+            let sum = sum.clone();
+            threads.push(thread::spawn(move || {
+                barrier.wait();
+                for seq in 0..NUM_ENTRIES {
+                    let val = thread_id * NUM_ENTRIES + seq;
+                    let entry = stack.push(val);
+                    if seq % 3 == 0 && entry.remove() {
+                        // The entry is never popped.
+                        sum.fetch_add(val, Relaxed);
+                    }
+                    if seq % 2 == 0 {
+                        if let Some(entry) = stack.pop() {
+                            sum.fetch_add(**entry, Relaxed);
+                        }
+                    }
+                }
+                barrier.wait();
+                while let Some(entry) = stack.pop() {
+                    sum.fetch_add(**entry, Relaxed);
+                }
+            }));
+        }
+        for thread in threads {
+            assert!(thread.join().is_ok());
+        }
+        assert!(stack.pop().is_none());
+        assert!(stack.is_empty());
+        let num_entries = NUM_THREADS * NUM_ENTRIES;
+        assert_eq!(sum.load(Relaxed), num_entries * (num_entries - 1) / 2);
+    }
+}
+
+#[cfg(test)]
+mod deque_test {
+    use crate::ebr;
+    use crate::Deque;
+
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    struct R(&'static AtomicUsize, usize);
+    impl R {
+        fn new(cnt: &'static AtomicUsize, val: usize) -> R {
+            cnt.fetch_add(1, Relaxed);
+            R(cnt, val)
+        }
+    }
+    impl Drop for R {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Relaxed);
+        }
+    }
+
+    #[test]
+    fn double_ended() {
+        let deque: Deque<usize> = Deque::default();
+        let entries: Vec<_> = (0..8)
+            .map(|i| {
+                if i % 2 == 0 {
+                    deque.push_back(i)
+                } else {
+                    deque.push_front(i)
+                }
+            })
+            .collect();
+
+        // 7, 5, 3, 1, 0, 2, 4, 6
+        assert_eq!(deque.peek_front(|e| **e), Some(7));
+        assert_eq!(deque.peek_back(|e| **e), Some(6));
+        assert!(entries[7].remove());
+        assert!(entries[6].remove());
+        assert!(entries[3].remove());
+        assert_eq!(deque.peek_front(|e| **e), Some(5));
+        assert_eq!(deque.peek_back(|e| **e), Some(4));
+        assert!(deque.pop_front_if(|e| **e == 4).is_err());
+        assert_eq!(deque.pop_front().map(|e| **e), Some(5));
+        assert_eq!(deque.pop_front().map(|e| **e), Some(1));
+        assert_eq!(
+            deque.pop_back_if(|e| **e == 4).ok().flatten().map(|e| **e),
+            Some(4)
+        );
+        assert_eq!(deque.pop_back().map(|e| **e), Some(2));
+        assert_eq!(deque.pop_front().map(|e| **e), Some(0));
+        assert!(deque.pop_back().is_none());
+        assert!(deque.is_empty());
+        assert!(entries.iter().all(|e| e.is_removed()));
+    }
+
+    #[test]
+    fn drop() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+
+        let deque: Deque<R> = Deque::default();
+        for i in 0..64 {
+            deque.push_back(R::new(&INST_CNT, i));
+            deque.push_front(R::new(&INST_CNT, i));
+        }
+        for _ in 0..16 {
+            assert!(deque.pop_back().is_some());
+            assert!(deque.pop_front().is_some());
+        }
+        let entry = deque.push_back(R::new(&INST_CNT, 64));
+        assert!(entry.remove());
+        std::mem::drop(deque);
+        std::mem::drop(entry);
+
+        while INST_CNT.load(Relaxed) != 0 {
+            std::mem::drop(ebr::Barrier::new());
+            thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn work_stealing() {
+        static INST_CNT: AtomicUsize = AtomicUsize::new(0);
+
+        const NUM_OWNERS: usize = 4;
+        const NUM_THIEVES: usize = 8;
+        const NUM_ENTRIES: usize = 4096;
+
+        let deque: Arc<Deque<R>> = Arc::new(Deque::default());
+        let barrier = Arc::new(Barrier::new(NUM_OWNERS + NUM_THIEVES));
+        let num_owners = Arc::new(AtomicUsize::new(NUM_OWNERS));
+        let sum = Arc::new(AtomicUsize::new(0));
+        let mut threads = Vec::with_capacity(NUM_OWNERS + NUM_THIEVES);
+        for thread_id in 0..NUM_OWNERS {
+            let deque = deque.clone();
+            let barrier = barrier.clone();
+            let num_owners = num_owners.clone();
+            let sum = sum.clone();
+            threads.push(thread::spawn(move || {
+                barrier.wait();
+                for seq in 0..NUM_ENTRIES {
+                    let val = thread_id * NUM_ENTRIES + seq;
+                    let entry = if seq % 2 == 0 {
+                        deque.push_back(R::new(&INST_CNT, val))
+                    } else {
+                        deque.push_front(R::new(&INST_CNT, val))
+                    };
+                    if seq % 5 == 0 && entry.remove() {
+                        sum.fetch_add(val, Relaxed);
+                    }
+                    if seq % 3 == 0 {
+                        if let Some(entry) = deque.pop_back() {
+                            sum.fetch_add(entry.1, Relaxed);
+                        }
+                    }
+                }
+                num_owners.fetch_sub(1, Relaxed);
+            }));
+        }
+        for _ in 0..NUM_THIEVES {
+            let deque = deque.clone();
+            let barrier = barrier.clone();
+            let num_owners = num_owners.clone();
+            let sum = sum.clone();
+            threads.push(thread::spawn(move || {
+                barrier.wait();
+                loop {
+                    let done = num_owners.load(Relaxed) == 0;
+                    if let Some(entry) = deque.pop_front() {
+                        sum.fetch_add(entry.1, Relaxed);
+                    } else if done {
+                        break;
+                    }
+                }
+            }));
+        }
+        for thread in threads {
+            assert!(thread.join().is_ok());
+        }
+        assert!(deque.is_empty());
+        let num_entries = NUM_OWNERS * NUM_ENTRIES;
+        assert_eq!(sum.load(Relaxed), num_entries * (num_entries - 1) / 2);
+
+        std::mem::drop(deque);
+        while INST_CNT.load(Relaxed) != 0 {
+            std::mem::drop(ebr::Barrier::new());
+            thread::yield_now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod channel_test {
+    use crate::channel::{self, Disconnected, SendError};
+
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn close() {
+        let (sender, receiver) = channel::unbounded();
+        for i in 0..16 {
+            assert!(sender.send(i).is_ok());
+        }
+        let receiver_cloned = receiver.clone();
+        sender.close();
+        assert!(receiver.is_closed());
+        assert_eq!(sender.send(16), Err(SendError::Disconnected(16)));
+        assert_eq!(sender.try_send(16).map_err(SendError::into_inner), Err(16));
+
+        // Remaining entries are drained before `Disconnected` is returned.
+        for i in 0..16 {
+            let receiver = if i % 2 == 0 {
+                &receiver
+            } else {
+                &receiver_cloned
+            };
+            assert_eq!(receiver.recv(), Ok(i));
+        }
+        assert_eq!(receiver.recv(), Err(Disconnected));
+        assert_eq!(
+            receiver_cloned.try_recv().map(|e| e.is_some()),
+            Err(Disconnected)
+        );
+
+        let (sender, receiver) = channel::bounded::<usize>(1);
+        let sender_cloned = sender.clone();
+        drop(sender);
+        assert!(!receiver.is_closed());
+        drop(sender_cloned);
+        assert!(receiver.is_closed());
+
+        let (sender, receiver) = channel::bounded::<usize>(1);
+        assert!(sender.try_send(0).is_ok());
+        let thread = thread::spawn(move || sender.send(1));
+        drop(receiver);
+        assert_eq!(thread.join().unwrap(), Err(SendError::Disconnected(1)));
+
+        // Received instances are owned by the `Receiver`.
+        let (sender, receiver) = channel::unbounded();
+        assert!(sender.send(String::from("scc")).is_ok());
+        let message: String = receiver.recv().unwrap();
+        assert_eq!(message, "scc");
+    }
+
+    #[test]
+    fn mpmc_sync() {
+        const NUM_THREADS: usize = 8;
+        const NUM_ENTRIES: usize = 1024;
+
+        let (sender, receiver) = channel::bounded(4);
+        let sum = Arc::new(AtomicUsize::new(0));
+        let mut threads = Vec::with_capacity(NUM_THREADS * 2);
+        for task_id in 0..NUM_THREADS {
+            let sender = sender.clone();
+            threads.push(thread::spawn(move || {
+                for seq in 0..NUM_ENTRIES {
+                    assert!(sender.send(task_id * NUM_ENTRIES + seq).is_ok());
+                }
+            }));
+            let receiver = receiver.clone();
+            let sum = sum.clone();
+            threads.push(thread::spawn(move || {
+                while let Ok(entry) = receiver.recv() {
+                    sum.fetch_add(entry, Relaxed);
+                }
+            }));
+        }
+        drop(sender);
+        drop(receiver);
+        for thread in threads {
+            assert!(thread.join().is_ok());
+        }
+        let num_entries = NUM_THREADS * NUM_ENTRIES;
+        assert_eq!(sum.load(Relaxed), num_entries * (num_entries - 1) / 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 16)]
+    async fn mpmc_async() {
+        const NUM_TASKS: usize = 8;
+        const NUM_ENTRIES: usize = 1024;
+
+        let (sender, receiver) = channel::bounded(4);
+        let sum = Arc::new(AtomicUsize::new(0));
+        let mut task_handles = Vec::with_capacity(NUM_TASKS * 2);
+        for task_id in 0..NUM_TASKS {
+            let sender = sender.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                for seq in 0..NUM_ENTRIES {
+                    assert!(sender.send_async(task_id * NUM_ENTRIES + seq).await.is_ok());
+                }
+            }));
+            let receiver = receiver.clone();
+            let sum = sum.clone();
+            task_handles.push(tokio::task::spawn(async move {
+                while let Ok(entry) = receiver.recv_async().await {
+                    sum.fetch_add(entry, Relaxed);
+                }
+            }));
+        }
+        drop(sender);
+        drop(receiver);
+        for r in futures::future::join_all(task_handles).await {
+            assert!(r.is_ok());
+        }
+        let num_entries = NUM_TASKS * NUM_ENTRIES;
+        assert_eq!(sum.load(Relaxed), num_entries * (num_entries - 1) / 2);
+    }
+
+    #[cfg(feature = "futures-core")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn stream() {
+        use futures::StreamExt;
+        use std::task::{Context, Poll};
+
+        const NUM_ENTRIES: usize = 1024;
+
+        let (sender, receiver) = channel::bounded(4);
+        let task_handle = tokio::task::spawn(async move {
+            for seq in 0..NUM_ENTRIES {
+                assert!(sender.send_async(seq).await.is_ok());
+            }
+        });
+        let entries: Vec<usize> = receiver.collect().await;
+        assert!(task_handle.await.is_ok());
+        assert_eq!(entries, (0..NUM_ENTRIES).collect::<Vec<_>>());
+
+        // A `Receiver` with a pending wait can be dropped.
+        let (sender, mut receiver) = channel::unbounded::<usize>();
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert!(receiver.poll_recv(&mut cx).is_pending());
+        assert!(receiver.poll_recv(&mut cx).is_pending());
+        drop(receiver);
+        assert!(sender.is_closed());
+
+        let (sender, mut receiver) = channel::unbounded::<usize>();
+        assert!(receiver.poll_recv(&mut cx).is_pending());
+        assert!(sender.send(1).is_ok());
+        assert_eq!(receiver.poll_recv(&mut cx), Poll::Ready(Ok(1)));
+    }
+
+    #[cfg(feature = "futures-sink")]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn sink() {
+        use futures::SinkExt;
+
+        const NUM_ENTRIES: usize = 1024;
+
+        let (mut sender, receiver) = channel::bounded(4);
+        let task_handle = tokio::task::spawn(async move {
+            for seq in 0..NUM_ENTRIES {
+                assert!(SinkExt::send(&mut sender, seq).await.is_ok());
+            }
+            assert!(SinkExt::close(&mut sender).await.is_ok());
+        });
+        for seq in 0..NUM_ENTRIES {
+            assert_eq!(receiver.recv_async().await, Ok(seq));
+        }
+        assert_eq!(receiver.recv_async().await, Err(Disconnected));
+        assert!(task_handle.await.is_ok());
+
+        // `start_send` must be preceded by `poll_ready`.
+        let (mut sender, _receiver) = channel::unbounded::<usize>();
+        assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            futures::Sink::start_send(std::pin::Pin::new(&mut sender), 0)
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn poll_ready() {
+        use std::task::{Context, Poll};
+
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        // The reserved room cannot be taken by another `Sender`.
+        let (mut sender, receiver) = channel::bounded(1);
+        let sender_cloned = sender.clone();
+        assert_eq!(sender.poll_ready(&mut cx), Poll::Ready(Ok(())));
+        assert_eq!(sender.poll_ready(&mut cx), Poll::Ready(Ok(())));
+        assert_eq!(sender_cloned.try_send(0), Err(SendError::Full(0)));
+        assert!(sender.start_send(1).is_ok());
+        assert!(sender.poll_ready(&mut cx).is_pending());
+        assert_eq!(receiver.try_recv(), Ok(Some(1)));
+        assert_eq!(sender.poll_ready(&mut cx), Poll::Ready(Ok(())));
+
+        // Dropping a `Sender` gives back the reserved room.
+        drop(sender);
+        assert!(sender_cloned.try_send(2).is_ok());
+
+        // A `Sender` with a pending wait can be dropped.
+        let mut sender = sender_cloned.clone();
+        assert!(sender.poll_ready(&mut cx).is_pending());
+        drop(sender);
+        assert_eq!(receiver.try_recv(), Ok(Some(2)));
+        assert!(sender_cloned.try_send(3).is_ok());
+
+        // The room is given back when the channel is closed.
+        let mut sender = sender_cloned.clone();
+        assert!(receiver.try_recv().is_ok());
+        assert_eq!(sender.poll_ready(&mut cx), Poll::Ready(Ok(())));
+        receiver.close();
+        assert_eq!(sender.start_send(4), Err(SendError::Disconnected(4)));
+        assert_eq!(sender.poll_ready(&mut cx), Poll::Ready(Err(Disconnected)));
+    }
 }
@@ -4,20 +4,25 @@ mod internal_node;
 mod leaf;
 mod leaf_node;
 mod node;
+mod shared;
 
 use crate::ebr::{Arc, AtomicArc, Barrier, Ptr, Tag};
 use crate::wait_queue::AsyncWait;
 
-use leaf::{InsertResult, Leaf, RemoveResult, Scanner};
-use node::Node;
+use leaf::{InsertResult, Leaf, OnDuplicate, RemoveResult, Scanner};
+use node::{Config, Node};
+
+pub use shared::{SharedRange, SharedTreeIndex, SharedVisitor};
 
 use std::borrow::Borrow;
 use std::cmp::Ordering;
-use std::iter::FusedIterator;
-use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::iter::{FusedIterator, Rev};
+use std::marker::PhantomData;
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
 use std::ops::RangeBounds;
 use std::pin::Pin;
-use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
+use std::ptr;
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
 
 /// Scalable concurrent B+ tree.
 ///
@@ -30,7 +35,7 @@ use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
 ///
 /// [`TreeIndex`] methods are linearizable, however its iterator methods are not; [`Visitor`] and
 /// [`Range`] are only guaranteed to observe events happened before the first call to
-/// [`Iterator::next`].
+/// [`Iterator::next`] or [`DoubleEndedIterator::next_back`].
 ///
 /// ## The key features of [`TreeIndex`]
 ///
@@ -41,8 +46,10 @@ use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
 ///
 /// ## The key statistics for [`TreeIndex`]
 ///
-/// * The maximum number of key-value pairs that a leaf can store: 14.
-/// * The maximum number of leaves or child nodes that a node can point to: 15.
+/// * The maximum number of key-value pairs that a leaf can store: 14 by default.
+/// * The maximum number of leaves or child nodes that a node can point to: 15 by default.
+/// * Both can be lowered, but not raised, through [`TreeIndex::with_config`]; leaves and nodes
+///   keep their default size in memory.
 /// * The size of metadata per key-value pair in a leaf: ~3-byte.
 pub struct TreeIndex<K, V>
 where
@@ -50,6 +57,7 @@ where
     V: 'static + Clone + Send + Sync,
 {
     root: AtomicArc<Node<K, V>>,
+    config: Config,
 }
 
 impl<K, V> TreeIndex<K, V>
@@ -70,7 +78,144 @@ where
     pub fn new() -> TreeIndex<K, V> {
         TreeIndex {
             root: AtomicArc::null(),
+            config: Config::default(),
+        }
+    }
+
+    /// Creates an empty [`TreeIndex`] with the given leaf capacity and node fan-out.
+    ///
+    /// `leaf_capacity` is the maximum number of key-value pairs that a leaf can store, and
+    /// `fan_out` is the maximum number of leaves or child nodes that a node can point to. Smaller
+    /// leaves and nodes make structural changes cheaper for write-heavy workloads. The metadata
+    /// of a leaf has to fit in a `usize`, therefore the values cannot exceed `14` and `15`
+    /// respectively on 64-bit targets, which are the default values of [`TreeIndex::new`].
+    ///
+    /// The values can only be lowered: leaves and nodes keep the memory layout of the default
+    /// configuration, and a lower capacity only makes them split earlier without reducing their
+    /// size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_capacity` is not in `[2, 14]` or `fan_out` is not in `[3, 15]` on 64-bit
+    /// targets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::with_config(4, 8);
+    ///
+    /// assert_eq!(treeindex.leaf_capacity(), 4);
+    /// assert_eq!(treeindex.fan_out(), 8);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_config(leaf_capacity: usize, fan_out: usize) -> TreeIndex<K, V> {
+        TreeIndex {
+            root: AtomicArc::null(),
+            config: Config::new(leaf_capacity, fan_out),
+        }
+    }
+
+    /// Returns the maximum number of key-value pairs that a leaf can store.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::with_config(4, 8);
+    ///
+    /// assert_eq!(treeindex.leaf_capacity(), 4);
+    /// ```
+    #[inline]
+    pub fn leaf_capacity(&self) -> usize {
+        self.config.leaf_capacity
+    }
+
+    /// Returns the maximum number of leaves or child nodes that a node can point to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::with_config(4, 3);
+    ///
+    /// assert_eq!(treeindex.fan_out(), 3);
+    /// ```
+    #[inline]
+    pub fn fan_out(&self) -> usize {
+        self.config.fan_out
+    }
+
+    /// Creates a [`TreeIndex`] from key-value pairs sorted in ascending key order.
+    ///
+    /// Fully packed leaves and nodes are built bottom-up without any structural changes. Key-value
+    /// pairs that are not in strictly ascending key order are inserted one by one afterwards, and
+    /// therefore a key-value pair is ignored if its key was already inserted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::from_sorted_iter((0..1024).map(|k| (k, 10)));
+    ///
+    /// assert_eq!(treeindex.len(), 1024);
+    /// assert_eq!(treeindex.read(&1023, |_, v| *v).unwrap(), 10);
+    /// ```
+    #[inline]
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> TreeIndex<K, V> {
+        Self::from_sorted_iter_with(iter, Config::default())
+    }
+
+    /// Creates a [`TreeIndex`] from key-value pairs sorted in ascending key order with the given
+    /// leaf capacity and node fan-out.
+    ///
+    /// See [`TreeIndex::with_config`] for the parameters, and [`TreeIndex::from_sorted_iter`] for
+    /// how the key-value pairs are loaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_capacity` or `fan_out` is out of range; see [`TreeIndex::with_config`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> =
+    ///     TreeIndex::from_sorted_iter_with_config((0..1024).map(|k| (k, 10)), 4, 4);
+    ///
+    /// assert_eq!(treeindex.len(), 1024);
+    /// assert_eq!(treeindex.leaf_capacity(), 4);
+    /// ```
+    #[inline]
+    pub fn from_sorted_iter_with_config<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+        leaf_capacity: usize,
+        fan_out: usize,
+    ) -> TreeIndex<K, V> {
+        Self::from_sorted_iter_with(iter, Config::new(leaf_capacity, fan_out))
+    }
+
+    /// Builds a [`TreeIndex`] bottom-up with the given [`Config`].
+    fn from_sorted_iter_with<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+        config: Config,
+    ) -> TreeIndex<K, V> {
+        let mut unsorted = Vec::new();
+        let treeindex = TreeIndex {
+            root: Node::from_sorted_iter(iter.into_iter(), config, &mut unsorted)
+                .map_or_else(AtomicArc::null, AtomicArc::from),
+            config,
+        };
+        for (key, value) in unsorted {
+            let _result = treeindex.insert(key, value);
         }
+        treeindex
     }
 
     /// Inserts a key-value pair.
@@ -91,47 +236,8 @@ where
     /// assert_eq!(treeindex.read(&1, |k, v| *v).unwrap(), 10);
     /// ```
     #[inline]
-    pub fn insert(&self, mut key: K, mut value: V) -> Result<(), (K, V)> {
-        loop {
-            let barrier = Barrier::new();
-            if let Some(root_ref) = self.root.load(Acquire, &barrier).as_ref() {
-                match root_ref.insert(key, value, None, &barrier) {
-                    Ok(r) => match r {
-                        InsertResult::Success => return Ok(()),
-                        InsertResult::Frozen(k, v) | InsertResult::Retry(k, v) => {
-                            key = k;
-                            value = v;
-                            root_ref.cleanup_link(key.borrow(), false, &barrier);
-                        }
-                        InsertResult::Duplicate(k, v) => return Err((k, v)),
-                        InsertResult::Full(k, v) => {
-                            let (k, v) = Node::split_root::<false>(k, v, &self.root, &barrier);
-                            key = k;
-                            value = v;
-                            continue;
-                        }
-                        InsertResult::Retired(k, v) => {
-                            key = k;
-                            value = v;
-                            let _result = Node::remove_root(&self.root, None, &barrier);
-                        }
-                    },
-                    Err((k, v)) => {
-                        key = k;
-                        value = v;
-                    }
-                }
-            }
-
-            let new_root = Arc::new(Node::new_leaf_node());
-            let _result = self.root.compare_exchange(
-                Ptr::null(),
-                (Some(new_root), Tag::None),
-                AcqRel,
-                Acquire,
-                &barrier,
-            );
-        }
+    pub fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        self.insert_with(key, value, OnDuplicate::Reject)
     }
 
     /// Inserts a key-value pair.
@@ -151,66 +257,121 @@ where
     /// let future_insert = treeindex.insert_async(1, 10);
     /// ```
     #[inline]
-    pub async fn insert_async(&self, mut key: K, mut value: V) -> Result<(), (K, V)> {
-        loop {
-            let mut async_wait = AsyncWait::default();
-            let mut async_wait_pinned = Pin::new(&mut async_wait);
+    pub async fn insert_async(&self, key: K, value: V) -> Result<(), (K, V)> {
+        self.insert_with_async(key, value, OnDuplicate::Reject)
+            .await
+    }
 
-            let need_await = {
-                let barrier = Barrier::new();
-                if let Some(root_ref) = self.root.load(Acquire, &barrier).as_ref() {
-                    match root_ref.insert(key, value, Some(async_wait_pinned.mut_ptr()), &barrier) {
-                        Ok(r) => match r {
-                            InsertResult::Success => return Ok(()),
-                            InsertResult::Frozen(k, v) | InsertResult::Retry(k, v) => {
-                                key = k;
-                                value = v;
-                                root_ref.cleanup_link(key.borrow(), false, &barrier);
-                                true
-                            }
-                            InsertResult::Duplicate(k, v) => return Err((k, v)),
-                            InsertResult::Full(k, v) => {
-                                let (k, v) = Node::split_root::<true>(k, v, &self.root, &barrier);
-                                key = k;
-                                value = v;
-                                continue;
-                            }
-                            InsertResult::Retired(k, v) => {
-                                key = k;
-                                value = v;
-                                !matches!(
-                                    Node::remove_root(
-                                        &self.root,
-                                        Some(async_wait_pinned.mut_ptr()),
-                                        &barrier
-                                    ),
-                                    Ok(true)
-                                )
-                            }
-                        },
-                        Err((k, v)) => {
-                            key = k;
-                            value = v;
-                            true
-                        }
-                    }
-                } else {
-                    false
-                }
-            };
+    /// Inserts a key-value pair, or replaces the existing value associated with the key.
+    ///
+    /// The old key-value pair is atomically replaced, therefore readers observe either the old
+    /// value or the new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// treeindex.upsert(1, 10);
+    /// assert_eq!(treeindex.read(&1, |_, v| *v).unwrap(), 10);
+    /// treeindex.upsert(1, 11);
+    /// assert_eq!(treeindex.read(&1, |_, v| *v).unwrap(), 11);
+    /// ```
+    #[inline]
+    pub fn upsert(&self, key: K, value: V) {
+        let result = self.insert_with(key, value, OnDuplicate::Replace);
+        debug_assert!(result.is_ok());
+    }
 
-            if need_await {
-                async_wait_pinned.await;
+    /// Inserts a key-value pair, or replaces the existing value associated with the key.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    /// let future_upsert = treeindex.upsert_async(1, 10);
+    /// ```
+    #[inline]
+    pub async fn upsert_async(&self, key: K, value: V) {
+        let result = self
+            .insert_with_async(key, value, OnDuplicate::Replace)
+            .await;
+        debug_assert!(result.is_ok());
+    }
+
+    /// Updates the value associated with the key if the key exists.
+    ///
+    /// A new value is built by the given closure, and it atomically replaces the old one; readers
+    /// observe either the old value or the new one. The closure may be invoked more than once if
+    /// the entry is concurrently modified.
+    ///
+    /// Returns `false` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// assert!(!treeindex.update(&1, |v| *v + 1));
+    /// assert!(treeindex.insert(1, 10).is_ok());
+    /// assert!(treeindex.update(&1, |v| *v + 1));
+    /// assert_eq!(treeindex.read(&1, |_, v| *v).unwrap(), 11);
+    /// ```
+    #[inline]
+    pub fn update<Q, F: FnMut(&V) -> V>(&self, key_ref: &Q, mut updater: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            if let Ok(result) =
+                self.update_entry::<false, _, _>(key_ref, &mut updater, None, &Barrier::new())
+            {
+                return result;
             }
+        }
+    }
 
-            let new_root = Arc::new(Node::new_leaf_node());
-            let _result = self.root.compare_exchange(
-                Ptr::null(),
-                (Some(new_root), Tag::None),
-                AcqRel,
-                Acquire,
+    /// Updates the value associated with the key if the key exists.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    /// let future_update = treeindex.update_async(&1, |v| *v + 1);
+    /// ```
+    #[inline]
+    pub async fn update_async<Q, F: FnMut(&V) -> V>(&self, key_ref: &Q, mut updater: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            let result = self.update_entry::<true, _, _>(
+                key_ref,
+                &mut updater,
+                Some(async_wait_pinned.mut_ptr()),
                 &Barrier::new(),
             );
+            match result {
+                Ok(result) => return result,
+                Err(true) => async_wait_pinned.await,
+                Err(false) => (),
+            }
         }
     }
 
@@ -379,9 +540,12 @@ where
         }
     }
 
-    /// Reads a key-value pair.
+    /// Removes all the key-value pairs in the given range, and returns the number of removed
+    /// entries.
     ///
-    /// It returns `None` if the key does not exist.
+    /// Leaves and nodes that become empty are unlinked at once instead of being coalesced one key
+    /// at a time. The removal is not atomic; key-value pairs inserted into the range by other
+    /// threads in the meantime may or may not be removed.
     ///
     /// # Examples
     ///
@@ -389,39 +553,244 @@ where
     /// use scc::TreeIndex;
     ///
     /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
-    /// assert!(treeindex.read(&1, |k, v| *v).is_none());
+    ///
+    /// for key in 0..16 {
+    ///     assert!(treeindex.insert(key, 10).is_ok());
+    /// }
+    /// assert_eq!(treeindex.remove_range(4..12), 8);
+    /// assert_eq!(treeindex.remove_range(..6), 4);
+    /// assert_eq!(treeindex.remove_range(..6), 0);
+    /// assert_eq!(treeindex.len(), 4);
     /// ```
     #[inline]
-    pub fn read<Q, R, F: FnOnce(&Q, &V) -> R>(&self, key_ref: &Q, reader: F) -> Option<R>
+    pub fn remove_range<Q, R>(&self, range: R) -> usize
     where
         K: Borrow<Q>,
         Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
     {
-        let barrier = Barrier::new();
-        self.read_with(key_ref, reader, &barrier)
+        let mut removed = 0;
+        loop {
+            let barrier = Barrier::new();
+            if let Some(root_ref) = self.root.load(Acquire, &barrier).as_ref() {
+                match root_ref.remove_range(&range, &mut removed, None, &barrier) {
+                    Ok(RemoveResult::Cleanup) => {
+                        if let Included(key) | Excluded(key) = range.start_bound() {
+                            root_ref.cleanup_link(key, false, &barrier);
+                        }
+                        return removed;
+                    }
+                    Ok(RemoveResult::Retired) => {
+                        if matches!(Node::remove_root(&self.root, None, &barrier), Ok(true)) {
+                            return removed;
+                        }
+                    }
+                    Ok(_) => return removed,
+                    Err(()) => (),
+                }
+            } else {
+                return removed;
+            }
+        }
     }
 
-    /// Reads a key-value pair using the supplied [`Barrier`].
+    /// Removes all the key-value pairs in the given range, and returns the number of removed
+    /// entries.
     ///
-    /// It enables the caller to use the value reference outside the method. It returns `None`
-    /// if the key does not exist.
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
     ///
     /// # Examples
     ///
     /// ```
     /// use scc::TreeIndex;
-    /// use scc::ebr::Barrier;
     ///
     /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
-    ///
-    /// let barrier = Barrier::new();
-    /// assert!(treeindex.read_with(&1, |k, v| v, &barrier).is_none());
+    /// let future_remove_range = treeindex.remove_range_async(4..12);
     /// ```
     #[inline]
-    pub fn read_with<'b, Q, R, F: FnOnce(&Q, &'b V) -> R>(
-        &self,
-        key_ref: &Q,
-        reader: F,
+    pub async fn remove_range_async<Q, R>(&self, range: R) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut removed = 0;
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            {
+                let barrier = Barrier::new();
+                if let Some(root_ref) = self.root.load(Acquire, &barrier).as_ref() {
+                    match root_ref.remove_range(
+                        &range,
+                        &mut removed,
+                        Some(async_wait_pinned.mut_ptr()),
+                        &barrier,
+                    ) {
+                        Ok(RemoveResult::Cleanup) => {
+                            if let Included(key) | Excluded(key) = range.start_bound() {
+                                root_ref.cleanup_link(key, false, &barrier);
+                            }
+                            return removed;
+                        }
+                        Ok(RemoveResult::Retired) => {
+                            if matches!(
+                                Node::remove_root(
+                                    &self.root,
+                                    Some(async_wait_pinned.mut_ptr()),
+                                    &barrier
+                                ),
+                                Ok(true)
+                            ) {
+                                return removed;
+                            }
+                        }
+                        Ok(_) => return removed,
+                        Err(()) => (),
+                    }
+                } else {
+                    return removed;
+                }
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Removes the minimum key-value pair, and returns it.
+    ///
+    /// Only a single caller is able to remove a specific key-value pair even if multiple callers
+    /// are concurrently trying to remove it. It returns `None` if the [`TreeIndex`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// assert!(treeindex.pop_first().is_none());
+    /// assert!(treeindex.insert(3, 10).is_ok());
+    /// assert!(treeindex.insert(1, 11).is_ok());
+    /// assert_eq!(treeindex.pop_first(), Some((1, 11)));
+    /// assert_eq!(treeindex.pop_first(), Some((3, 10)));
+    /// assert!(treeindex.pop_first().is_none());
+    /// ```
+    #[inline]
+    pub fn pop_first(&self) -> Option<(K, V)> {
+        let mut popped = None;
+        while self
+            .pop_entry::<false>(&mut popped, None, &Barrier::new())
+            .is_err()
+        {}
+        popped
+    }
+
+    /// Removes the minimum key-value pair, and returns it.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    /// let future_pop = treeindex.pop_first_async();
+    /// ```
+    #[inline]
+    pub async fn pop_first_async(&self) -> Option<(K, V)> {
+        self.pop_entry_async::<false>().await
+    }
+
+    /// Removes the maximum key-value pair, and returns it.
+    ///
+    /// Only a single caller is able to remove a specific key-value pair even if multiple callers
+    /// are concurrently trying to remove it. It returns `None` if the [`TreeIndex`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// assert!(treeindex.pop_last().is_none());
+    /// assert!(treeindex.insert(3, 10).is_ok());
+    /// assert!(treeindex.insert(1, 11).is_ok());
+    /// assert_eq!(treeindex.pop_last(), Some((3, 10)));
+    /// assert_eq!(treeindex.pop_last(), Some((1, 11)));
+    /// assert!(treeindex.pop_last().is_none());
+    /// ```
+    #[inline]
+    pub fn pop_last(&self) -> Option<(K, V)> {
+        let mut popped = None;
+        while self
+            .pop_entry::<true>(&mut popped, None, &Barrier::new())
+            .is_err()
+        {}
+        popped
+    }
+
+    /// Removes the maximum key-value pair, and returns it.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    /// let future_pop = treeindex.pop_last_async();
+    /// ```
+    #[inline]
+    pub async fn pop_last_async(&self) -> Option<(K, V)> {
+        self.pop_entry_async::<true>().await
+    }
+
+    /// Reads a key-value pair.
+    ///
+    /// It returns `None` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    /// assert!(treeindex.read(&1, |k, v| *v).is_none());
+    /// ```
+    #[inline]
+    pub fn read<Q, R, F: FnOnce(&Q, &V) -> R>(&self, key_ref: &Q, reader: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let barrier = Barrier::new();
+        self.read_with(key_ref, reader, &barrier)
+    }
+
+    /// Reads a key-value pair using the supplied [`Barrier`].
+    ///
+    /// It enables the caller to use the value reference outside the method. It returns `None`
+    /// if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// let barrier = Barrier::new();
+    /// assert!(treeindex.read_with(&1, |k, v| v, &barrier).is_none());
+    /// ```
+    #[inline]
+    pub fn read_with<'b, Q, R, F: FnOnce(&Q, &'b V) -> R>(
+        &self,
+        key_ref: &Q,
+        reader: F,
         barrier: &'b Barrier,
     ) -> Option<R>
     where
@@ -436,6 +805,211 @@ where
         None
     }
 
+    /// Returns the minimum key-value pair.
+    ///
+    /// The returned references can live as long as the supplied [`Barrier`]. It returns `None`
+    /// if the [`TreeIndex`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// let barrier = Barrier::new();
+    /// assert!(treeindex.first(&barrier).is_none());
+    ///
+    /// assert!(treeindex.insert(3, 10).is_ok());
+    /// assert!(treeindex.insert(1, 11).is_ok());
+    /// assert_eq!(treeindex.first(&barrier), Some((&1, &11)));
+    /// ```
+    #[inline]
+    pub fn first<'b>(&self, barrier: &'b Barrier) -> Option<(&'b K, &'b V)> {
+        self.iter(barrier).next()
+    }
+
+    /// Returns the maximum key-value pair.
+    ///
+    /// The returned references can live as long as the supplied [`Barrier`]. It returns `None`
+    /// if the [`TreeIndex`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// let barrier = Barrier::new();
+    /// assert!(treeindex.last(&barrier).is_none());
+    ///
+    /// assert!(treeindex.insert(3, 10).is_ok());
+    /// assert!(treeindex.insert(1, 11).is_ok());
+    /// assert_eq!(treeindex.last(&barrier), Some((&3, &10)));
+    /// ```
+    #[inline]
+    pub fn last<'b>(&self, barrier: &'b Barrier) -> Option<(&'b K, &'b V)> {
+        self.iter(barrier).next_back()
+    }
+
+    /// Returns the key-value pair with the maximum key among those keys smaller than or equal to
+    /// the given key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, 10).is_ok());
+    /// assert!(treeindex.insert(3, 11).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert!(treeindex.floor(&0, &barrier).is_none());
+    /// assert_eq!(treeindex.floor(&2, &barrier), Some((&1, &10)));
+    /// assert_eq!(treeindex.floor(&3, &barrier), Some((&3, &11)));
+    /// ```
+    #[inline]
+    pub fn floor<'b, Q>(&self, key_ref: &Q, barrier: &'b Barrier) -> Option<(&'b K, &'b V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.upper_bound(Included(key_ref), barrier)
+    }
+
+    /// Returns the key-value pair with the minimum key among those keys greater than or equal to
+    /// the given key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, 10).is_ok());
+    /// assert!(treeindex.insert(3, 11).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert_eq!(treeindex.ceiling(&1, &barrier), Some((&1, &10)));
+    /// assert_eq!(treeindex.ceiling(&2, &barrier), Some((&3, &11)));
+    /// assert!(treeindex.ceiling(&4, &barrier).is_none());
+    /// ```
+    #[inline]
+    pub fn ceiling<'b, Q>(&self, key_ref: &Q, barrier: &'b Barrier) -> Option<(&'b K, &'b V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.lower_bound(Included(key_ref), barrier)
+    }
+
+    /// Returns the key-value pair with the minimum key above the given bound.
+    ///
+    /// `Included(key)` returns the minimum key that is equal to or greater than `key`,
+    /// `Excluded(key)` returns the minimum key that is greater than `key`, and `Unbounded` returns
+    /// the minimum key in the [`TreeIndex`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Barrier;
+    /// use std::ops::Bound::{self, Excluded, Included, Unbounded};
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, 10).is_ok());
+    /// assert!(treeindex.insert(3, 11).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert_eq!(treeindex.lower_bound(Included(&1), &barrier), Some((&1, &10)));
+    /// assert_eq!(treeindex.lower_bound(Excluded(&1), &barrier), Some((&3, &11)));
+    /// assert_eq!(treeindex.lower_bound(Unbounded, &barrier), Some((&1, &10)));
+    /// assert!(treeindex.lower_bound(Excluded(&3), &barrier).is_none());
+    /// ```
+    #[inline]
+    pub fn lower_bound<'b, Q>(
+        &self,
+        bound: Bound<&Q>,
+        barrier: &'b Barrier,
+    ) -> Option<(&'b K, &'b V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let key_ref = match bound {
+            Excluded(key_ref) | Included(key_ref) => key_ref,
+            Unbounded => return self.first(barrier),
+        };
+        let root_ref = self.root.load(Acquire, barrier).as_ref()?;
+        let mut scanner = root_ref.min_greater_equal(key_ref, barrier)?;
+        let entry = scanner.get()?;
+        if matches!(bound, Included(_)) || entry.0.borrow() != key_ref {
+            return Some(entry);
+        }
+
+        // Proceeds to the next entry.
+        if let Some(entry) = scanner.next() {
+            return Some(entry);
+        }
+        scanner.jump(Some(key_ref), barrier)?.get()
+    }
+
+    /// Returns the key-value pair with the maximum key below the given bound.
+    ///
+    /// `Included(key)` returns the maximum key that is equal to or smaller than `key`,
+    /// `Excluded(key)` returns the maximum key that is smaller than `key`, and `Unbounded` returns
+    /// the maximum key in the [`TreeIndex`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Barrier;
+    /// use std::ops::Bound::{self, Excluded, Included, Unbounded};
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, 10).is_ok());
+    /// assert!(treeindex.insert(3, 11).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert_eq!(treeindex.upper_bound(Included(&3), &barrier), Some((&3, &11)));
+    /// assert_eq!(treeindex.upper_bound(Excluded(&3), &barrier), Some((&1, &10)));
+    /// assert_eq!(treeindex.upper_bound(Unbounded, &barrier), Some((&3, &11)));
+    /// assert!(treeindex.upper_bound(Excluded(&1), &barrier).is_none());
+    /// ```
+    #[inline]
+    pub fn upper_bound<'b, Q>(
+        &self,
+        bound: Bound<&Q>,
+        barrier: &'b Barrier,
+    ) -> Option<(&'b K, &'b V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let key_ref = match bound {
+            Excluded(key_ref) | Included(key_ref) => key_ref,
+            Unbounded => return self.last(barrier),
+        };
+        let root_ref = self.root.load(Acquire, barrier).as_ref()?;
+        if matches!(bound, Included(_)) {
+            if let Some(entry) = root_ref.search_entry(key_ref, barrier) {
+                return Some(entry);
+            }
+        }
+        root_ref.max_less(key_ref, barrier)?.get()
+    }
+
     /// Clears the [`TreeIndex`].
     ///
     /// # Examples
@@ -453,6 +1027,111 @@ where
         self.root.swap((None, Tag::None), Relaxed);
     }
 
+    /// Splits the [`TreeIndex`] into two at the given key.
+    ///
+    /// Returns a new [`TreeIndex`] containing all the key-value pairs having keys equal to or
+    /// greater than the given key, and those having smaller keys are kept in the [`TreeIndex`].
+    /// Only the nodes on the path to the key are rebuilt, and the other nodes are relinked to
+    /// either of the two [`TreeIndex`] instances without copying key-value pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let mut treeindex: TreeIndex<u64, u32> = (0..64).map(|k| (k, 0)).collect();
+    ///
+    /// let high_keys = treeindex.split_off(&40);
+    /// assert_eq!(treeindex.len(), 40);
+    /// assert_eq!(high_keys.len(), 24);
+    /// assert!(treeindex.read(&40, |_, v| *v).is_none());
+    /// assert_eq!(high_keys.read(&40, |_, v| *v), Some(0));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn split_off<Q>(&mut self, key_ref: &Q) -> TreeIndex<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let barrier = Barrier::new();
+        let high_key_treeindex = TreeIndex {
+            root: AtomicArc::null(),
+            config: self.config,
+        };
+        if let Some(root) = self.root.get_arc(Acquire, &barrier) {
+            let (low_key_root, high_key_root) = root.split_off(key_ref, &barrier);
+            self.root.swap(
+                (
+                    Some(Node::shrink_root(Arc::new(low_key_root), &barrier)),
+                    Tag::None,
+                ),
+                Release,
+            );
+            high_key_treeindex.root.swap(
+                (
+                    Some(Node::shrink_root(Arc::new(high_key_root), &barrier)),
+                    Tag::None,
+                ),
+                Release,
+            );
+            if self.first(&barrier).is_none() {
+                self.clear();
+            }
+            if high_key_treeindex.first(&barrier).is_none() {
+                high_key_treeindex.clear();
+            }
+        }
+        high_key_treeindex
+    }
+
+    /// Moves all the key-value pairs in `other` into the [`TreeIndex`], leaving `other` empty.
+    ///
+    /// If all the keys in `other` are greater than those in the [`TreeIndex`], the nodes of
+    /// `other` are relinked to the [`TreeIndex`] without copying key-value pairs, otherwise the
+    /// key-value pairs are copied one by one, and those in `other` take precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let mut treeindex: TreeIndex<u64, u32> = (0..32).map(|k| (k, 0)).collect();
+    /// let mut other: TreeIndex<u64, u32> = (32..64).map(|k| (k, 1)).collect();
+    ///
+    /// treeindex.append(&mut other);
+    /// assert_eq!(treeindex.len(), 64);
+    /// assert_eq!(treeindex.read(&48, |_, v| *v), Some(1));
+    /// assert!(other.is_empty());
+    /// ```
+    #[inline]
+    pub fn append(&mut self, other: &mut TreeIndex<K, V>) {
+        let barrier = Barrier::new();
+        let boundary_key = match (self.last(&barrier), other.first(&barrier)) {
+            (_, None) => return,
+            (None, Some(_)) => {
+                let (root, _) = other.root.swap((None, Tag::None), Relaxed);
+                self.root.swap((root, Tag::None), Release);
+                return;
+            }
+            (Some((max_key, _)), Some((min_key, _))) if max_key < min_key => max_key.clone(),
+            _ => {
+                for (key, value) in other.iter(&barrier) {
+                    self.upsert(key.clone(), value.clone());
+                }
+                other.clear();
+                return;
+            }
+        };
+        if let (Some(low_key_root), (Some(high_key_root), _)) = (
+            self.root.get_arc(Acquire, &barrier),
+            other.root.swap((None, Tag::None), Relaxed),
+        ) {
+            let root = Node::append(low_key_root, high_key_root, boundary_key, &barrier);
+            self.root.swap((Some(root), Tag::None), Release);
+        }
+    }
+
     /// Returns the size of the [`TreeIndex`].
     ///
     /// It internally scans all the leaf nodes, and therefore the time complexity is O(N).
@@ -510,10 +1189,32 @@ where
 
     /// Returns a [`Visitor`].
     ///
-    /// The returned [`Visitor`] starts scanning from the minimum key-value pair. Key-value pairs
-    /// are scanned in ascending order, and key-value pairs that have existed since the invocation
-    /// of the method are guaranteed to be visited if they are not removed. However, it is possible
-    /// to visit removed key-value pairs momentarily.
+    /// The returned [`Visitor`] starts scanning from the minimum key-value pair. Key-value pairs
+    /// are scanned in ascending order, and key-value pairs that have existed since the invocation
+    /// of the method are guaranteed to be visited if they are not removed. However, it is possible
+    /// to visit removed key-value pairs momentarily.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
+    ///
+    /// let barrier = Barrier::new();
+    /// let mut visitor = treeindex.iter(&barrier);
+    /// assert!(visitor.next().is_none());
+    /// ```
+    #[inline]
+    pub fn iter<'t, 'b>(&'t self, barrier: &'b Barrier) -> Visitor<'t, 'b, K, V> {
+        Visitor::new(&self.root, barrier)
+    }
+
+    /// Returns a [`Visitor`] that scans key-value pairs in descending order.
+    ///
+    /// The returned iterator starts scanning from the maximum key-value pair, and it is identical
+    /// to reversing the [`Visitor`] returned by [`TreeIndex::iter`].
     ///
     /// # Examples
     ///
@@ -523,20 +1224,26 @@ where
     ///
     /// let treeindex: TreeIndex<u64, u32> = TreeIndex::new();
     ///
+    /// assert!(treeindex.insert(1, 10).is_ok());
+    /// assert!(treeindex.insert(2, 11).is_ok());
+    ///
     /// let barrier = Barrier::new();
-    /// let mut visitor = treeindex.iter(&barrier);
+    /// let mut visitor = treeindex.iter_rev(&barrier);
+    /// assert_eq!(visitor.next(), Some((&2, &11)));
+    /// assert_eq!(visitor.next(), Some((&1, &10)));
     /// assert!(visitor.next().is_none());
     /// ```
     #[inline]
-    pub fn iter<'t, 'b>(&'t self, barrier: &'b Barrier) -> Visitor<'t, 'b, K, V> {
-        Visitor::new(&self.root, barrier)
+    pub fn iter_rev<'t, 'b>(&'t self, barrier: &'b Barrier) -> Rev<Visitor<'t, 'b, K, V>> {
+        self.iter(barrier).rev()
     }
 
     /// Returns a [`Range`] that scans keys in the given range.
     ///
-    /// Key-value pairs in the range are scanned in ascending order, and key-value pairs that have
-    /// existed since the invocation of the method are guaranteed to be visited if they are not
-    /// removed. However, it is possible to visit removed key-value pairs momentarily.
+    /// Key-value pairs in the range are scanned in ascending order, or in descending order from
+    /// the back end of the returned [`Range`], and key-value pairs that have existed since the
+    /// invocation of the method are guaranteed to be visited if they are not removed. However, it
+    /// is possible to visit removed key-value pairs momentarily.
     ///
     /// # Examples
     ///
@@ -548,15 +1255,275 @@ where
     ///
     /// let barrier = Barrier::new();
     /// assert_eq!(treeindex.range(4..=8, &barrier).count(), 0);
+    ///
+    /// for k in 0..16 {
+    ///     assert!(treeindex.insert(k, 0).is_ok());
+    /// }
+    /// let last_keys: Vec<u64> = treeindex.range(..8, &barrier).rev().take(2).map(|(k, _)| *k).collect();
+    /// assert_eq!(last_keys, vec![7, 6]);
     /// ```
     #[inline]
-    pub fn range<'t, 'b, R: RangeBounds<K>>(
+    pub fn range<'t, 'b, Q, R>(
         &'t self,
         range: R,
         barrier: &'b Barrier,
-    ) -> Range<'t, 'b, K, V, R> {
+    ) -> Range<'t, 'b, K, V, R, Q>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
         Range::new(&self.root, range, barrier)
     }
+
+    /// Replaces the entry associated with the key with a new one built by the updater.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a retry is required with a boolean flag indicating that the caller has
+    /// to wait.
+    fn update_entry<const ASYNC: bool, Q, F: FnMut(&V) -> V>(
+        &self,
+        key_ref: &Q,
+        updater: &mut F,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<bool, bool>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if let Some(root_ref) = self.root.load(Acquire, barrier).as_ref() {
+            if let Some((key, value)) = root_ref.search_entry(key_ref, barrier) {
+                // The value address identifies the entry as long as `barrier` is alive.
+                let value_ptr: *const V = value;
+                let on_duplicate = OnDuplicate::ReplaceIf(value_ptr as usize);
+                return match root_ref.insert_with(
+                    key.clone(),
+                    updater(value),
+                    on_duplicate,
+                    async_wait,
+                    barrier,
+                ) {
+                    Ok(InsertResult::Success) => Ok(true),
+                    Ok(InsertResult::Frozen(k, _) | InsertResult::Retry(k, _)) => {
+                        root_ref.cleanup_link(k.borrow(), false, barrier);
+                        Err(true)
+                    }
+                    Ok(InsertResult::Duplicate(..)) => Err(false),
+                    Ok(InsertResult::Full(k, v)) => {
+                        let _result = Node::split_root::<ASYNC>(k, v, &self.root, barrier);
+                        Err(false)
+                    }
+                    Ok(InsertResult::Retired(..)) => Err(!matches!(
+                        Node::remove_root(&self.root, async_wait, barrier),
+                        Ok(true)
+                    )),
+                    Err(_) => Err(true),
+                };
+            }
+        }
+        Ok(false)
+    }
+
+    /// Removes the minimum or maximum key-value pair, and moves it into `popped`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a retry is required with a boolean flag indicating that the caller has
+    /// to wait.
+    fn pop_entry<const LAST: bool>(
+        &self,
+        popped: &mut Option<(K, V)>,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<(), bool> {
+        if let Some(root_ref) = self.root.load(Acquire, barrier).as_ref() {
+            if popped.is_some() {
+                // The entry was removed, but the retired root could not be removed.
+                return match Node::remove_root(&self.root, async_wait, barrier) {
+                    Ok(_) => Ok(()),
+                    Err(()) => Err(true),
+                };
+            }
+            let entry = if LAST {
+                self.last(barrier)
+            } else {
+                self.first(barrier)
+            };
+            if let Some((key, value)) = entry {
+                // The value address identifies the entry as long as `barrier` is alive.
+                let mut condition = |v: &V| ptr::eq(v, value);
+                let result = root_ref.remove_if::<_, _>(key, &mut condition, async_wait, barrier);
+                if matches!(
+                    result,
+                    Ok(RemoveResult::Success | RemoveResult::Cleanup | RemoveResult::Retired)
+                        | Err(true)
+                ) {
+                    popped.replace((key.clone(), value.clone()));
+                }
+                return match result {
+                    Ok(RemoveResult::Success) => Ok(()),
+                    Ok(RemoveResult::Cleanup) => {
+                        root_ref.cleanup_link(key, false, barrier);
+                        Ok(())
+                    }
+                    Ok(RemoveResult::Retired) => {
+                        match Node::remove_root(&self.root, async_wait, barrier) {
+                            Ok(_) => Ok(()),
+                            Err(()) => Err(true),
+                        }
+                    }
+                    Ok(RemoveResult::Fail) => Err(false),
+                    Ok(RemoveResult::Frozen) | Err(_) => Err(true),
+                };
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the minimum or maximum key-value pair, and returns it.
+    async fn pop_entry_async<const LAST: bool>(&self) -> Option<(K, V)> {
+        let mut popped = None;
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            let result = self.pop_entry::<LAST>(
+                &mut popped,
+                Some(async_wait_pinned.mut_ptr()),
+                &Barrier::new(),
+            );
+            match result {
+                Ok(()) => return popped,
+                Err(true) => async_wait_pinned.await,
+                Err(false) => (),
+            }
+        }
+    }
+
+    /// Inserts a key-value pair, or replaces the existing entry according to the policy.
+    fn insert_with(
+        &self,
+        mut key: K,
+        mut value: V,
+        on_duplicate: OnDuplicate,
+    ) -> Result<(), (K, V)> {
+        loop {
+            let barrier = Barrier::new();
+            if let Some(root_ref) = self.root.load(Acquire, &barrier).as_ref() {
+                match root_ref.insert_with(key, value, on_duplicate, None, &barrier) {
+                    Ok(r) => match r {
+                        InsertResult::Success => return Ok(()),
+                        InsertResult::Frozen(k, v) | InsertResult::Retry(k, v) => {
+                            key = k;
+                            value = v;
+                            root_ref.cleanup_link(key.borrow(), false, &barrier);
+                        }
+                        InsertResult::Duplicate(k, v) => return Err((k, v)),
+                        InsertResult::Full(k, v) => {
+                            let (k, v) = Node::split_root::<false>(k, v, &self.root, &barrier);
+                            key = k;
+                            value = v;
+                            continue;
+                        }
+                        InsertResult::Retired(k, v) => {
+                            key = k;
+                            value = v;
+                            let _result = Node::remove_root(&self.root, None, &barrier);
+                        }
+                    },
+                    Err((k, v)) => {
+                        key = k;
+                        value = v;
+                    }
+                }
+            }
+
+            let new_root = Arc::new(Node::new_leaf_node_with_config(self.config));
+            let _result = self.root.compare_exchange(
+                Ptr::null(),
+                (Some(new_root), Tag::None),
+                AcqRel,
+                Acquire,
+                &barrier,
+            );
+        }
+    }
+
+    /// Inserts a key-value pair, or replaces the existing entry according to the policy.
+    async fn insert_with_async(
+        &self,
+        mut key: K,
+        mut value: V,
+        on_duplicate: OnDuplicate,
+    ) -> Result<(), (K, V)> {
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+
+            let need_await = {
+                let barrier = Barrier::new();
+                if let Some(root_ref) = self.root.load(Acquire, &barrier).as_ref() {
+                    match root_ref.insert_with(
+                        key,
+                        value,
+                        on_duplicate,
+                        Some(async_wait_pinned.mut_ptr()),
+                        &barrier,
+                    ) {
+                        Ok(r) => match r {
+                            InsertResult::Success => return Ok(()),
+                            InsertResult::Frozen(k, v) | InsertResult::Retry(k, v) => {
+                                key = k;
+                                value = v;
+                                root_ref.cleanup_link(key.borrow(), false, &barrier);
+                                true
+                            }
+                            InsertResult::Duplicate(k, v) => return Err((k, v)),
+                            InsertResult::Full(k, v) => {
+                                let (k, v) = Node::split_root::<true>(k, v, &self.root, &barrier);
+                                key = k;
+                                value = v;
+                                continue;
+                            }
+                            InsertResult::Retired(k, v) => {
+                                key = k;
+                                value = v;
+                                !matches!(
+                                    Node::remove_root(
+                                        &self.root,
+                                        Some(async_wait_pinned.mut_ptr()),
+                                        &barrier
+                                    ),
+                                    Ok(true)
+                                )
+                            }
+                        },
+                        Err((k, v)) => {
+                            key = k;
+                            value = v;
+                            true
+                        }
+                    }
+                } else {
+                    false
+                }
+            };
+
+            if need_await {
+                async_wait_pinned.await;
+            }
+
+            let new_root = Arc::new(Node::new_leaf_node_with_config(self.config));
+            let _result = self.root.compare_exchange(
+                Ptr::null(),
+                (Some(new_root), Tag::None),
+                AcqRel,
+                Acquire,
+                &Barrier::new(),
+            );
+        }
+    }
 }
 
 impl<K, V> Default for TreeIndex<K, V>
@@ -579,10 +1546,39 @@ where
     }
 }
 
+impl<K, V> FromIterator<(K, V)> for TreeIndex<K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Clone + Send + Sync,
+{
+    /// Creates a [`TreeIndex`] from key-value pairs.
+    ///
+    /// The key-value pairs are sorted before the [`TreeIndex`] is built bottom-up, and the first
+    /// one is kept among those having the same key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeIndex;
+    ///
+    /// let treeindex: TreeIndex<u64, u32> = [(3, 1), (1, 2), (3, 3)].into_iter().collect();
+    ///
+    /// assert_eq!(treeindex.len(), 2);
+    /// assert_eq!(treeindex.read(&3, |_, v| *v).unwrap(), 1);
+    /// ```
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut entries: Vec<(K, V)> = iter.into_iter().collect();
+        entries.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        TreeIndex::from_sorted_iter(entries)
+    }
+}
+
 /// [`Visitor`] scans all the key-value pairs in the [`TreeIndex`].
 ///
 /// It is guaranteed to visit all the key-value pairs that outlive the [`Visitor`], and it
-/// scans keys in monotonically increasing order.
+/// scans keys in monotonically increasing order, or in monotonically decreasing order from the
+/// back end.
 pub struct Visitor<'t, 'b, K, V>
 where
     K: 'static + Clone + Ord + Send + Sync,
@@ -590,6 +1586,10 @@ where
 {
     root: &'t AtomicArc<Node<K, V>>,
     leaf_scanner: Option<Scanner<'b, K, V>>,
+    back_scanner: Option<Scanner<'b, K, V>>,
+    front_key: Option<&'b K>,
+    back_key: Option<&'b K>,
+    finished: bool,
     barrier: &'b Barrier,
 }
 
@@ -602,18 +1602,15 @@ where
         Visitor::<'t, 'b, K, V> {
             root,
             leaf_scanner: None,
+            back_scanner: None,
+            front_key: None,
+            back_key: None,
+            finished: false,
             barrier,
         }
     }
-}
 
-impl<'t, 'b, K, V> Iterator for Visitor<'t, 'b, K, V>
-where
-    K: 'static + Clone + Ord + Send + Sync,
-    V: 'static + Clone + Send + Sync,
-{
-    type Item = (&'b K, &'b V);
-    fn next(&mut self) -> Option<Self::Item> {
+    fn next_unbounded(&mut self) -> Option<(&'b K, &'b V)> {
         // Starts scanning.
         if self.leaf_scanner.is_none() {
             let root_ptr = self.root.load(Acquire, self.barrier);
@@ -643,6 +1640,70 @@ where
         }
         None
     }
+
+    fn next_back_unbounded(&mut self) -> Option<(&'b K, &'b V)> {
+        // Proceeds to the previous entry in the current leaf.
+        if let Some(mut scanner) = self.back_scanner.take() {
+            if let Some(result) = scanner.prev() {
+                self.back_scanner.replace(scanner);
+                return Some(result);
+            }
+        }
+
+        // Searches for the previous leaf from the root.
+        let root_ref = self.root.load(Acquire, self.barrier).as_ref()?;
+        let scanner = if let Some(back_key) = self.back_key {
+            root_ref.max_less(back_key, self.barrier)?
+        } else {
+            root_ref.max(self.barrier)?
+        };
+        let result = scanner.get();
+        self.back_scanner.replace(scanner);
+        result
+    }
+}
+
+impl<'t, 'b, K, V> Iterator for Visitor<'t, 'b, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Clone + Send + Sync,
+{
+    type Item = (&'b K, &'b V);
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        if let Some((key_ref, value_ref)) = self.next_unbounded() {
+            // Stops if it met the back end.
+            if !matches!(self.back_key, Some(back_key) if key_ref >= back_key) {
+                self.front_key.replace(key_ref);
+                return Some((key_ref, value_ref));
+            }
+        }
+        self.finished = true;
+        None
+    }
+}
+
+impl<K, V> DoubleEndedIterator for Visitor<'_, '_, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Clone + Send + Sync,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        if let Some((key_ref, value_ref)) = self.next_back_unbounded() {
+            self.back_key.replace(key_ref);
+            // Stops if it met the front end.
+            if !matches!(self.front_key, Some(front_key) if key_ref <= front_key) {
+                return Some((key_ref, value_ref));
+            }
+        }
+        self.finished = true;
+        None
+    }
 }
 
 impl<'t, 'b, K, V> FusedIterator for Visitor<'t, 'b, K, V>
@@ -656,38 +1717,50 @@ where
 ///
 /// It is identical to [`Visitor`] except that it does not traverse keys outside of the given
 /// range.
-pub struct Range<'t, 'b, K, V, R>
+pub struct Range<'t, 'b, K, V, R, Q: ?Sized = K>
 where
-    K: 'static + Clone + Ord + Send + Sync,
+    K: 'static + Borrow<Q> + Clone + Ord + Send + Sync,
     V: 'static + Clone + Send + Sync,
-    R: 'static + RangeBounds<K>,
+    Q: Ord,
+    R: 'static + RangeBounds<Q>,
 {
     root: &'t AtomicArc<Node<K, V>>,
     leaf_scanner: Option<Scanner<'b, K, V>>,
+    back_scanner: Option<Scanner<'b, K, V>>,
+    front_key: Option<&'b K>,
+    back_key: Option<&'b K>,
+    finished: bool,
     range: R,
     check_lower_bound: bool,
     check_upper_bound: bool,
     barrier: &'b Barrier,
+    query: PhantomData<fn(&Q)>,
 }
 
-impl<'t, 'b, K, V, R> Range<'t, 'b, K, V, R>
+impl<'t, 'b, K, V, R, Q> Range<'t, 'b, K, V, R, Q>
 where
-    K: 'static + Clone + Ord + Send + Sync,
+    K: 'static + Borrow<Q> + Clone + Ord + Send + Sync,
     V: 'static + Clone + Send + Sync,
-    R: RangeBounds<K>,
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
 {
     fn new(
         root: &'t AtomicArc<Node<K, V>>,
         range: R,
         barrier: &'b Barrier,
-    ) -> Range<'t, 'b, K, V, R> {
-        Range::<'t, 'b, K, V, R> {
+    ) -> Range<'t, 'b, K, V, R, Q> {
+        Range::<'t, 'b, K, V, R, Q> {
             root,
             leaf_scanner: None,
+            back_scanner: None,
+            front_key: None,
+            back_key: None,
+            finished: false,
             range,
             check_lower_bound: true,
             check_upper_bound: false,
             barrier,
+            query: PhantomData,
         }
     }
 
@@ -703,35 +1776,37 @@ where
                         None
                     }
                 };
-                if let Some(leaf_scanner) = min_allowed_key.map_or_else(
-                    || {
-                        // Take the min entry.
+                if let Some(leaf_scanner) = min_allowed_key
+                    .and_then(|min_allowed_key| {
+                        // Take an entry that is close enough to the lower bound.
+                        root_ref.max_le_appr(min_allowed_key, self.barrier)
+                    })
+                    .or_else(|| {
+                        // Take the min entry if no keys are smaller than the lower bound.
                         if let Some(mut min_scanner) = root_ref.min(self.barrier) {
                             min_scanner.next();
                             Some(min_scanner)
                         } else {
                             None
                         }
-                    },
-                    |min_allowed_key| {
-                        // Take an entry that is close enough to the lower bound.
-                        root_ref.max_le_appr(min_allowed_key, self.barrier)
-                    },
-                ) {
+                    })
+                {
                     // Need to check the upper bound.
                     self.check_upper_bound = match self.range.end_bound() {
-                        Excluded(key) => leaf_scanner
-                            .max_entry()
-                            .map_or(false, |max_entry| max_entry.0.cmp(key) != Ordering::Less),
-                        Included(key) => leaf_scanner
-                            .max_entry()
-                            .map_or(false, |max_entry| max_entry.0.cmp(key) == Ordering::Greater),
+                        Excluded(key) => leaf_scanner.max_entry().map_or(false, |max_entry| {
+                            max_entry.0.borrow().cmp(key) != Ordering::Less
+                        }),
+                        Included(key) => leaf_scanner.max_entry().map_or(false, |max_entry| {
+                            max_entry.0.borrow().cmp(key) == Ordering::Greater
+                        }),
                         Unbounded => false,
                     };
-                    if let Some(result) = leaf_scanner.get() {
-                        self.leaf_scanner.replace(leaf_scanner);
-                        return Some(result);
+                    let result = leaf_scanner.get();
+                    self.leaf_scanner.replace(leaf_scanner);
+                    if result.is_some() {
+                        return result;
                     }
+                    // The `Leaf` has been emptied, therefore scanning continues from the next one.
                 }
             } else {
                 // Empty.
@@ -747,15 +1822,15 @@ where
                 return Some(result);
             }
             // Go to the next leaf node.
-            if let Some(new_scanner) = scanner.jump(min_allowed_key, self.barrier).take() {
+            if let Some(new_scanner) = scanner.jump::<K>(min_allowed_key, self.barrier).take() {
                 if let Some(entry) = new_scanner.get() {
                     self.check_upper_bound = match self.range.end_bound() {
-                        Excluded(key) => new_scanner
-                            .max_entry()
-                            .map_or(false, |max_entry| max_entry.0.cmp(key) != Ordering::Less),
-                        Included(key) => new_scanner
-                            .max_entry()
-                            .map_or(false, |max_entry| max_entry.0.cmp(key) == Ordering::Greater),
+                        Excluded(key) => new_scanner.max_entry().map_or(false, |max_entry| {
+                            max_entry.0.borrow().cmp(key) != Ordering::Less
+                        }),
+                        Included(key) => new_scanner.max_entry().map_or(false, |max_entry| {
+                            max_entry.0.borrow().cmp(key) == Ordering::Greater
+                        }),
                         Unbounded => false,
                     };
                     self.leaf_scanner.replace(new_scanner);
@@ -765,26 +1840,65 @@ where
         }
         None
     }
+
+    fn next_back_unbounded(&mut self) -> Option<(&'b K, &'b V)> {
+        // Proceeds to the previous entry in the current leaf.
+        if let Some(mut scanner) = self.back_scanner.take() {
+            if let Some(result) = scanner.prev() {
+                self.back_scanner.replace(scanner);
+                return Some(result);
+            }
+        }
+
+        // Searches for the previous leaf from the root.
+        let root_ref = self.root.load(Acquire, self.barrier).as_ref()?;
+        let scanner = if let Some(back_key) = self.back_key {
+            root_ref.max_less::<K>(back_key, self.barrier)?
+        } else {
+            match self.range.end_bound() {
+                Excluded(key) => root_ref.max_less(key, self.barrier)?,
+                Included(key) => {
+                    if let Some(entry) = root_ref.search_entry(key, self.barrier) {
+                        return Some(entry);
+                    }
+                    root_ref.max_less(key, self.barrier)?
+                }
+                Unbounded => root_ref.max(self.barrier)?,
+            }
+        };
+        let result = scanner.get();
+        self.back_scanner.replace(scanner);
+        result
+    }
 }
 
-impl<'t, 'b, K, V, R> Iterator for Range<'t, 'b, K, V, R>
+impl<'t, 'b, K, V, R, Q> Iterator for Range<'t, 'b, K, V, R, Q>
 where
-    K: 'static + Clone + Ord + Send + Sync,
+    K: 'static + Borrow<Q> + Clone + Ord + Send + Sync,
     V: 'static + Clone + Send + Sync,
-    R: RangeBounds<K>,
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
 {
     type Item = (&'b K, &'b V);
     fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
         while let Some((key_ref, value_ref)) = self.next_unbounded() {
+            if matches!(self.back_key, Some(back_key) if key_ref >= back_key) {
+                // Met the back end.
+                break;
+            }
+            self.front_key.replace(key_ref);
             if self.check_lower_bound {
                 match self.range.start_bound() {
                     Excluded(key) => {
-                        if key_ref.cmp(key) != Ordering::Greater {
+                        if key_ref.borrow().cmp(key) != Ordering::Greater {
                             continue;
                         }
                     }
                     Included(key) => {
-                        if key_ref.cmp(key) == Ordering::Less {
+                        if key_ref.borrow().cmp(key) == Ordering::Less {
                             continue;
                         }
                     }
@@ -795,12 +1909,12 @@ where
             if self.check_upper_bound {
                 match self.range.end_bound() {
                     Excluded(key) => {
-                        if key_ref.cmp(key) == Ordering::Less {
+                        if key_ref.borrow().cmp(key) == Ordering::Less {
                             return Some((key_ref, value_ref));
                         }
                     }
                     Included(key) => {
-                        if key_ref.cmp(key) != Ordering::Greater {
+                        if key_ref.borrow().cmp(key) != Ordering::Greater {
                             return Some((key_ref, value_ref));
                         }
                     }
@@ -812,14 +1926,45 @@ where
             }
             return Some((key_ref, value_ref));
         }
+        self.finished = true;
         None
     }
 }
 
-impl<'t, 'b, K, V, R> FusedIterator for Range<'t, 'b, K, V, R>
+impl<K, V, R, Q> DoubleEndedIterator for Range<'_, '_, K, V, R, Q>
 where
-    K: 'static + Clone + Ord + Send + Sync,
+    K: 'static + Borrow<Q> + Clone + Ord + Send + Sync,
+    V: 'static + Clone + Send + Sync,
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        if let Some((key_ref, value_ref)) = self.next_back_unbounded() {
+            self.back_key.replace(key_ref);
+            // Stops if it met the front end or the lower bound.
+            let in_range = !matches!(self.front_key, Some(front_key) if key_ref <= front_key)
+                && match self.range.start_bound() {
+                    Excluded(key) => key_ref.borrow().cmp(key) == Ordering::Greater,
+                    Included(key) => key_ref.borrow().cmp(key) != Ordering::Less,
+                    Unbounded => true,
+                };
+            if in_range {
+                return Some((key_ref, value_ref));
+            }
+        }
+        self.finished = true;
+        None
+    }
+}
+
+impl<'t, 'b, K, V, R, Q> FusedIterator for Range<'t, 'b, K, V, R, Q>
+where
+    K: 'static + Borrow<Q> + Clone + Ord + Send + Sync,
     V: 'static + Clone + Send + Sync,
-    R: RangeBounds<K>,
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
 {
 }
@@ -1,12 +1,17 @@
-use super::leaf::{InsertResult, Leaf, RemoveResult, Scanner, DIMENSION};
+use super::leaf::{
+    is_above_range, is_below_range, InsertResult, Leaf, OnDuplicate, RemoveResult, Scanner,
+    DIMENSION,
+};
 use super::leaf_node::{LOCKED, RETIRED};
-use super::node::{Node, Type};
+use super::node::{Config, Node, Type};
 
 use crate::ebr::{Arc, AtomicArc, Barrier, Ptr, Tag};
 use crate::wait_queue::{AsyncWait, WaitQueue};
 
 use std::borrow::Borrow;
 use std::cmp::Ordering::{Equal, Greater, Less};
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
 use std::ptr::addr_of;
 use std::sync::atomic::Ordering::{self, Acquire, Relaxed, Release};
 
@@ -33,6 +38,9 @@ where
 
     /// `wait_queue` for `latch`.
     wait_queue: WaitQueue,
+
+    /// The capacity of [`Leaf`] instances and the fan-out of the [`InternalNode`].
+    config: Config,
 }
 
 impl<K, V> InternalNode<K, V>
@@ -40,16 +48,124 @@ where
     K: 'static + Clone + Ord + Send + Sync,
     V: 'static + Clone + Send + Sync,
 {
-    /// Creates a new empty internal node.
-    pub(crate) fn new() -> InternalNode<K, V> {
+    /// Creates a new empty internal node with the given [`Config`].
+    pub(crate) fn with_config(config: Config) -> InternalNode<K, V> {
         InternalNode {
-            children: Leaf::new(),
+            children: Leaf::with_capacity(config.node_capacity()),
             unbounded_child: AtomicArc::null(),
             latch: AtomicArc::null(),
             wait_queue: WaitQueue::default(),
+            config,
+        }
+    }
+
+    /// Creates a new [`InternalNode`] from child nodes sorted in ascending key order.
+    ///
+    /// Each bounded child node is paired with the upper bound of its keys; the number of bounded
+    /// child nodes must not exceed the capacity of an [`InternalNode`].
+    pub(crate) fn from_children(
+        bounded_children: Vec<(K, Arc<Node<K, V>>)>,
+        unbounded_child: Arc<Node<K, V>>,
+        config: Config,
+    ) -> InternalNode<K, V> {
+        let children = Leaf::with_capacity(config.node_capacity());
+        for (bound, child) in bounded_children {
+            let result = children.insert(bound, AtomicArc::from(child));
+            debug_assert!(matches!(result, InsertResult::Success));
+        }
+        InternalNode {
+            children,
+            unbounded_child: AtomicArc::from(unbounded_child),
+            latch: AtomicArc::null(),
+            wait_queue: WaitQueue::default(),
+            config,
         }
     }
 
+    /// Returns the [`Config`] of the [`InternalNode`].
+    pub(crate) fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Splits the [`InternalNode`] into two at the given key.
+    ///
+    /// Only the child node containing the key is split, and the other child nodes are relinked to
+    /// either of the new [`InternalNode`] instances. It must not be called while other threads
+    /// are able to access the [`InternalNode`].
+    pub(crate) fn split_off<Q>(
+        &self,
+        key: &Q,
+        barrier: &Barrier,
+    ) -> (InternalNode<K, V>, InternalNode<K, V>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if self.unbounded_child.is_null(Relaxed) {
+            // A retired `InternalNode` is split into two retired ones for the parent to clean
+            // them up.
+            let (low_key_node, high_key_node) = (
+                InternalNode::with_config(self.config),
+                InternalNode::with_config(self.config),
+            );
+            low_key_node.unbounded_child.swap((None, RETIRED), Relaxed);
+            high_key_node.unbounded_child.swap((None, RETIRED), Relaxed);
+            return (low_key_node, high_key_node);
+        }
+        let unbounded_child = self.unbounded_child.get_arc(Relaxed, barrier).unwrap();
+
+        let mut low_key_children = Vec::new();
+        let mut high_key_children = Vec::new();
+        let mut low_key_unbounded_child = None;
+        for (bound, child) in Scanner::new(&self.children) {
+            let child = child.get_arc(Relaxed, barrier).unwrap();
+            if low_key_unbounded_child.is_some() {
+                high_key_children.push((bound.clone(), child));
+            } else if bound.borrow() < key {
+                low_key_children.push((bound.clone(), child));
+            } else {
+                let (low_key_child, high_key_child) = child.split_off(key, barrier);
+                high_key_children.push((bound.clone(), Arc::new(high_key_child)));
+                low_key_unbounded_child.replace(Arc::new(low_key_child));
+            }
+        }
+        let (low_key_unbounded_child, high_key_unbounded_child) =
+            if let Some(low_key_unbounded_child) = low_key_unbounded_child {
+                (low_key_unbounded_child, unbounded_child)
+            } else {
+                let (low_key_child, high_key_child) = unbounded_child.split_off(key, barrier);
+                (Arc::new(low_key_child), Arc::new(high_key_child))
+            };
+        (
+            InternalNode::from_children(low_key_children, low_key_unbounded_child, self.config),
+            InternalNode::from_children(high_key_children, high_key_unbounded_child, self.config),
+        )
+    }
+
+    /// Returns the bounded child nodes paired with the upper bounds of their keys.
+    pub(crate) fn bounded_children(&self, barrier: &Barrier) -> Vec<(K, Arc<Node<K, V>>)> {
+        Scanner::new(&self.children)
+            .filter_map(|(bound, child)| {
+                child
+                    .get_arc(Relaxed, barrier)
+                    .map(|child| (bound.clone(), child))
+            })
+            .collect()
+    }
+
+    /// Returns the first or last child node of the [`InternalNode`].
+    pub(crate) fn edge_child<const LAST: bool>(
+        &self,
+        barrier: &Barrier,
+    ) -> Option<Arc<Node<K, V>>> {
+        if !LAST {
+            if let Some((_, child)) = Scanner::new(&self.children).next() {
+                return child.get_arc(Relaxed, barrier);
+            }
+        }
+        self.unbounded_child.get_arc(Relaxed, barrier)
+    }
+
     /// Returns the depth of the node.
     pub(crate) fn depth(&self, depth: usize, barrier: &Barrier) -> usize {
         let unbounded_ptr = self.unbounded_child.load(Relaxed, barrier);
@@ -66,6 +182,19 @@ where
 
     /// Searches for an entry associated with the given key.
     pub(crate) fn search<'b, Q>(&self, key: &Q, barrier: &'b Barrier) -> Option<&'b V>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search_entry(key, barrier).map(|(_, v)| v)
+    }
+
+    /// Searches for an entry associated with the given key, and returns the key-value pair.
+    pub(crate) fn search_entry<'b, Q>(
+        &self,
+        key: &Q,
+        barrier: &'b Barrier,
+    ) -> Option<(&'b K, &'b V)>
     where
         K: 'b + Borrow<Q>,
         Q: Ord + ?Sized,
@@ -76,7 +205,7 @@ where
                 if let Some(child) = child.load(Acquire, barrier).as_ref() {
                     if self.children.validate(metadata) {
                         // Data race resolution - see `LeafNode::search`.
-                        return child.search(key, barrier);
+                        return child.search_entry(key, barrier);
                     }
                 }
                 continue;
@@ -86,7 +215,7 @@ where
                 if !self.children.validate(metadata) {
                     continue;
                 }
-                return unbounded.search(key, barrier);
+                return unbounded.search_entry(key, barrier);
             }
             return None;
         }
@@ -178,11 +307,197 @@ where
         None
     }
 
+    /// Returns a [`Scanner`] pointing to the entry with the minimum key among those keys equal to
+    /// or greater than the given key.
+    ///
+    /// It returns `None` if all the keys in the [`InternalNode`] are smaller than the given key.
+    pub(crate) fn min_greater_equal<'b, Q>(
+        &self,
+        key: &Q,
+        barrier: &'b Barrier,
+    ) -> Option<Scanner<'b, K, V>>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let (child, metadata) = self.children.min_greater_equal(key);
+            if let Some((_, child)) = child {
+                if let Some(child) = child.load(Acquire, barrier).as_ref() {
+                    if self.children.validate(metadata) {
+                        // Data race resolution - see `LeafNode::search`.
+                        if let Some(scanner) = child.min_greater_equal(key, barrier) {
+                            return Some(scanner);
+                        }
+
+                        // All the keys in `child` are smaller than the given key.
+                        if let Some(mut scanner) = Scanner::min_greater_equal(&self.children, key) {
+                            if scanner.metadata() == metadata {
+                                if let Ok(result) = self.min_in_children(&mut scanner, key, barrier)
+                                {
+                                    return result;
+                                }
+                            }
+                        }
+                    }
+                }
+                // It is not a hot loop - see `LeafNode::search`.
+                continue;
+            }
+            let unbounded_ptr = self.unbounded_child.load(Acquire, barrier);
+            if let Some(unbounded) = unbounded_ptr.as_ref() {
+                if self.children.validate(metadata) {
+                    return unbounded.min_greater_equal(key, barrier);
+                }
+                continue;
+            }
+            return None;
+        }
+    }
+
+    /// Returns a [`Scanner`] pointing to the entry with the minimum key among those keys equal to
+    /// or greater than the given key in the children that the given [`Scanner`] has not visited
+    /// yet in ascending order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the children of the [`InternalNode`] have been modified.
+    fn min_in_children<'b, Q>(
+        &self,
+        scanner: &mut Scanner<'_, K, AtomicArc<Node<K, V>>>,
+        key: &Q,
+        barrier: &'b Barrier,
+    ) -> Result<Option<Scanner<'b, K, V>>, ()>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        while let Some((_, child)) = scanner.next() {
+            if let Some(child) = child.load(Acquire, barrier).as_ref() {
+                if self.children.validate(scanner.metadata()) {
+                    if let Some(child_scanner) = child.min_greater_equal(key, barrier) {
+                        return Ok(Some(child_scanner));
+                    }
+                    continue;
+                }
+            }
+            return Err(());
+        }
+        let unbounded_ptr = self.unbounded_child.load(Acquire, barrier);
+        if let Some(unbounded) = unbounded_ptr.as_ref() {
+            if self.children.validate(scanner.metadata()) {
+                return Ok(unbounded.min_greater_equal(key, barrier));
+            }
+            return Err(());
+        }
+        Ok(None)
+    }
+
+    /// Returns a [`Scanner`] pointing to the maximum key entry.
+    pub(crate) fn max<'b>(&self, barrier: &'b Barrier) -> Option<Scanner<'b, K, V>> {
+        loop {
+            let mut scanner = Scanner::new(&self.children);
+            let unbounded_ptr = self.unbounded_child.load(Acquire, barrier);
+            if let Some(unbounded) = unbounded_ptr.as_ref() {
+                if self.children.validate(scanner.metadata()) {
+                    // Data race resolution - see `LeafNode::search`.
+                    if let Some(scanner) = unbounded.max(barrier) {
+                        return Some(scanner);
+                    }
+                    scanner.prev();
+                    if let Ok(result) = self.max_in_children(&mut scanner, barrier) {
+                        return result;
+                    }
+                }
+                // It is not a hot loop - see `LeafNode::search`.
+                continue;
+            }
+            return None;
+        }
+    }
+
+    /// Returns a [`Scanner`] pointing to the entry with the maximum key among those keys smaller
+    /// than the given key.
+    ///
+    /// It returns `None` if all the keys in the [`InternalNode`] are equal to or greater than the
+    /// given key.
+    pub(crate) fn max_less<'b, Q>(&self, key: &Q, barrier: &'b Barrier) -> Option<Scanner<'b, K, V>>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let (child, metadata) = self.children.min_greater_equal(key);
+            let child_ptr = if let Some((_, child)) = child {
+                child.load(Acquire, barrier)
+            } else {
+                self.unbounded_child.load(Acquire, barrier)
+            };
+            if let Some(child) = child_ptr.as_ref() {
+                if self.children.validate(metadata) {
+                    // Data race resolution - see `LeafNode::search`.
+                    if let Some(scanner) = child.max_less(key, barrier) {
+                        return Some(scanner);
+                    }
+
+                    // All the keys in `child` are equal to or greater than the given key.
+                    let mut scanner = Scanner::max_less(&self.children, key)?;
+                    if let Ok(result) = self.max_in_children(&mut scanner, barrier) {
+                        return result;
+                    }
+                }
+            } else if child.is_none() {
+                return None;
+            }
+            // It is not a hot loop - see `LeafNode::search`.
+        }
+    }
+
+    /// Returns a [`Scanner`] pointing to the maximum key entry in the children that the given
+    /// [`Scanner`] is pointing to or has not visited yet in descending order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the children of the [`InternalNode`] have been modified.
+    fn max_in_children<'b>(
+        &self,
+        scanner: &mut Scanner<'_, K, AtomicArc<Node<K, V>>>,
+        barrier: &'b Barrier,
+    ) -> Result<Option<Scanner<'b, K, V>>, ()> {
+        let mut child_entry = scanner.get();
+        while let Some((_, child)) = child_entry {
+            if let Some(child) = child.load(Acquire, barrier).as_ref() {
+                if self.children.validate(scanner.metadata()) {
+                    if let Some(child_scanner) = child.max(barrier) {
+                        return Ok(Some(child_scanner));
+                    }
+                    child_entry = scanner.prev();
+                    continue;
+                }
+            }
+            return Err(());
+        }
+        Ok(None)
+    }
+
     /// Inserts a key-value pair.
+    #[cfg(test)]
     pub(crate) fn insert(
+        &self,
+        key: K,
+        value: V,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<InsertResult<K, V>, (K, V)> {
+        self.insert_with(key, value, OnDuplicate::Reject, async_wait, barrier)
+    }
+
+    /// Inserts a key-value pair, or replaces the existing entry according to the policy.
+    pub(crate) fn insert_with(
         &self,
         mut key: K,
         mut value: V,
+        on_duplicate: OnDuplicate,
         async_wait: Option<*mut AsyncWait>,
         barrier: &Barrier,
     ) -> Result<InsertResult<K, V>, (K, V)> {
@@ -193,7 +508,13 @@ where
                 if let Some(child_ref) = child_ptr.as_ref() {
                     if self.children.validate(metadata) {
                         // Data race resolution - see `LeafNode::search`.
-                        match child_ref.insert(key, value, async_wait, barrier)? {
+                        match child_ref.insert_with(
+                            key,
+                            value,
+                            on_duplicate,
+                            async_wait,
+                            barrier,
+                        )? {
                             InsertResult::Success => return Ok(InsertResult::Success),
                             InsertResult::Duplicate(k, v) => {
                                 return Ok(InsertResult::Duplicate(k, v));
@@ -241,7 +562,7 @@ where
                 if !self.children.validate(metadata) {
                     continue;
                 }
-                match unbounded.insert(key, value, async_wait, barrier)? {
+                match unbounded.insert_with(key, value, on_duplicate, async_wait, barrier)? {
                     InsertResult::Success => return Ok(InsertResult::Success),
                     InsertResult::Duplicate(k, v) => {
                         return Ok(InsertResult::Duplicate(k, v));
@@ -345,6 +666,105 @@ where
         }
     }
 
+    /// Removes all the entries in the given range.
+    ///
+    /// The number of removed entries is added to `removed`, and retired children are unlinked at
+    /// once after the whole range has been processed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a retry is required.
+    pub(crate) fn remove_range<Q, R>(
+        &self,
+        range: &R,
+        removed: &mut usize,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<RemoveResult, ()>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut retired = false;
+        let mut cleanup = false;
+        let mut failed = false;
+        loop {
+            let mut retry = false;
+            let mut prev_bound = None;
+            let scanner = Scanner::new(&self.children);
+            let metadata = scanner.metadata();
+            for (bound, child) in scanner {
+                if matches!(prev_bound, Some(prev_bound) if is_above_range(range, prev_bound)) {
+                    break;
+                }
+                prev_bound.replace(bound);
+                if is_below_range(range, bound) {
+                    continue;
+                }
+                if let Some(child) = child.load(Acquire, barrier).as_ref() {
+                    if self.children.validate(metadata) {
+                        // Data race resolution - see `LeafNode::search`.
+                        match child.remove_range(range, removed, async_wait, barrier) {
+                            Ok(RemoveResult::Retired) => retired = true,
+                            Ok(RemoveResult::Cleanup) => cleanup = true,
+                            Ok(_) => (),
+                            Err(()) => failed = true,
+                        }
+                        if failed {
+                            break;
+                        }
+                        continue;
+                    }
+                }
+                // It is not a hot loop - see `LeafNode::search`.
+                retry = true;
+                break;
+            }
+            if retry {
+                continue;
+            }
+            if !failed
+                && !matches!(prev_bound, Some(prev_bound) if is_above_range(range, prev_bound))
+            {
+                let unbounded_ptr = self.unbounded_child.load(Acquire, barrier);
+                if let Some(unbounded) = unbounded_ptr.as_ref() {
+                    if !self.children.validate(metadata) {
+                        // Data race resolution - see `LeafNode::search`.
+                        continue;
+                    }
+                    match unbounded.remove_range(range, removed, async_wait, barrier) {
+                        Ok(RemoveResult::Retired) => retired = true,
+                        Ok(RemoveResult::Cleanup) => cleanup = true,
+                        Ok(_) => (),
+                        Err(()) => failed = true,
+                    }
+                }
+            }
+            break;
+        }
+
+        if cleanup {
+            // Retired `Leaves` preceding the range are unlinked from the `Leaf` right before it.
+            cleanup = match range.start_bound() {
+                Included(key) | Excluded(key) => !self.cleanup_link(key, false, barrier),
+                Unbounded => false,
+            };
+        }
+        let mut result = if retired {
+            self.coalesce::<K>(barrier)
+        } else {
+            RemoveResult::Success
+        };
+        if cleanup && result == RemoveResult::Success {
+            result = RemoveResult::Cleanup;
+        }
+        if failed {
+            return Err(());
+        }
+        Ok(result)
+    }
+
     /// Splits a full node.
     ///
     /// # Errors
@@ -411,8 +831,8 @@ where
 
                 // Copies nodes except for the known full node to the newly allocated internal node entries.
                 let internal_nodes = (
-                    Arc::new(Node::new_internal_node()),
-                    Arc::new(Node::new_internal_node()),
+                    Arc::new(Node::new_internal_node_with_config(self.config)),
+                    Arc::new(Node::new_internal_node_with_config(self.config)),
                 );
                 let low_key_nodes =
                     if let Type::Internal(low_key_internal_node) = &internal_nodes.0.node() {
@@ -534,8 +954,8 @@ where
             Type::Leaf(full_leaf_node) => {
                 // Copies leaves except for the known full leaf to the newly allocated leaf node entries.
                 let leaf_nodes = (
-                    Arc::new(Node::new_leaf_node()),
-                    Arc::new(Node::new_leaf_node()),
+                    Arc::new(Node::new_leaf_node_with_config(self.config)),
+                    Arc::new(Node::new_leaf_node_with_config(self.config)),
                 );
                 let low_key_leaf_node = if let Type::Leaf(low_key_leaf_node) = &leaf_nodes.0.node()
                 {
@@ -873,10 +1293,12 @@ mod test {
                     unbounded_child: AtomicArc::new(Node::new_leaf_node()),
                     latch: AtomicArc::null(),
                     wait_queue: WaitQueue::default(),
+                    config: Config::default(),
                 }),
             }),
             latch: AtomicArc::null(),
             wait_queue: WaitQueue::default(),
+            config: Config::default(),
         }
     }
 
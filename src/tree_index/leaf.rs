@@ -1,9 +1,11 @@
-use crate::ebr::{Arc, AtomicArc, Barrier};
+use crate::ebr::{Arc, AtomicArc, Barrier, Tag};
 use crate::LinkedList;
 
 use std::borrow::Borrow;
 use std::cmp::Ordering;
 use std::mem::{size_of, MaybeUninit};
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
 use std::ptr::{self, addr_of};
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
@@ -52,6 +54,48 @@ pub enum RemoveResult {
     Frozen,
 }
 
+/// Returns `true` if the keys smaller than or equal to `key` are all out of the range.
+pub fn is_below_range<K, Q, R>(range: &R, key: &K) -> bool
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
+{
+    let key: &Q = key.borrow();
+    match range.start_bound() {
+        Included(start) => key < start,
+        Excluded(start) => key <= start,
+        Unbounded => false,
+    }
+}
+
+/// Returns `true` if the keys greater than `key` are all out of the range.
+pub fn is_above_range<K, Q, R>(range: &R, key: &K) -> bool
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
+{
+    let key: &Q = key.borrow();
+    match range.end_bound() {
+        Included(end) | Excluded(end) => key >= end,
+        Unbounded => false,
+    }
+}
+
+/// The policy of insertion when the key exists.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OnDuplicate {
+    /// Insertion fails.
+    Reject,
+
+    /// The existing entry is replaced.
+    Replace,
+
+    /// The existing entry is replaced if its value is stored at the given address.
+    ReplaceIf(usize),
+}
+
 /// The number of entries and number of state bits per entry.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Dimension {
@@ -174,6 +218,9 @@ where
     /// The entry state transitions as follows.
     /// * Uninit -> removed -> rank -> removed.
     metadata: AtomicUsize,
+
+    /// The maximum number of entries that the [`Leaf`] can store.
+    capacity: usize,
 }
 
 impl<K, V> Leaf<K, V>
@@ -182,11 +229,21 @@ where
     V: 'static + Clone + Sync,
 {
     /// Creates a new [`Leaf`].
+    #[cfg(test)]
     pub fn new() -> Leaf<K, V> {
+        Self::with_capacity(DIMENSION.num_entries)
+    }
+
+    /// Creates a new [`Leaf`] that stores up to `capacity` entries.
+    ///
+    /// The metadata encoding does not change, and slots beyond the capacity are never used.
+    pub fn with_capacity(capacity: usize) -> Leaf<K, V> {
+        debug_assert!((2..=DIMENSION.num_entries).contains(&capacity));
         Leaf {
             entry_array: unsafe { MaybeUninit::uninit().assume_init() },
             link: AtomicArc::null(),
             metadata: AtomicUsize::new(0),
+            capacity,
         }
     }
 
@@ -228,6 +285,14 @@ where
 
     /// Inserts a key value pair.
     pub fn insert(&self, key: K, value: V) -> InsertResult<K, V> {
+        self.insert_with(key, value, OnDuplicate::Reject)
+    }
+
+    /// Inserts a key value pair, or replaces the existing entry according to the policy.
+    ///
+    /// The existing entry is replaced by updating the metadata once, therefore readers see either
+    /// the old entry or the new one.
+    pub fn insert_with(&self, key: K, value: V, on_duplicate: OnDuplicate) -> InsertResult<K, V> {
         let mut metadata = self.metadata.load(Acquire);
         while !Dimension::retired(metadata) {
             if Dimension::frozen(metadata) {
@@ -235,7 +300,7 @@ where
             }
 
             let mut has_free_slot = false;
-            for i in 0..DIMENSION.num_entries {
+            for i in 0..self.capacity {
                 let rank = DIMENSION.state(metadata, i);
                 if rank == Dimension::uninit_state() {
                     has_free_slot = true;
@@ -254,12 +319,19 @@ where
                     }
 
                     self.write(i, key, value);
-                    return self.post_insert(i, interim_metadata);
+                    return self.post_insert(i, interim_metadata, on_duplicate);
                 }
             }
 
             if !has_free_slot {
-                if self.search_slot(key.borrow(), metadata).is_some() {
+                if let Some(index) = self.search_slot(key.borrow(), metadata) {
+                    if self.replaceable(index, on_duplicate) {
+                        // A free slot is required to replace the entry.
+                        return InsertResult::Full(key, value);
+                    }
+                    return InsertResult::Duplicate(key, value);
+                }
+                if let OnDuplicate::ReplaceIf(_) = on_duplicate {
                     return InsertResult::Duplicate(key, value);
                 }
                 return InsertResult::Full(key, value);
@@ -352,14 +424,75 @@ where
         RemoveResult::Fail
     }
 
+    /// Removes all the entries in the given range.
+    ///
+    /// The number of removed entries is added to `removed`.
+    pub fn remove_range<Q, R>(&self, range: &R, removed: &mut usize) -> RemoveResult
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut metadata = self.metadata.load(Acquire);
+        loop {
+            if Dimension::frozen(metadata) {
+                return RemoveResult::Frozen;
+            }
+            let mut new_metadata = metadata;
+            let mut num_removed = 0;
+            let mut empty = true;
+            for i in 0..DIMENSION.num_entries {
+                let rank = DIMENSION.state(metadata, i);
+                if rank == Dimension::uninit_state() || rank == DIMENSION.removed_state() {
+                    continue;
+                }
+                if range.contains(self.read(i).0.borrow()) {
+                    new_metadata |= DIMENSION.state_mask(i);
+                    num_removed += 1;
+                } else {
+                    empty = false;
+                }
+            }
+            if num_removed == 0 {
+                return RemoveResult::Fail;
+            }
+            if empty {
+                new_metadata = Dimension::retire(new_metadata);
+            }
+            match self
+                .metadata
+                .compare_exchange(metadata, new_metadata, Release, Relaxed)
+            {
+                Ok(_) => {
+                    *removed += num_removed;
+                    if empty {
+                        return RemoveResult::Retired;
+                    }
+                    return RemoveResult::Success;
+                }
+                Err(actual) => metadata = actual,
+            }
+        }
+    }
+
     /// Returns a value associated with the key.
+    #[cfg(test)]
     pub fn search<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search_entry(key).map(|(_, v)| v)
+    }
+
+    /// Returns the key-value pair associated with the key.
+    pub fn search_entry<Q>(&self, key: &Q) -> Option<(&K, &V)>
     where
         K: Borrow<Q>,
         Q: Ord + ?Sized,
     {
         let metadata = self.metadata.load(Acquire);
-        self.search_slot(key, metadata).map(|i| self.read(i).1)
+        self.search_slot(key, metadata).map(|i| self.read(i))
     }
 
     /// Returns the index and a pointer to the key-value pair that is smaller than the given key.
@@ -410,6 +543,22 @@ where
         Q: Ord + ?Sized,
     {
         let metadata = self.metadata.load(Acquire);
+        let index = self.min_greater_equal_index(metadata, key);
+        if index == DIMENSION.num_entries {
+            return (None, metadata);
+        }
+        (Some(self.read(index)), metadata)
+    }
+
+    /// Returns the index of the minimum entry among those that are not `Ordering::Less` than
+    /// the given key in the given metadata.
+    ///
+    /// It returns `DIMENSION.num_entries` if there is no such entry.
+    fn min_greater_equal_index<Q>(&self, metadata: usize, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
         let mut max_min_rank = 0;
         let mut min_max_index = DIMENSION.num_entries;
         let mut min_max_rank = DIMENSION.removed_state();
@@ -429,15 +578,12 @@ where
                         }
                     }
                     Ordering::Equal => {
-                        return (Some(self.read(i)), metadata);
+                        return i;
                     }
                 }
             }
         }
-        if min_max_rank != DIMENSION.removed_state() {
-            return (Some(self.read(min_max_index)), metadata);
-        }
-        (None, metadata)
+        min_max_index
     }
 
     /// Compares the given metadata value with the current one.
@@ -479,6 +625,35 @@ where
         (usize::MAX, ptr::null())
     }
 
+    /// Returns the index and a pointer to the corresponding entry of the next lower ranked entry.
+    pub fn prev(&self, index: usize, metadata: usize) -> (usize, *const (K, V)) {
+        let current_entry_rank = if index < DIMENSION.num_entries {
+            DIMENSION.state(metadata, index)
+        } else {
+            DIMENSION.removed_state()
+        };
+        let mut prev_rank = Dimension::uninit_state();
+        let mut prev_index = DIMENSION.num_entries;
+        for i in 0..DIMENSION.num_entries {
+            if i == index {
+                continue;
+            }
+            let rank = DIMENSION.state(metadata, i);
+            if rank == Dimension::uninit_state() || rank == DIMENSION.removed_state() {
+                continue;
+            }
+            debug_assert_ne!(rank, current_entry_rank);
+            if prev_rank < rank && rank < current_entry_rank {
+                prev_rank = rank;
+                prev_index = i;
+            }
+        }
+        if prev_rank != Dimension::uninit_state() {
+            return (prev_index, self.entry_array[prev_index].as_ptr());
+        }
+        (usize::MAX, ptr::null())
+    }
+
     /// Freezes the [`Leaf`] temporarily.
     ///
     /// A frozen [`Leaf`] cannot store more entries, and on-going insertion is cancelled.
@@ -494,6 +669,46 @@ where
             .is_ok()
     }
 
+    /// Moves the entries having keys equal to or greater than the given key to a new [`Leaf`].
+    ///
+    /// The new [`Leaf`] takes over the rest of the linked list, and the [`Leaf`] becomes the last
+    /// one in its linked list.
+    /// It must not be called while other threads are able to access the [`Leaf`].
+    pub fn split_off<Q>(&self, key: &Q) -> Leaf<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let high_key_leaf = Leaf::with_capacity(self.capacity);
+        let mut metadata = self.metadata.load(Acquire);
+        for i in 0..DIMENSION.num_entries {
+            let rank = DIMENSION.state(metadata, i);
+            if rank == Dimension::uninit_state() || rank == DIMENSION.removed_state() {
+                continue;
+            }
+            let (k, v) = self.read(i);
+            if k.borrow() >= key {
+                let result = high_key_leaf.insert(k.clone(), v.clone());
+                debug_assert!(matches!(result, InsertResult::Success));
+                metadata |= DIMENSION.state_mask(i);
+            }
+        }
+        self.metadata.store(metadata, Release);
+
+        let (next, tag) = self.link.swap((None, Tag::None), Relaxed);
+        self.link.swap((None, tag), Relaxed);
+        high_key_leaf.link.swap((next, Tag::None), Relaxed);
+        high_key_leaf
+    }
+
+    /// Replaces the next [`Leaf`] in the linked list.
+    ///
+    /// It must not be called while other threads are able to access the [`Leaf`].
+    pub fn relink(&self, next: Option<Arc<Leaf<K, V>>>) {
+        let tag = self.link.tag(Relaxed);
+        self.link.swap((next, tag), Relaxed);
+    }
+
     /// Freezes the [`Leaf`] and distribute entries to two new leaves.
     pub fn freeze_and_distribute(
         &self,
@@ -515,9 +730,9 @@ where
                 entry_ptr: ptr::null(),
             };
             for entry in scanner {
-                let result = if iterated < DIMENSION.num_entries / 2 {
+                let result = if iterated < self.capacity / 2 {
                     if low_key_leaf.is_none() {
-                        low_key_leaf.replace(Arc::new(Leaf::new()));
+                        low_key_leaf.replace(Arc::new(Leaf::with_capacity(self.capacity)));
                     }
                     iterated += 1;
                     low_key_leaf
@@ -526,7 +741,7 @@ where
                         .insert(entry.0.clone(), entry.1.clone())
                 } else {
                     if high_key_leaf.is_none() {
-                        high_key_leaf.replace(Arc::new(Leaf::new()));
+                        high_key_leaf.replace(Arc::new(Leaf::with_capacity(self.capacity)));
                     }
                     high_key_leaf
                         .as_ref()
@@ -573,12 +788,18 @@ where
     }
 
     /// Post-processing after reserving a free slot.
-    fn post_insert(&self, free_slot_index: usize, mut metadata: usize) -> InsertResult<K, V> {
+    fn post_insert(
+        &self,
+        free_slot_index: usize,
+        mut metadata: usize,
+        on_duplicate: OnDuplicate,
+    ) -> InsertResult<K, V> {
         let key_ref = self.read(free_slot_index).0;
         loop {
             let mut new_metadata = metadata;
             let mut max_min_rank = 0;
             let mut min_max_rank = DIMENSION.removed_state();
+            let mut replaced = None;
             for i in 0..DIMENSION.num_entries {
                 let rank = DIMENSION.state(metadata, i);
                 if rank == Dimension::uninit_state() || rank == DIMENSION.removed_state() {
@@ -598,8 +819,12 @@ where
                             new_metadata = DIMENSION.augment(new_metadata, i, rank + 1);
                         }
                         Ordering::Equal => {
-                            // Duplicate key.
-                            return self.rollback(free_slot_index);
+                            if !self.replaceable(i, on_duplicate) {
+                                // Duplicate key.
+                                return self.rollback(free_slot_index);
+                            }
+                            replaced.replace((i, rank));
+                            break;
                         }
                     }
                 } else if rank > min_max_rank {
@@ -607,8 +832,17 @@ where
                 }
             }
 
-            // Make the newly inserted value reachable.
-            let final_metadata = DIMENSION.augment(new_metadata, free_slot_index, max_min_rank + 1);
+            let final_metadata = if let Some((index, rank)) = replaced {
+                // The new entry takes over the rank of the old one that becomes unreachable.
+                let new_metadata = DIMENSION.augment(metadata, free_slot_index, rank);
+                DIMENSION.augment(new_metadata, index, DIMENSION.removed_state())
+            } else if let OnDuplicate::ReplaceIf(_) = on_duplicate {
+                // The entry to replace has been removed.
+                return self.rollback(free_slot_index);
+            } else {
+                // Make the newly inserted value reachable.
+                DIMENSION.augment(new_metadata, free_slot_index, max_min_rank + 1)
+            };
             if let Err(actual) =
                 self.metadata
                     .compare_exchange(metadata, final_metadata, AcqRel, Acquire)
@@ -624,6 +858,18 @@ where
         }
     }
 
+    /// Checks if the entry can be replaced according to the policy.
+    fn replaceable(&self, index: usize, on_duplicate: OnDuplicate) -> bool {
+        match on_duplicate {
+            OnDuplicate::Reject => false,
+            OnDuplicate::Replace => true,
+            OnDuplicate::ReplaceIf(addr) => {
+                let value_ptr: *const V = self.read(index).1;
+                value_ptr as usize == addr
+            }
+        }
+    }
+
     fn rollback(&self, index: usize) -> InsertResult<K, V> {
         let (key, value) = self.take(index);
         let result = self
@@ -748,6 +994,25 @@ where
         }
     }
 
+    /// Returns a [`Scanner`] pointing to the min-greater-equal entry if there is one.
+    pub fn min_greater_equal<Q>(leaf: &'l Leaf<K, V>, key: &Q) -> Option<Scanner<'l, K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let metadata = leaf.metadata.load(Acquire);
+        let index = leaf.min_greater_equal_index(metadata, key);
+        if index == DIMENSION.num_entries {
+            return None;
+        }
+        Some(Scanner {
+            leaf,
+            metadata,
+            entry_index: index,
+            entry_ptr: leaf.entry_array[index].as_ptr(),
+        })
+    }
+
     /// Returns the metadata that the [`Scanner`] is currently using.
     pub fn metadata(&self) -> usize {
         self.metadata
@@ -802,6 +1067,14 @@ where
         None
     }
 
+    /// Moves the [`Scanner`] to the previous entry, and returns a reference to it.
+    ///
+    /// A newly created [`Scanner`] moves to the maximum key entry.
+    pub fn prev(&mut self) -> Option<(&'l K, &'l V)> {
+        self.proceed_rev();
+        self.get()
+    }
+
     fn proceed(&mut self) {
         self.entry_ptr = ptr::null();
         if self.entry_index == usize::MAX {
@@ -811,6 +1084,16 @@ where
         self.entry_index = index;
         self.entry_ptr = ptr;
     }
+
+    fn proceed_rev(&mut self) {
+        self.entry_ptr = ptr::null();
+        if self.entry_index == usize::MAX {
+            return;
+        }
+        let (index, ptr) = self.leaf.prev(self.entry_index, self.metadata);
+        self.entry_index = index;
+        self.entry_ptr = ptr;
+    }
 }
 
 impl<'l, K, V> Iterator for Scanner<'l, K, V>
@@ -892,6 +1175,16 @@ mod test {
             InsertResult::Full(..)
         ));
 
+        let forward: Vec<(&String, &String)> = Scanner::new(&leaf).collect();
+        let mut scanner = Scanner::new(&leaf);
+        let mut backward = Vec::new();
+        while let Some(e) = scanner.prev() {
+            backward.push(e);
+        }
+        backward.reverse();
+        assert_eq!(forward, backward);
+        assert!(scanner.prev().is_none());
+
         let mut scanner = Scanner::new(&leaf);
         for i in 0..DIMENSION.num_entries {
             if let Some(e) = scanner.next() {
@@ -912,6 +1205,70 @@ mod test {
         ));
     }
 
+    #[test]
+    fn remove_range() {
+        let leaf: Leaf<usize, usize> = Leaf::new();
+        for i in 0..DIMENSION.num_entries {
+            assert!(matches!(leaf.insert(i, i), InsertResult::Success));
+        }
+        let mut removed = 0;
+        assert_eq!(
+            leaf.remove_range(&(2..5), &mut removed),
+            RemoveResult::Success
+        );
+        assert_eq!(removed, 3);
+        assert_eq!(leaf.remove_range(&(3..4), &mut removed), RemoveResult::Fail);
+        assert_eq!(removed, 3);
+        assert!(leaf.search_entry(&1).is_some());
+        assert!(leaf.search_entry(&2).is_none());
+        assert!(leaf.search_entry(&5).is_some());
+        assert_eq!(
+            leaf.remove_range(&(1..), &mut removed),
+            RemoveResult::Success
+        );
+        assert_eq!(
+            leaf.remove_range(&(..=0), &mut removed),
+            RemoveResult::Retired
+        );
+        assert_eq!(removed, DIMENSION.num_entries);
+        assert!(leaf.retired());
+    }
+
+    #[test]
+    fn capacity() {
+        let leaf: Arc<Leaf<usize, usize>> = Arc::new(Leaf::with_capacity(3));
+        for i in 0..3 {
+            assert!(matches!(leaf.insert(i, i), InsertResult::Success));
+        }
+        assert!(matches!(leaf.insert(3, 3), InsertResult::Full(3, 3)));
+
+        let mut low_key_leaf = None;
+        let mut high_key_leaf = None;
+        assert!(leaf.freeze_and_distribute(&mut low_key_leaf, &mut high_key_leaf));
+        let low_key_leaf = low_key_leaf.unwrap();
+        let high_key_leaf = high_key_leaf.unwrap();
+        assert_eq!(Scanner::new(&low_key_leaf).count(), 1);
+        assert_eq!(Scanner::new(&high_key_leaf).count(), 2);
+        assert!(matches!(high_key_leaf.insert(4, 4), InsertResult::Success));
+        assert!(matches!(
+            high_key_leaf.insert(5, 5),
+            InsertResult::Full(5, 5)
+        ));
+    }
+
+    #[test]
+    fn split_off() {
+        let leaf: Arc<Leaf<usize, usize>> = Arc::new(Leaf::with_capacity(8));
+        for i in 0..8 {
+            assert!(matches!(leaf.insert(i, i), InsertResult::Success));
+        }
+        let high_key_leaf = leaf.split_off(&5);
+        assert!(Scanner::new(&leaf).map(|(k, _)| *k).eq(0..5));
+        assert!(Scanner::new(&high_key_leaf).map(|(k, _)| *k).eq(5..8));
+        assert!(matches!(leaf.insert(5, 5), InsertResult::Full(5, 5)));
+        assert!(matches!(high_key_leaf.insert(8, 8), InsertResult::Success));
+    }
+
     #[test]
     fn special() {
         let leaf: Leaf<usize, usize> = Leaf::new();
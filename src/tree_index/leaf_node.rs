@@ -1,4 +1,7 @@
-use super::leaf::{InsertResult, RemoveResult, Scanner, DIMENSION};
+use super::leaf::{
+    is_above_range, is_below_range, InsertResult, OnDuplicate, RemoveResult, Scanner, DIMENSION,
+};
+use super::node::Config;
 use super::Leaf;
 
 use crate::ebr::{Arc, AtomicArc, Barrier, Ptr, Tag};
@@ -7,6 +10,7 @@ use crate::LinkedList;
 
 use std::borrow::Borrow;
 use std::cmp::Ordering::{Equal, Greater, Less};
+use std::ops::RangeBounds;
 use std::ptr::addr_of;
 use std::sync::atomic::AtomicPtr;
 use std::sync::atomic::Ordering::{self, AcqRel, Acquire, Relaxed, Release};
@@ -40,6 +44,9 @@ where
 
     /// `wait_queue` for `latch`.
     wait_queue: WaitQueue,
+
+    /// The capacity of [`Leaf`] instances and the fan-out of the [`LeafNode`].
+    config: Config,
 }
 
 impl<K, V> LeafNode<K, V>
@@ -48,13 +55,115 @@ where
     V: 'static + Clone + Send + Sync,
 {
     /// Creates a new empty [`LeafNode`].
+    #[cfg(test)]
     pub(crate) fn new() -> LeafNode<K, V> {
+        Self::with_config(Config::default())
+    }
+
+    /// Creates a new empty [`LeafNode`] with the given [`Config`].
+    pub(crate) fn with_config(config: Config) -> LeafNode<K, V> {
         LeafNode {
-            children: Leaf::new(),
+            children: Leaf::with_capacity(config.node_capacity()),
             unbounded_child: AtomicArc::null(),
             latch: AtomicArc::null(),
             wait_queue: WaitQueue::default(),
+            config,
+        }
+    }
+
+    /// Creates a new [`LeafNode`] from [`Leaf`] instances sorted in ascending key order.
+    ///
+    /// Each bounded [`Leaf`] is paired with the upper bound of its keys; the number of bounded
+    /// [`Leaf`] instances must not exceed the capacity of a [`LeafNode`].
+    pub(crate) fn from_leaves(
+        bounded_leaves: Vec<(K, Arc<Leaf<K, V>>)>,
+        unbounded_leaf: Arc<Leaf<K, V>>,
+        config: Config,
+    ) -> LeafNode<K, V> {
+        let children = Leaf::with_capacity(config.node_capacity());
+        for (bound, leaf) in bounded_leaves {
+            let result = children.insert(bound, AtomicArc::from(leaf));
+            debug_assert!(matches!(result, InsertResult::Success));
         }
+        LeafNode {
+            children,
+            unbounded_child: AtomicArc::from(unbounded_leaf),
+            latch: AtomicArc::null(),
+            wait_queue: WaitQueue::default(),
+            config,
+        }
+    }
+
+    /// Returns the [`Config`] of the [`LeafNode`].
+    pub(crate) fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Splits the [`LeafNode`] into two at the given key.
+    ///
+    /// Only the [`Leaf`] containing the key is split, and the other [`Leaf`] instances are
+    /// relinked to either of the new [`LeafNode`] instances. It must not be called while other
+    /// threads are able to access the [`LeafNode`].
+    pub(crate) fn split_off<Q>(
+        &self,
+        key: &Q,
+        barrier: &Barrier,
+    ) -> (LeafNode<K, V>, LeafNode<K, V>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        if self.unbounded_child.is_null(Relaxed) {
+            // A retired `LeafNode` is split into two retired ones for the parent to clean them up.
+            let (low_key_leaf_node, high_key_leaf_node) = (
+                LeafNode::with_config(self.config),
+                LeafNode::with_config(self.config),
+            );
+            low_key_leaf_node
+                .unbounded_child
+                .swap((None, RETIRED), Relaxed);
+            high_key_leaf_node
+                .unbounded_child
+                .swap((None, RETIRED), Relaxed);
+            return (low_key_leaf_node, high_key_leaf_node);
+        }
+        let unbounded_leaf = self.unbounded_child.get_arc(Relaxed, barrier).unwrap();
+
+        let mut low_key_leaves = Vec::new();
+        let mut high_key_leaves = Vec::new();
+        let mut low_key_unbounded_leaf = None;
+        for (bound, child) in Scanner::new(&self.children) {
+            let leaf = child.get_arc(Relaxed, barrier).unwrap();
+            if low_key_unbounded_leaf.is_some() {
+                high_key_leaves.push((bound.clone(), leaf));
+            } else if bound.borrow() < key {
+                low_key_leaves.push((bound.clone(), leaf));
+            } else {
+                high_key_leaves.push((bound.clone(), Arc::new(leaf.split_off(key))));
+                low_key_unbounded_leaf.replace(leaf);
+            }
+        }
+        let (low_key_unbounded_leaf, high_key_unbounded_leaf) =
+            if let Some(low_key_unbounded_leaf) = low_key_unbounded_leaf {
+                (low_key_unbounded_leaf, unbounded_leaf)
+            } else {
+                let high_key_unbounded_leaf = Arc::new(unbounded_leaf.split_off(key));
+                (unbounded_leaf, high_key_unbounded_leaf)
+            };
+        (
+            LeafNode::from_leaves(low_key_leaves, low_key_unbounded_leaf, self.config),
+            LeafNode::from_leaves(high_key_leaves, high_key_unbounded_leaf, self.config),
+        )
+    }
+
+    /// Returns the first or last [`Leaf`] in the [`LeafNode`].
+    pub(crate) fn edge_leaf<const LAST: bool>(&self, barrier: &Barrier) -> Option<Arc<Leaf<K, V>>> {
+        if !LAST {
+            if let Some((_, child)) = Scanner::new(&self.children).next() {
+                return child.get_arc(Relaxed, barrier);
+            }
+        }
+        self.unbounded_child.get_arc(Relaxed, barrier)
     }
 
     /// Returns `true` if the [`LeafNode`] has retired.
@@ -64,6 +173,19 @@ where
 
     /// Searches for an entry associated with the given key.
     pub(crate) fn search<'b, Q>(&self, key: &Q, barrier: &'b Barrier) -> Option<&'b V>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search_entry(key, barrier).map(|(_, v)| v)
+    }
+
+    /// Searches for an entry associated with the given key, and returns the key-value pair.
+    pub(crate) fn search_entry<'b, Q>(
+        &self,
+        key: &Q,
+        barrier: &'b Barrier,
+    ) -> Option<(&'b K, &'b V)>
     where
         K: 'b + Borrow<Q>,
         Q: Ord + ?Sized,
@@ -82,7 +204,7 @@ where
                         // Consequently, the reader may miss keys in the low key leaf.
                         //
                         // Resolution: metadata validation.
-                        return child.search(key);
+                        return child.search_entry(key);
                     }
                 }
 
@@ -97,7 +219,7 @@ where
                 if !self.children.validate(metadata) {
                     continue;
                 }
-                return unbounded.search(key);
+                return unbounded.search_entry(key);
             }
             return None;
         }
@@ -181,17 +303,209 @@ where
         None
     }
 
+    /// Returns a [`Scanner`] pointing to the entry with the minimum key among those keys equal to
+    /// or greater than the given key.
+    ///
+    /// It returns `None` if all the keys in the [`LeafNode`] are smaller than the given key.
+    pub(crate) fn min_greater_equal<'b, Q>(
+        &self,
+        key: &Q,
+        barrier: &'b Barrier,
+    ) -> Option<Scanner<'b, K, V>>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let (child, metadata) = self.children.min_greater_equal(key);
+            if let Some((_, child)) = child {
+                if let Some(child) = child.load(Acquire, barrier).as_ref() {
+                    if self.children.validate(metadata) {
+                        // Data race resolution - see `LeafNode::search`.
+                        if let Some(scanner) = Scanner::min_greater_equal(child, key) {
+                            return Some(scanner);
+                        }
+
+                        // All the keys in `child` are smaller than the given key.
+                        if let Some(mut scanner) = Scanner::min_greater_equal(&self.children, key) {
+                            if scanner.metadata() == metadata {
+                                if let Ok(result) = self.min_in_children(&mut scanner, key, barrier)
+                                {
+                                    return result;
+                                }
+                            }
+                        }
+                    }
+                }
+                // It is not a hot loop - see `LeafNode::search`.
+                continue;
+            }
+            let unbounded_ptr = self.unbounded_child.load(Acquire, barrier);
+            if let Some(unbounded) = unbounded_ptr.as_ref() {
+                if self.children.validate(metadata) {
+                    return Scanner::min_greater_equal(unbounded, key);
+                }
+                continue;
+            }
+            return None;
+        }
+    }
+
+    /// Returns a [`Scanner`] pointing to the entry with the minimum key among those keys equal to
+    /// or greater than the given key in the children that the given [`Scanner`] has not visited
+    /// yet in ascending order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the children of the [`LeafNode`] have been modified.
+    fn min_in_children<'b, Q>(
+        &self,
+        scanner: &mut Scanner<'_, K, AtomicArc<Leaf<K, V>>>,
+        key: &Q,
+        barrier: &'b Barrier,
+    ) -> Result<Option<Scanner<'b, K, V>>, ()>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        while let Some((_, child)) = scanner.next() {
+            if let Some(child) = child.load(Acquire, barrier).as_ref() {
+                if self.children.validate(scanner.metadata()) {
+                    if let Some(child_scanner) = Scanner::min_greater_equal(child, key) {
+                        return Ok(Some(child_scanner));
+                    }
+                    continue;
+                }
+            }
+            return Err(());
+        }
+        let unbounded_ptr = self.unbounded_child.load(Acquire, barrier);
+        if let Some(unbounded) = unbounded_ptr.as_ref() {
+            if self.children.validate(scanner.metadata()) {
+                return Ok(Scanner::min_greater_equal(unbounded, key));
+            }
+            return Err(());
+        }
+        Ok(None)
+    }
+
+    /// Returns a [`Scanner`] pointing to the maximum key entry.
+    pub(crate) fn max<'b>(&self, barrier: &'b Barrier) -> Option<Scanner<'b, K, V>> {
+        loop {
+            let mut scanner = Scanner::new(&self.children);
+            let unbounded_ptr = self.unbounded_child.load(Acquire, barrier);
+            if let Some(unbounded) = unbounded_ptr.as_ref() {
+                if self.children.validate(scanner.metadata()) {
+                    // Data race resolution - see `LeafNode::search`.
+                    let mut unbounded_scanner = Scanner::new(unbounded);
+                    if unbounded_scanner.prev().is_some() {
+                        return Some(unbounded_scanner);
+                    }
+                    scanner.prev();
+                    if let Ok(result) = self.max_in_children(&mut scanner, barrier) {
+                        return result;
+                    }
+                }
+                // It is not a hot loop - see `LeafNode::search`.
+                continue;
+            }
+            return None;
+        }
+    }
+
+    /// Returns a [`Scanner`] pointing to the entry with the maximum key among those keys smaller
+    /// than the given key.
+    ///
+    /// It returns `None` if all the keys in the [`LeafNode`] are equal to or greater than the
+    /// given key.
+    pub(crate) fn max_less<'b, Q>(&self, key: &Q, barrier: &'b Barrier) -> Option<Scanner<'b, K, V>>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let (child, metadata) = self.children.min_greater_equal(key);
+            let child_ptr = if let Some((_, child)) = child {
+                child.load(Acquire, barrier)
+            } else {
+                self.unbounded_child.load(Acquire, barrier)
+            };
+            if let Some(child) = child_ptr.as_ref() {
+                if self.children.validate(metadata) {
+                    // Data race resolution - see `LeafNode::search`.
+                    if let Some(scanner) = Scanner::max_less(child, key) {
+                        return Some(scanner);
+                    }
+
+                    // All the keys in `child` are equal to or greater than the given key.
+                    let mut scanner = Scanner::max_less(&self.children, key)?;
+                    if let Ok(result) = self.max_in_children(&mut scanner, barrier) {
+                        return result;
+                    }
+                }
+            } else if child.is_none() {
+                return None;
+            }
+            // It is not a hot loop - see `LeafNode::search`.
+        }
+    }
+
+    /// Returns a [`Scanner`] pointing to the maximum key entry in the children that the given
+    /// [`Scanner`] is pointing to or has not visited yet in descending order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the children of the [`LeafNode`] have been modified.
+    fn max_in_children<'b>(
+        &self,
+        scanner: &mut Scanner<'_, K, AtomicArc<Leaf<K, V>>>,
+        barrier: &'b Barrier,
+    ) -> Result<Option<Scanner<'b, K, V>>, ()> {
+        let mut child_entry = scanner.get();
+        while let Some((_, child)) = child_entry {
+            if let Some(child) = child.load(Acquire, barrier).as_ref() {
+                if self.children.validate(scanner.metadata()) {
+                    let mut child_scanner = Scanner::new(child);
+                    if child_scanner.prev().is_some() {
+                        return Ok(Some(child_scanner));
+                    }
+                    child_entry = scanner.prev();
+                    continue;
+                }
+            }
+            return Err(());
+        }
+        Ok(None)
+    }
+
     /// Inserts a key-value pair.
     ///
     /// # Errors
     ///
     /// Returns an error if a retry is required.
+    #[cfg(test)]
     pub(crate) fn insert(
         &self,
         key: K,
         value: V,
         async_wait: Option<*mut AsyncWait>,
         barrier: &Barrier,
+    ) -> Result<InsertResult<K, V>, (K, V)> {
+        self.insert_with(key, value, OnDuplicate::Reject, async_wait, barrier)
+    }
+
+    /// Inserts a key-value pair, or replaces the existing entry according to the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a retry is required.
+    pub(crate) fn insert_with(
+        &self,
+        key: K,
+        value: V,
+        on_duplicate: OnDuplicate,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
     ) -> Result<InsertResult<K, V>, (K, V)> {
         loop {
             let (child, metadata) = self.children.min_greater_equal(&key);
@@ -200,7 +514,7 @@ where
                 if let Some(child_ref) = child_ptr.as_ref() {
                     if self.children.validate(metadata) {
                         // Data race resolution - see `LeafNode::search`.
-                        match child_ref.insert(key, value) {
+                        match child_ref.insert_with(key, value, on_duplicate) {
                             InsertResult::Success => return Ok(InsertResult::Success),
                             InsertResult::Duplicate(k, v) => {
                                 return Ok(InsertResult::Duplicate(k, v));
@@ -233,7 +547,10 @@ where
             if unbounded_ptr.is_null() {
                 match self.unbounded_child.compare_exchange(
                     Ptr::null(),
-                    (Some(Arc::new(Leaf::new())), Tag::None),
+                    (
+                        Some(Arc::new(Leaf::with_capacity(self.config.leaf_capacity))),
+                        Tag::None,
+                    ),
                     AcqRel,
                     Acquire,
                     barrier,
@@ -251,7 +568,7 @@ where
                 if !self.children.validate(metadata) {
                     continue;
                 }
-                match unbounded.insert(key, value) {
+                match unbounded.insert_with(key, value, on_duplicate) {
                     InsertResult::Success => return Ok(InsertResult::Success),
                     InsertResult::Duplicate(k, v) => {
                         return Ok(InsertResult::Duplicate(k, v));
@@ -336,6 +653,94 @@ where
         }
     }
 
+    /// Removes all the entries in the given range.
+    ///
+    /// The number of removed entries is added to `removed`, and retired [`Leaf`] instances are
+    /// unlinked at once after the whole range has been processed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a retry is required.
+    pub(crate) fn remove_range<Q, R>(
+        &self,
+        range: &R,
+        removed: &mut usize,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<RemoveResult, ()>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut retired = false;
+        let mut frozen = false;
+        loop {
+            let mut retry = false;
+            let mut prev_bound = None;
+            let scanner = Scanner::new(&self.children);
+            let metadata = scanner.metadata();
+            for (bound, child) in scanner {
+                if matches!(prev_bound, Some(prev_bound) if is_above_range(range, prev_bound)) {
+                    break;
+                }
+                prev_bound.replace(bound);
+                if is_below_range(range, bound) {
+                    continue;
+                }
+                if let Some(child) = child.load(Acquire, barrier).as_ref() {
+                    if self.children.validate(metadata) {
+                        // Data race resolution - see `LeafNode::search`.
+                        match child.remove_range(range, removed) {
+                            RemoveResult::Retired => retired = true,
+                            RemoveResult::Frozen => frozen = true,
+                            _ => (),
+                        }
+                        if frozen {
+                            break;
+                        }
+                        continue;
+                    }
+                }
+                // It is not a hot loop - see `LeafNode::search`.
+                retry = true;
+                break;
+            }
+            if retry {
+                continue;
+            }
+            if !frozen
+                && !matches!(prev_bound, Some(prev_bound) if is_above_range(range, prev_bound))
+            {
+                let unbounded_ptr = self.unbounded_child.load(Acquire, barrier);
+                if let Some(unbounded) = unbounded_ptr.as_ref() {
+                    if !self.children.validate(metadata) {
+                        // Data race resolution - see `LeafNode::search`.
+                        continue;
+                    }
+                    match unbounded.remove_range(range, removed) {
+                        RemoveResult::Retired => retired = true,
+                        RemoveResult::Frozen => frozen = true,
+                        _ => (),
+                    }
+                }
+            }
+            break;
+        }
+
+        let result = if retired {
+            self.coalesce::<K>(barrier)
+        } else {
+            RemoveResult::Success
+        };
+        if frozen {
+            // When a `Leaf` is frozen, its entries may be being copied to new `Leaves`.
+            self.wait(async_wait, barrier);
+            return Err(());
+        }
+        Ok(result)
+    }
+
     /// Splits a full leaf.
     ///
     /// # Errors
@@ -414,9 +819,13 @@ where
             }
         } else {
             // No valid keys in the full leaf.
-            new_leaves
-                .low_key_leaf
-                .swap((Some(Arc::new(Leaf::new())), Tag::None), Relaxed);
+            new_leaves.low_key_leaf.swap(
+                (
+                    Some(Arc::new(Leaf::with_capacity(self.config.leaf_capacity))),
+                    Tag::None,
+                ),
+                Relaxed,
+            );
         }
 
         // When a new leaf is added to the linked list, the leaf is marked to let `Scanners`
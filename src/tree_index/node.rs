@@ -1,13 +1,62 @@
 use super::internal_node::{self, InternalNode};
-use super::leaf::{InsertResult, RemoveResult, Scanner};
+use super::leaf::{InsertResult, Leaf, OnDuplicate, RemoveResult, Scanner, DIMENSION};
 use super::leaf_node::{self, LeafNode};
 
 use crate::ebr::{Arc, AtomicArc, Barrier, Tag};
 use crate::wait_queue::AsyncWait;
+use crate::LinkedList;
 
 use std::borrow::Borrow;
+use std::ops::RangeBounds;
 use std::sync::atomic::Ordering::{self, Acquire, Relaxed, Release};
 
+/// [`Config`] determines the capacity of [`Leaf`] instances and the fan-out of nodes in a tree.
+///
+/// It can only lower the limits of [`DIMENSION`]; a [`Leaf`] always has room for
+/// `DIMENSION.num_entries` entries and the same metadata encoding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Config {
+    /// The maximum number of key-value pairs that a [`Leaf`] can store.
+    pub leaf_capacity: usize,
+
+    /// The maximum number of children that a node can point to, including the unbounded one.
+    pub fan_out: usize,
+}
+
+impl Config {
+    /// Creates a new [`Config`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if either of the values is out of the range that the metadata of a [`Leaf`] can
+    /// represent.
+    pub(crate) fn new(leaf_capacity: usize, fan_out: usize) -> Config {
+        assert!(
+            (2..=DIMENSION.num_entries).contains(&leaf_capacity),
+            "leaf capacity out of range"
+        );
+        assert!(
+            (3..=DIMENSION.num_entries + 1).contains(&fan_out),
+            "fan-out out of range"
+        );
+        Config {
+            leaf_capacity,
+            fan_out,
+        }
+    }
+
+    /// Returns the maximum number of bounded children of a node.
+    pub(crate) fn node_capacity(self) -> usize {
+        self.fan_out - 1
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new(DIMENSION.num_entries, DIMENSION.num_entries + 1)
+    }
+}
+
 /// [`Type`] indicates the type of a [`Node`].
 pub enum Type<K, V>
 where
@@ -18,6 +67,10 @@ where
     Leaf(LeafNode<K, V>),
 }
 
+/// [`Subtree`] is a [`Node`], and the upper bound of its keys along with its new sibling if the
+/// [`Node`] had to be split.
+type Subtree<K, V> = (Arc<Node<K, V>>, Option<(K, Arc<Node<K, V>>)>);
+
 /// [`Node`] is either [`Type::Internal`] or [`Type::Leaf`].
 pub struct Node<K, V>
 where
@@ -32,18 +85,321 @@ where
     K: 'static + Clone + Ord + Send + Sync,
     V: 'static + Clone + Send + Sync,
 {
-    /// Creates a new [`InternalNode`].
-    pub(crate) fn new_internal_node() -> Node<K, V> {
+    /// Creates a new [`InternalNode`] with the given [`Config`].
+    pub(crate) fn new_internal_node_with_config(config: Config) -> Node<K, V> {
         Node {
-            node: Type::Internal(InternalNode::new()),
+            node: Type::Internal(InternalNode::with_config(config)),
         }
     }
 
     /// Creates a new [`LeafNode`].
+    #[cfg(test)]
     pub(crate) fn new_leaf_node() -> Node<K, V> {
+        Self::new_leaf_node_with_config(Config::default())
+    }
+
+    /// Creates a new [`LeafNode`] with the given [`Config`].
+    pub(crate) fn new_leaf_node_with_config(config: Config) -> Node<K, V> {
         Node {
-            node: Type::Leaf(LeafNode::new()),
+            node: Type::Leaf(LeafNode::with_config(config)),
+        }
+    }
+
+    /// Returns the [`Config`] of the node.
+    pub(crate) fn config(&self) -> Config {
+        match &self.node {
+            Type::Internal(internal_node) => internal_node.config(),
+            Type::Leaf(leaf_node) => leaf_node.config(),
+        }
+    }
+
+    /// Builds a tree of fully packed nodes bottom-up from key-value pairs sorted in ascending key
+    /// order, and returns the root.
+    ///
+    /// Key-value pairs that are not in strictly ascending key order are pushed to `unsorted`.
+    pub(crate) fn from_sorted_iter<I: Iterator<Item = (K, V)>>(
+        iter: I,
+        config: Config,
+        unsorted: &mut Vec<(K, V)>,
+    ) -> Option<Arc<Node<K, V>>> {
+        let barrier = Barrier::new();
+        let mut leaves: Vec<Arc<Leaf<K, V>>> = Vec::new();
+        for (key, value) in iter {
+            if matches!(
+                leaves.last().and_then(|leaf| leaf.max()),
+                Some((max_key, _)) if *max_key >= key
+            ) {
+                unsorted.push((key, value));
+                continue;
+            }
+            let (key, value) = if let Some(leaf) = leaves.last() {
+                match leaf.insert(key, value) {
+                    InsertResult::Success => continue,
+                    InsertResult::Full(key, value) => (key, value),
+                    _ => unreachable!(),
+                }
+            } else {
+                (key, value)
+            };
+            let leaf = Arc::new(Leaf::with_capacity(config.leaf_capacity));
+            let result = leaf.insert(key, value);
+            debug_assert!(matches!(result, InsertResult::Success));
+            if let Some(prev_leaf) = leaves.last() {
+                let result = prev_leaf.push_back(leaf.clone(), false, Release, &barrier);
+                debug_assert!(result.is_ok());
+            }
+            leaves.push(leaf);
+        }
+
+        let fan_out = config.fan_out;
+        let mut nodes = Vec::with_capacity(leaves.len().div_ceil(fan_out));
+        let mut leaves = leaves.into_iter().peekable();
+        while leaves.peek().is_some() {
+            let mut group: Vec<(K, Arc<Leaf<K, V>>)> = leaves
+                .by_ref()
+                .take(fan_out)
+                .map(|leaf| (leaf.max().unwrap().0.clone(), leaf))
+                .collect();
+            let (max_key, unbounded_leaf) = group.pop().unwrap();
+            let node = Node {
+                node: Type::Leaf(LeafNode::from_leaves(group, unbounded_leaf, config)),
+            };
+            nodes.push((max_key, Arc::new(node)));
+        }
+        while nodes.len() > 1 {
+            let mut parents = Vec::with_capacity(nodes.len().div_ceil(fan_out));
+            let mut children = nodes.into_iter().peekable();
+            while children.peek().is_some() {
+                let mut group: Vec<(K, Arc<Node<K, V>>)> =
+                    children.by_ref().take(fan_out).collect();
+                let (max_key, unbounded_child) = group.pop().unwrap();
+                let node = Node {
+                    node: Type::Internal(InternalNode::from_children(
+                        group,
+                        unbounded_child,
+                        config,
+                    )),
+                };
+                parents.push((max_key, Arc::new(node)));
+            }
+            nodes = parents;
+        }
+        nodes.pop().map(|(_, root)| root)
+    }
+
+    /// Splits the node into two at the given key.
+    ///
+    /// All the keys in the first node are smaller than the given key, and those in the second
+    /// node are equal to or greater than the key. It must not be called while other threads are
+    /// able to access the node.
+    pub(crate) fn split_off<Q>(&self, key: &Q, barrier: &Barrier) -> (Node<K, V>, Node<K, V>)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match &self.node {
+            Type::Internal(internal_node) => {
+                let (low_key_node, high_key_node) = internal_node.split_off(key, barrier);
+                (
+                    Node {
+                        node: Type::Internal(low_key_node),
+                    },
+                    Node {
+                        node: Type::Internal(high_key_node),
+                    },
+                )
+            }
+            Type::Leaf(leaf_node) => {
+                let (low_key_node, high_key_node) = leaf_node.split_off(key, barrier);
+                (
+                    Node {
+                        node: Type::Leaf(low_key_node),
+                    },
+                    Node {
+                        node: Type::Leaf(high_key_node),
+                    },
+                )
+            }
+        }
+    }
+
+    /// Appends the tree of `high_key_root` to the tree of `low_key_root`, and returns the new
+    /// root.
+    ///
+    /// All the keys in the tree of `low_key_root` must be equal to or smaller than `boundary_key`,
+    /// and those in the tree of `high_key_root` must be greater than it. Only the nodes on the
+    /// edges where the two trees meet are rebuilt. It must not be called while other threads are
+    /// able to access the trees.
+    pub(crate) fn append(
+        low_key_root: Arc<Node<K, V>>,
+        high_key_root: Arc<Node<K, V>>,
+        boundary_key: K,
+        barrier: &Barrier,
+    ) -> Arc<Node<K, V>> {
+        if let (Some(last_leaf), Some(first_leaf)) = (
+            low_key_root.edge_leaf::<true>(barrier),
+            high_key_root.edge_leaf::<false>(barrier),
+        ) {
+            last_leaf.relink(Some(first_leaf));
+        }
+
+        let config = low_key_root.config();
+        let low_key_depth = low_key_root.depth(1, barrier);
+        let high_key_depth = high_key_root.depth(1, barrier);
+        let (root, sibling) = if low_key_depth >= high_key_depth {
+            Self::attach_subtree::<true>(
+                &low_key_root,
+                low_key_depth,
+                high_key_root,
+                high_key_depth,
+                boundary_key,
+                barrier,
+            )
+        } else {
+            Self::attach_subtree::<false>(
+                &high_key_root,
+                high_key_depth,
+                low_key_root,
+                low_key_depth,
+                boundary_key,
+                barrier,
+            )
+        };
+        if let Some((bound, sibling)) = sibling {
+            Arc::new(Node {
+                node: Type::Internal(InternalNode::from_children(
+                    vec![(bound, root)],
+                    sibling,
+                    config,
+                )),
+            })
+        } else {
+            root
+        }
+    }
+
+    /// Replaces the root with its only child while the root has no bounded children.
+    pub(crate) fn shrink_root(mut root: Arc<Node<K, V>>, barrier: &Barrier) -> Arc<Node<K, V>> {
+        while let Type::Internal(internal_node) = &root.node {
+            match internal_node.edge_child::<true>(barrier) {
+                Some(unbounded_child) if internal_node.bounded_children(barrier).is_empty() => {
+                    root = unbounded_child;
+                }
+                _ => break,
+            }
+        }
+        root
+    }
+
+    /// Returns the first or last [`Leaf`] in the tree.
+    pub(crate) fn edge_leaf<const LAST: bool>(&self, barrier: &Barrier) -> Option<Arc<Leaf<K, V>>> {
+        match &self.node {
+            Type::Internal(internal_node) => internal_node
+                .edge_child::<LAST>(barrier)?
+                .edge_leaf::<LAST>(barrier),
+            Type::Leaf(leaf_node) => leaf_node.edge_leaf::<LAST>(barrier),
+        }
+    }
+
+    /// Attaches `subtree` to the last edge of the tree if `BACK`, or to the first edge otherwise.
+    ///
+    /// It returns the new node, and the upper bound of the keys in the new node along with its new
+    /// sibling node if the children did not fit in one node.
+    fn attach_subtree<const BACK: bool>(
+        node: &Arc<Node<K, V>>,
+        depth: usize,
+        subtree: Arc<Node<K, V>>,
+        subtree_depth: usize,
+        boundary_key: K,
+        barrier: &Barrier,
+    ) -> Subtree<K, V> {
+        if depth == subtree_depth {
+            return if BACK {
+                (node.clone(), Some((boundary_key, subtree)))
+            } else {
+                (subtree, Some((boundary_key, node.clone())))
+            };
+        }
+        let internal_node = match &node.node {
+            Type::Internal(internal_node) => internal_node,
+            Type::Leaf(_) => unreachable!(),
+        };
+        let mut bounded_children = internal_node.bounded_children(barrier);
+        let mut unbounded_child = internal_node.edge_child::<true>(barrier).unwrap();
+        if BACK || bounded_children.is_empty() {
+            let (child, sibling) = Self::attach_subtree::<BACK>(
+                &unbounded_child,
+                depth - 1,
+                subtree,
+                subtree_depth,
+                boundary_key,
+                barrier,
+            );
+            if let Some((bound, sibling)) = sibling {
+                bounded_children.push((bound, child));
+                unbounded_child = sibling;
+            } else {
+                unbounded_child = child;
+            }
+        } else {
+            let (first_bound, first_child) = bounded_children.remove(0);
+            let (child, sibling) = Self::attach_subtree::<false>(
+                &first_child,
+                depth - 1,
+                subtree,
+                subtree_depth,
+                boundary_key,
+                barrier,
+            );
+            if let Some((bound, sibling)) = sibling {
+                bounded_children.insert(0, (first_bound, sibling));
+                bounded_children.insert(0, (bound, child));
+            } else {
+                bounded_children.insert(0, (first_bound, child));
+            }
         }
+        Self::build_internal_node(bounded_children, unbounded_child, internal_node.config())
+    }
+
+    /// Creates an [`InternalNode`], or two of them if the children do not fit in one.
+    ///
+    /// It returns the new node, and the upper bound of the keys in the new node along with its
+    /// sibling node if the children were split.
+    fn build_internal_node(
+        mut bounded_children: Vec<(K, Arc<Node<K, V>>)>,
+        unbounded_child: Arc<Node<K, V>>,
+        config: Config,
+    ) -> Subtree<K, V> {
+        if bounded_children.len() <= config.node_capacity() {
+            let node = Node {
+                node: Type::Internal(InternalNode::from_children(
+                    bounded_children,
+                    unbounded_child,
+                    config,
+                )),
+            };
+            return (Arc::new(node), None);
+        }
+        let high_key_children = bounded_children.split_off(bounded_children.len() / 2 + 1);
+        let (bound, low_key_unbounded_child) = bounded_children.pop().unwrap();
+        let low_key_node = Node {
+            node: Type::Internal(InternalNode::from_children(
+                bounded_children,
+                low_key_unbounded_child,
+                config,
+            )),
+        };
+        let high_key_node = Node {
+            node: Type::Internal(InternalNode::from_children(
+                high_key_children,
+                unbounded_child,
+                config,
+            )),
+        };
+        (
+            Arc::new(low_key_node),
+            Some((bound, Arc::new(high_key_node))),
+        )
     }
 
     /// Returns a reference to the node internal.
@@ -79,6 +435,22 @@ where
         }
     }
 
+    /// Searches for an entry associated with the given key, and returns the key-value pair.
+    pub(crate) fn search_entry<'b, Q>(
+        &self,
+        key: &Q,
+        barrier: &'b Barrier,
+    ) -> Option<(&'b K, &'b V)>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match &self.node {
+            Type::Internal(internal_node) => internal_node.search_entry(key, barrier),
+            Type::Leaf(leaf_node) => leaf_node.search_entry(key, barrier),
+        }
+    }
+
     /// Returns the minimum key-value pair.
     ///
     /// This method is not linearizable.
@@ -108,17 +480,66 @@ where
         }
     }
 
-    /// Inserts a key-value pair.
-    pub(crate) fn insert(
+    /// Returns a [`Scanner`] pointing to the entry with the minimum key among those keys equal to
+    /// or greater than the given key.
+    ///
+    /// This method is not linearizable.
+    pub(crate) fn min_greater_equal<'b, Q>(
+        &self,
+        key: &Q,
+        barrier: &'b Barrier,
+    ) -> Option<Scanner<'b, K, V>>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match &self.node {
+            Type::Internal(internal_node) => internal_node.min_greater_equal(key, barrier),
+            Type::Leaf(leaf_node) => leaf_node.min_greater_equal(key, barrier),
+        }
+    }
+
+    /// Returns a [`Scanner`] pointing to the maximum key entry.
+    ///
+    /// This method is not linearizable.
+    pub(crate) fn max<'b>(&self, barrier: &'b Barrier) -> Option<Scanner<'b, K, V>> {
+        match &self.node {
+            Type::Internal(internal_node) => internal_node.max(barrier),
+            Type::Leaf(leaf_node) => leaf_node.max(barrier),
+        }
+    }
+
+    /// Returns a [`Scanner`] pointing to the entry with the maximum key among those keys smaller
+    /// than the given key.
+    ///
+    /// This method is not linearizable.
+    pub(crate) fn max_less<'b, Q>(&self, key: &Q, barrier: &'b Barrier) -> Option<Scanner<'b, K, V>>
+    where
+        K: 'b + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        match &self.node {
+            Type::Internal(internal_node) => internal_node.max_less(key, barrier),
+            Type::Leaf(leaf_node) => leaf_node.max_less(key, barrier),
+        }
+    }
+
+    /// Inserts a key-value pair, or replaces the existing entry according to the policy.
+    pub(crate) fn insert_with(
         &self,
         key: K,
         value: V,
+        on_duplicate: OnDuplicate,
         async_wait: Option<*mut AsyncWait>,
         barrier: &Barrier,
     ) -> Result<InsertResult<K, V>, (K, V)> {
         match &self.node {
-            Type::Internal(internal_node) => internal_node.insert(key, value, async_wait, barrier),
-            Type::Leaf(leaf_node) => leaf_node.insert(key, value, async_wait, barrier),
+            Type::Internal(internal_node) => {
+                internal_node.insert_with(key, value, on_duplicate, async_wait, barrier)
+            }
+            Type::Leaf(leaf_node) => {
+                leaf_node.insert_with(key, value, on_duplicate, async_wait, barrier)
+            }
         }
     }
 
@@ -144,6 +565,27 @@ where
         }
     }
 
+    /// Removes all the entries in the given range.
+    pub(crate) fn remove_range<Q, R>(
+        &self,
+        range: &R,
+        removed: &mut usize,
+        async_wait: Option<*mut AsyncWait>,
+        barrier: &Barrier,
+    ) -> Result<RemoveResult, ()>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        match &self.node {
+            Type::Internal(internal_node) => {
+                internal_node.remove_range(range, removed, async_wait, barrier)
+            }
+            Type::Leaf(leaf_node) => leaf_node.remove_range(range, removed, async_wait, barrier),
+        }
+    }
+
     /// Splits the current root node.
     pub(crate) fn split_root<const ASYNC: bool>(
         key: K,
@@ -153,7 +595,8 @@ where
     ) -> (K, V) {
         // The fact that the `TreeIndex` calls this function means that the root is full and
         // locked.
-        let mut new_root: Node<K, V> = Node::new_internal_node();
+        let config = root.load(Relaxed, barrier).as_ref().unwrap().config();
+        let mut new_root: Node<K, V> = Node::new_internal_node_with_config(config);
         if let Type::Internal(internal_node) = &mut new_root.node {
             internal_node.unbounded_child = root.clone(Relaxed, barrier);
             let result = internal_node.split_node(
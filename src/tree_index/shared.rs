@@ -0,0 +1,1199 @@
+//! [`SharedTreeIndex`] is a [`TreeIndex`] variant for keys and values that are not cloned.
+
+use super::{Range, TreeIndex, Visitor};
+
+use crate::ebr::{Arc, Barrier};
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::iter::{FusedIterator, Rev};
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
+use std::ptr::addr_of;
+
+/// Scalable concurrent B+ tree for keys and values that are shared instead of cloned.
+///
+/// [`SharedTreeIndex`] is a [`TreeIndex`] that stores each key and value in an [`Arc`], so that
+/// they are shared instead of cloned when key-value pairs are copied to new leaves, or keys are
+/// used as the separators in the nodes of the tree. It requires neither keys nor values to
+/// implement [`Clone`], e.g., `Box<dyn Trait>`, at the cost of two heap allocations per key-value
+/// pair.
+pub struct SharedTreeIndex<K, V>
+where
+    K: 'static + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    tree: TreeIndex<Key<K>, Arc<V>>,
+}
+
+impl<K, V> SharedTreeIndex<K, V>
+where
+    K: 'static + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Creates an empty [`SharedTreeIndex`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<dyn Fn() -> u32 + Send + Sync>> =
+    ///     SharedTreeIndex::new();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new() -> SharedTreeIndex<K, V> {
+        SharedTreeIndex {
+            tree: TreeIndex::new(),
+        }
+    }
+
+    /// Creates an empty [`SharedTreeIndex`] with the given leaf capacity and node fan-out.
+    ///
+    /// See [`TreeIndex::with_config`] for the supported ranges of the parameters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_capacity` or `fan_out` is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::with_config(4, 8);
+    /// assert_eq!(treeindex.leaf_capacity(), 4);
+    /// assert_eq!(treeindex.fan_out(), 8);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_config(leaf_capacity: usize, fan_out: usize) -> SharedTreeIndex<K, V> {
+        SharedTreeIndex {
+            tree: TreeIndex::with_config(leaf_capacity, fan_out),
+        }
+    }
+
+    /// Returns the maximum number of key-value pairs that a leaf can store.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    /// assert_eq!(treeindex.leaf_capacity(), 14);
+    /// ```
+    #[inline]
+    pub fn leaf_capacity(&self) -> usize {
+        self.tree.leaf_capacity()
+    }
+
+    /// Returns the maximum number of leaves or child nodes that a node can point to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    /// assert_eq!(treeindex.fan_out(), 15);
+    /// ```
+    #[inline]
+    pub fn fan_out(&self) -> usize {
+        self.tree.fan_out()
+    }
+
+    /// Builds a [`SharedTreeIndex`] from key-value pairs sorted in ascending key order.
+    ///
+    /// See [`TreeIndex::from_sorted_iter`] for how the key-value pairs are packed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> =
+    ///     SharedTreeIndex::from_sorted_iter((0..64).map(|k| (k, k.to_string())));
+    /// assert_eq!(treeindex.len(), 64);
+    /// ```
+    #[inline]
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> SharedTreeIndex<K, V> {
+        SharedTreeIndex {
+            tree: TreeIndex::from_sorted_iter(iter.into_iter().map(share_entry)),
+        }
+    }
+
+    /// Builds a [`SharedTreeIndex`] with the given leaf capacity and node fan-out from key-value
+    /// pairs sorted in ascending key order.
+    ///
+    /// See [`TreeIndex::from_sorted_iter_with_config`] for how the key-value pairs are packed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_capacity` or `fan_out` is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::from_sorted_iter_with_config(
+    ///     (0..64).map(|k| (k, k.to_string())),
+    ///     4,
+    ///     8,
+    /// );
+    /// assert_eq!(treeindex.len(), 64);
+    /// assert_eq!(treeindex.leaf_capacity(), 4);
+    /// ```
+    #[inline]
+    pub fn from_sorted_iter_with_config<I: IntoIterator<Item = (K, V)>>(
+        iter: I,
+        leaf_capacity: usize,
+        fan_out: usize,
+    ) -> SharedTreeIndex<K, V> {
+        SharedTreeIndex {
+            tree: TreeIndex::from_sorted_iter_with_config(
+                iter.into_iter().map(share_entry),
+                leaf_capacity,
+                fan_out,
+            ),
+        }
+    }
+
+    /// Inserts a key-value pair.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied key-value pair if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, Box::new(10)).is_ok());
+    /// assert_eq!(*treeindex.insert(1, Box::new(11)).err().unwrap().1, 11);
+    /// assert_eq!(treeindex.read(&1, |_, v| **v).unwrap(), 10);
+    /// ```
+    #[inline]
+    pub fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let (key, value) = share_entry((key, value));
+        self.tree.insert(key, value).map_err(unshare_entry)
+    }
+
+    /// Inserts a key-value pair.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied key-value pair if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    /// let future_insert = treeindex.insert_async(1, Box::new(10));
+    /// ```
+    #[inline]
+    pub async fn insert_async(&self, key: K, value: V) -> Result<(), (K, V)> {
+        let (key, value) = share_entry((key, value));
+        self.tree
+            .insert_async(key, value)
+            .await
+            .map_err(unshare_entry)
+    }
+
+    /// Inserts a key-value pair, or replaces the existing value with the supplied one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    ///
+    /// treeindex.upsert(1, Box::new(10));
+    /// treeindex.upsert(1, Box::new(11));
+    /// assert_eq!(treeindex.read(&1, |_, v| **v).unwrap(), 11);
+    /// ```
+    #[inline]
+    pub fn upsert(&self, key: K, value: V) {
+        let (key, value) = share_entry((key, value));
+        self.tree.upsert(key, value);
+    }
+
+    /// Inserts a key-value pair, or replaces the existing value with the supplied one.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    /// let future_upsert = treeindex.upsert_async(1, Box::new(10));
+    /// ```
+    #[inline]
+    pub async fn upsert_async(&self, key: K, value: V) {
+        let (key, value) = share_entry((key, value));
+        self.tree.upsert_async(key, value).await;
+    }
+
+    /// Replaces the value associated with the key with a new one built by the updater.
+    ///
+    /// Returns `false` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, Box::new(10)).is_ok());
+    /// assert!(treeindex.update(&1, |v| Box::new(**v + 1)));
+    /// assert_eq!(treeindex.read(&1, |_, v| **v).unwrap(), 11);
+    /// ```
+    #[inline]
+    pub fn update<Q, F: FnMut(&V) -> V>(&self, key_ref: &Q, mut updater: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree
+            .update(Query::new(key_ref), |v| Arc::new(updater(v)))
+    }
+
+    /// Replaces the value associated with the key with a new one built by the updater.
+    ///
+    /// Returns `false` if the key does not exist. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    /// let future_update = treeindex.update_async(&1, |v| Box::new(**v + 1));
+    /// ```
+    #[inline]
+    pub async fn update_async<Q, F: FnMut(&V) -> V>(&self, key_ref: &Q, mut updater: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree
+            .update_async(Query::new(key_ref), |v| Arc::new(updater(v)))
+            .await
+    }
+
+    /// Removes a key-value pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    ///
+    /// assert!(!treeindex.remove(&1));
+    /// assert!(treeindex.insert(1, Box::new(10)).is_ok());
+    /// assert!(treeindex.remove(&1));
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&self, key_ref: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree.remove(Query::new(key_ref))
+    }
+
+    /// Removes a key-value pair.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    /// let future_remove = treeindex.remove_async(&1);
+    /// ```
+    #[inline]
+    pub async fn remove_async<Q>(&self, key_ref: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree.remove_async(Query::new(key_ref)).await
+    }
+
+    /// Removes a key-value pair if the given condition is met.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, Box::new(10)).is_ok());
+    /// assert!(!treeindex.remove_if(&1, |v| **v == 0));
+    /// assert!(treeindex.remove_if(&1, |v| **v == 10));
+    /// ```
+    #[inline]
+    pub fn remove_if<Q, F: FnMut(&V) -> bool>(&self, key_ref: &Q, mut condition: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree.remove_if(Query::new(key_ref), |v| condition(v))
+    }
+
+    /// Removes a key-value pair if the given condition is met.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    /// let future_remove_if = treeindex.remove_if_async(&1, |v| **v == 0);
+    /// ```
+    #[inline]
+    pub async fn remove_if_async<Q, F: FnMut(&V) -> bool>(
+        &self,
+        key_ref: &Q,
+        mut condition: F,
+    ) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree
+            .remove_if_async(Query::new(key_ref), |v| condition(v))
+            .await
+    }
+
+    /// Removes all the key-value pairs in the given range, and returns the number of removed
+    /// entries.
+    ///
+    /// See [`TreeIndex::remove_range`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    ///
+    /// for k in 0..16 {
+    ///     assert!(treeindex.insert(k, Box::new(0)).is_ok());
+    /// }
+    /// assert_eq!(treeindex.remove_range(4..12), 8);
+    /// assert_eq!(treeindex.len(), 8);
+    /// ```
+    #[inline]
+    pub fn remove_range<Q, R>(&self, range: R) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        self.tree.remove_range(QueryRange(range))
+    }
+
+    /// Removes all the key-value pairs in the given range, and returns the number of removed
+    /// entries.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    /// let future_remove_range = treeindex.remove_range_async(4..12);
+    /// ```
+    #[inline]
+    pub async fn remove_range_async<Q, R>(&self, range: R) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        self.tree.remove_range_async(QueryRange(range)).await
+    }
+
+    /// Removes the minimum key-value pair, and returns it.
+    ///
+    /// The key and the value are returned in an [`Arc`] since other threads may still be reading
+    /// them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(2, Box::new(20)).is_ok());
+    /// assert!(treeindex.insert(1, Box::new(10)).is_ok());
+    /// let (key, value) = treeindex.pop_first().unwrap();
+    /// assert_eq!((*key, **value), (1, 10));
+    /// ```
+    #[inline]
+    pub fn pop_first(&self) -> Option<(Arc<K>, Arc<V>)> {
+        self.tree.pop_first().map(|(k, v)| (k.0, v))
+    }
+
+    /// Removes the minimum key-value pair, and returns it.
+    ///
+    /// The key and the value are returned in an [`Arc`] since other threads may still be reading
+    /// them. It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    /// let future_pop_first = treeindex.pop_first_async();
+    /// ```
+    #[inline]
+    pub async fn pop_first_async(&self) -> Option<(Arc<K>, Arc<V>)> {
+        self.tree.pop_first_async().await.map(|(k, v)| (k.0, v))
+    }
+
+    /// Removes the maximum key-value pair, and returns it.
+    ///
+    /// The key and the value are returned in an [`Arc`] since other threads may still be reading
+    /// them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(2, Box::new(20)).is_ok());
+    /// assert!(treeindex.insert(1, Box::new(10)).is_ok());
+    /// let (key, value) = treeindex.pop_last().unwrap();
+    /// assert_eq!((*key, **value), (2, 20));
+    /// ```
+    #[inline]
+    pub fn pop_last(&self) -> Option<(Arc<K>, Arc<V>)> {
+        self.tree.pop_last().map(|(k, v)| (k.0, v))
+    }
+
+    /// Removes the maximum key-value pair, and returns it.
+    ///
+    /// The key and the value are returned in an [`Arc`] since other threads may still be reading
+    /// them. It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<u32>> = SharedTreeIndex::new();
+    /// let future_pop_last = treeindex.pop_last_async();
+    /// ```
+    #[inline]
+    pub async fn pop_last_async(&self) -> Option<(Arc<K>, Arc<V>)> {
+        self.tree.pop_last_async().await.map(|(k, v)| (k.0, v))
+    }
+
+    /// Reads a key-value pair.
+    ///
+    /// It returns `None` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, Box<dyn Fn() -> u32 + Send + Sync>> =
+    ///     SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, Box::new(|| 10)).is_ok());
+    /// assert_eq!(treeindex.read(&1, |_, f| f()).unwrap(), 10);
+    /// ```
+    #[inline]
+    pub fn read<Q, R, F: FnOnce(&Q, &V) -> R>(&self, key_ref: &Q, reader: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree.read(Query::new(key_ref), |k, v| reader(&k.0, v))
+    }
+
+    /// Reads a key-value pair using the supplied [`Barrier`].
+    ///
+    /// It enables the caller to use the value reference outside the method. It returns `None` if
+    /// the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(1, String::from("one")).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// let value_ref = treeindex.read_with(&1, |_, v| v, &barrier).unwrap();
+    /// assert_eq!(value_ref, "one");
+    /// ```
+    #[inline]
+    pub fn read_with<'b, Q, R, F: FnOnce(&Q, &'b V) -> R>(
+        &self,
+        key_ref: &Q,
+        reader: F,
+        barrier: &'b Barrier,
+    ) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree.read_with(
+            Query::new(key_ref),
+            |k, v: &'b Arc<V>| reader(&k.0, v),
+            barrier,
+        )
+    }
+
+    /// Returns the minimum key-value pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(3, String::from("three")).is_ok());
+    /// assert!(treeindex.insert(1, String::from("one")).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert_eq!(treeindex.first(&barrier).unwrap().1, "one");
+    /// ```
+    #[inline]
+    pub fn first<'b>(&self, barrier: &'b Barrier) -> Option<(&'b K, &'b V)> {
+        self.tree.first(barrier).map(deref_entry)
+    }
+
+    /// Returns the maximum key-value pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(3, String::from("three")).is_ok());
+    /// assert!(treeindex.insert(1, String::from("one")).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert_eq!(treeindex.last(&barrier).unwrap().1, "three");
+    /// ```
+    #[inline]
+    pub fn last<'b>(&self, barrier: &'b Barrier) -> Option<(&'b K, &'b V)> {
+        self.tree.last(barrier).map(deref_entry)
+    }
+
+    /// Returns the key-value pair having the greatest key that is equal to or smaller than the
+    /// given key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(3, String::from("three")).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert_eq!(treeindex.floor(&4, &barrier).unwrap().1, "three");
+    /// assert!(treeindex.floor(&2, &barrier).is_none());
+    /// ```
+    #[inline]
+    pub fn floor<'b, Q>(&self, key_ref: &Q, barrier: &'b Barrier) -> Option<(&'b K, &'b V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree
+            .floor(Query::new(key_ref), barrier)
+            .map(deref_entry)
+    }
+
+    /// Returns the key-value pair having the smallest key that is equal to or greater than the
+    /// given key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(3, String::from("three")).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert_eq!(treeindex.ceiling(&2, &barrier).unwrap().1, "three");
+    /// assert!(treeindex.ceiling(&4, &barrier).is_none());
+    /// ```
+    #[inline]
+    pub fn ceiling<'b, Q>(&self, key_ref: &Q, barrier: &'b Barrier) -> Option<(&'b K, &'b V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree
+            .ceiling(Query::new(key_ref), barrier)
+            .map(deref_entry)
+    }
+
+    /// Returns the key-value pair with the minimum key above the given bound.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    /// use scc::ebr::Barrier;
+    /// use std::ops::Bound::Excluded;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(3, String::from("three")).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert!(treeindex.lower_bound(Excluded(&3), &barrier).is_none());
+    /// ```
+    #[inline]
+    pub fn lower_bound<'b, Q>(
+        &self,
+        bound: Bound<&Q>,
+        barrier: &'b Barrier,
+    ) -> Option<(&'b K, &'b V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree
+            .lower_bound(query_bound(bound), barrier)
+            .map(deref_entry)
+    }
+
+    /// Returns the key-value pair with the maximum key below the given bound.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    /// use scc::ebr::Barrier;
+    /// use std::ops::Bound::Excluded;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    ///
+    /// assert!(treeindex.insert(3, String::from("three")).is_ok());
+    ///
+    /// let barrier = Barrier::new();
+    /// assert!(treeindex.upper_bound(Excluded(&3), &barrier).is_none());
+    /// ```
+    #[inline]
+    pub fn upper_bound<'b, Q>(
+        &self,
+        bound: Bound<&Q>,
+        barrier: &'b Barrier,
+    ) -> Option<(&'b K, &'b V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree
+            .upper_bound(query_bound(bound), barrier)
+            .map(deref_entry)
+    }
+
+    /// Clears the [`SharedTreeIndex`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    ///
+    /// treeindex.clear();
+    /// assert_eq!(treeindex.len(), 0);
+    /// ```
+    #[inline]
+    pub fn clear(&self) {
+        self.tree.clear();
+    }
+
+    /// Splits the [`SharedTreeIndex`] into two at the given key.
+    ///
+    /// See [`TreeIndex::split_off`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let mut treeindex: SharedTreeIndex<u64, String> =
+    ///     (0..64).map(|k| (k, k.to_string())).collect();
+    ///
+    /// let high_keys = treeindex.split_off(&40);
+    /// assert_eq!(treeindex.len(), 40);
+    /// assert_eq!(high_keys.len(), 24);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn split_off<Q>(&mut self, key_ref: &Q) -> SharedTreeIndex<K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        SharedTreeIndex {
+            tree: self.tree.split_off(Query::new(key_ref)),
+        }
+    }
+
+    /// Moves all the key-value pairs in `other` into the [`SharedTreeIndex`], leaving `other`
+    /// empty.
+    ///
+    /// See [`TreeIndex::append`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let mut treeindex: SharedTreeIndex<u64, String> =
+    ///     (0..32).map(|k| (k, k.to_string())).collect();
+    /// let mut other: SharedTreeIndex<u64, String> =
+    ///     (32..64).map(|k| (k, k.to_string())).collect();
+    ///
+    /// treeindex.append(&mut other);
+    /// assert_eq!(treeindex.len(), 64);
+    /// assert!(other.is_empty());
+    /// ```
+    #[inline]
+    pub fn append(&mut self, other: &mut SharedTreeIndex<K, V>) {
+        self.tree.append(&mut other.tree);
+    }
+
+    /// Returns the size of the [`SharedTreeIndex`].
+    ///
+    /// It internally scans all the leaf nodes, and therefore the time complexity is O(N).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    /// assert_eq!(treeindex.len(), 0);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns `true` if the [`SharedTreeIndex`] is empty.
+    ///
+    /// It internally scans all the leaf nodes, and therefore the time complexity is O(N).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    /// assert!(treeindex.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns the depth of the [`SharedTreeIndex`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::new();
+    /// assert_eq!(treeindex.depth(), 0);
+    /// ```
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.tree.depth()
+    }
+
+    /// Returns a [`SharedVisitor`].
+    ///
+    /// The returned [`SharedVisitor`] starts scanning from the minimum key-value pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> =
+    ///     (0..4).map(|k| (k, k.to_string())).collect();
+    ///
+    /// let barrier = Barrier::new();
+    /// let values: Vec<&str> = treeindex.iter(&barrier).map(|(_, v)| v.as_str()).collect();
+    /// assert_eq!(values, vec!["0", "1", "2", "3"]);
+    /// ```
+    #[inline]
+    pub fn iter<'t, 'b>(&'t self, barrier: &'b Barrier) -> SharedVisitor<'t, 'b, K, V> {
+        SharedVisitor {
+            visitor: self.tree.iter(barrier),
+        }
+    }
+
+    /// Returns a [`SharedVisitor`] that scans key-value pairs in descending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> =
+    ///     (0..4).map(|k| (k, k.to_string())).collect();
+    ///
+    /// let barrier = Barrier::new();
+    /// let keys: Vec<u64> = treeindex.iter_rev(&barrier).map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, vec![3, 2, 1, 0]);
+    /// ```
+    #[inline]
+    pub fn iter_rev<'t, 'b>(&'t self, barrier: &'b Barrier) -> Rev<SharedVisitor<'t, 'b, K, V>> {
+        self.iter(barrier).rev()
+    }
+
+    /// Returns a [`SharedRange`] that scans keys in the given range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> =
+    ///     (0..16).map(|k| (k, k.to_string())).collect();
+    ///
+    /// let barrier = Barrier::new();
+    /// assert_eq!(treeindex.range(4..8, &barrier).count(), 4);
+    /// assert_eq!(treeindex.range(4..8, &barrier).next_back().unwrap().1, "7");
+    /// ```
+    #[inline]
+    pub fn range<'t, 'b, Q, R>(
+        &'t self,
+        range: R,
+        barrier: &'b Barrier,
+    ) -> SharedRange<'t, 'b, K, V, R, Q>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: 'static + RangeBounds<Q>,
+    {
+        SharedRange {
+            range: self.tree.range(QueryRange(range), barrier),
+        }
+    }
+}
+
+impl<K, V> Default for SharedTreeIndex<K, V>
+where
+    K: 'static + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Creates a [`SharedTreeIndex`] with the default parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> = SharedTreeIndex::default();
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        SharedTreeIndex::new()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for SharedTreeIndex<K, V>
+where
+    K: 'static + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Creates a [`SharedTreeIndex`] from key-value pairs.
+    ///
+    /// The key-value pairs are sorted before the [`SharedTreeIndex`] is built bottom-up, and the
+    /// first one is kept among those having the same key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::SharedTreeIndex;
+    ///
+    /// let treeindex: SharedTreeIndex<u64, String> =
+    ///     [(3, String::from("three")), (1, String::from("one"))].into_iter().collect();
+    ///
+    /// assert_eq!(treeindex.len(), 2);
+    /// ```
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        SharedTreeIndex {
+            tree: iter.into_iter().map(share_entry).collect(),
+        }
+    }
+}
+
+/// [`SharedVisitor`] scans all the key-value pairs in the [`SharedTreeIndex`].
+///
+/// It has the same guarantees as [`Visitor`].
+pub struct SharedVisitor<'t, 'b, K, V>
+where
+    K: 'static + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    visitor: Visitor<'t, 'b, Key<K>, Arc<V>>,
+}
+
+impl<'b, K, V> Iterator for SharedVisitor<'_, 'b, K, V>
+where
+    K: 'static + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    type Item = (&'b K, &'b V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.visitor.next().map(deref_entry)
+    }
+}
+
+impl<K, V> DoubleEndedIterator for SharedVisitor<'_, '_, K, V>
+where
+    K: 'static + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.visitor.next_back().map(deref_entry)
+    }
+}
+
+impl<K, V> FusedIterator for SharedVisitor<'_, '_, K, V>
+where
+    K: 'static + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+}
+
+/// [`SharedRange`] represents a range of keys in the [`SharedTreeIndex`].
+///
+/// It has the same guarantees as [`Range`].
+pub struct SharedRange<'t, 'b, K, V, R, Q: ?Sized = K>
+where
+    K: 'static + Borrow<Q> + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+    Q: Ord,
+    R: 'static + RangeBounds<Q>,
+{
+    range: Range<'t, 'b, Key<K>, Arc<V>, QueryRange<R>, Query<Q>>,
+}
+
+impl<'b, K, V, R, Q> Iterator for SharedRange<'_, 'b, K, V, R, Q>
+where
+    K: 'static + Borrow<Q> + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+    Q: Ord + ?Sized,
+    R: 'static + RangeBounds<Q>,
+{
+    type Item = (&'b K, &'b V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(deref_entry)
+    }
+}
+
+impl<K, V, R, Q> DoubleEndedIterator for SharedRange<'_, '_, K, V, R, Q>
+where
+    K: 'static + Borrow<Q> + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+    Q: Ord + ?Sized,
+    R: 'static + RangeBounds<Q>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(deref_entry)
+    }
+}
+
+impl<K, V, R, Q> FusedIterator for SharedRange<'_, '_, K, V, R, Q>
+where
+    K: 'static + Borrow<Q> + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+    Q: Ord + ?Sized,
+    R: 'static + RangeBounds<Q>,
+{
+}
+
+/// [`Key`] shares a key among the leaves and nodes of the tree.
+///
+/// It is ordered by the key, and can be borrowed as a [`Query`] of anything the key can be
+/// borrowed as.
+struct Key<K: 'static>(Arc<K>);
+
+impl<K: 'static> Clone for Key<K> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Key(self.0.clone())
+    }
+}
+
+impl<K: 'static + Ord> Eq for Key<K> {}
+
+impl<K: 'static + Ord> Ord for Key<K> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (*self.0).cmp(&*other.0)
+    }
+}
+
+impl<K: 'static + Ord> PartialEq for Key<K> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        *self.0 == *other.0
+    }
+}
+
+impl<K: 'static + Ord> PartialOrd for Key<K> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: 'static + Borrow<Q>, Q: ?Sized> Borrow<Query<Q>> for Key<K> {
+    #[inline]
+    fn borrow(&self) -> &Query<Q> {
+        Query::new((*self.0).borrow())
+    }
+}
+
+/// [`Query`] is a reference to a key or to anything a key can be borrowed as.
+///
+/// It is a distinct type so that [`Key`] can be borrowed as it without conflicting with the
+/// blanket implementation of [`Borrow`].
+#[repr(transparent)]
+struct Query<Q: ?Sized>(Q);
+
+impl<Q: ?Sized> Query<Q> {
+    #[inline]
+    fn new(key_ref: &Q) -> &Query<Q> {
+        // `Query` is a transparent wrapper of `Q`.
+        unsafe { &*(addr_of!(*key_ref) as *const Query<Q>) }
+    }
+}
+
+impl<Q: Ord + ?Sized> Eq for Query<Q> {}
+
+impl<Q: Ord + ?Sized> Ord for Query<Q> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<Q: Ord + ?Sized> PartialEq for Query<Q> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<Q: Ord + ?Sized> PartialOrd for Query<Q> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// [`QueryRange`] turns a range of keys into a range of [`Query`] instances.
+struct QueryRange<R>(R);
+
+impl<Q: ?Sized, R: RangeBounds<Q>> RangeBounds<Query<Q>> for QueryRange<R> {
+    #[inline]
+    fn start_bound(&self) -> Bound<&Query<Q>> {
+        query_bound(self.0.start_bound())
+    }
+
+    #[inline]
+    fn end_bound(&self) -> Bound<&Query<Q>> {
+        query_bound(self.0.end_bound())
+    }
+}
+
+/// Converts a bound of a key into a bound of a [`Query`].
+fn query_bound<Q: ?Sized>(bound: Bound<&Q>) -> Bound<&Query<Q>> {
+    match bound {
+        Included(key_ref) => Included(Query::new(key_ref)),
+        Excluded(key_ref) => Excluded(Query::new(key_ref)),
+        Unbounded => Unbounded,
+    }
+}
+
+/// Moves a key-value pair into [`Arc`] instances.
+fn share_entry<K: 'static, V: 'static>((key, value): (K, V)) -> (Key<K>, Arc<V>) {
+    (Key(Arc::new(key)), Arc::new(value))
+}
+
+/// Takes a key-value pair out of [`Arc`] instances that have never been inserted.
+fn unshare_entry<K: 'static, V: 'static>((key, value): (Key<K>, Arc<V>)) -> (K, V) {
+    // The `Arc` instances are returned without having been exposed to other threads.
+    let key = unsafe { key.0.try_unwrap() }.unwrap_or_else(|_| unreachable!());
+    let value = unsafe { value.try_unwrap() }.unwrap_or_else(|_| unreachable!());
+    (key, value)
+}
+
+/// Dereferences the [`Arc`] instances of a key-value pair.
+fn deref_entry<'b, K: 'static, V: 'static>(
+    (key, value): (&'b Key<K>, &'b Arc<V>),
+) -> (&'b K, &'b V) {
+    (&key.0, value)
+}
@@ -0,0 +1,1543 @@
+//! [`TreeMap`] is a concurrent and asynchronous B+ tree optimized for write.
+
+use super::ebr::{Arc, Barrier};
+use super::lock::Lock;
+use super::tree_index::TreeIndex;
+use super::wait_queue::AsyncWait;
+
+use std::borrow::Borrow;
+use std::cell::UnsafeCell;
+use std::fmt::{self, Debug};
+use std::mem::{forget, replace, ManuallyDrop};
+use std::ops::RangeBounds;
+use std::pin::Pin;
+use std::ptr::{self, addr_of};
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::Relaxed;
+
+/// Scalable concurrent B+ tree optimized for write.
+///
+/// [`TreeMap`] is a companion of [`TreeIndex`] for write-heavy workloads. [`TreeIndex`] never
+/// modifies a key-value pair in place, therefore updating a value entails copying the value, and
+/// the values are required to implement [`Clone`]. [`TreeMap`] instead places each value in a
+/// separately allocated slot that is protected by a customized mutex, allowing values to be
+/// modified in place.
+///
+/// ## The key features of [`TreeMap`]
+///
+/// * In-place mutation of values: [`TreeMap::update`], [`TreeMap::upsert`], and [`Entry`].
+/// * Values are not required to implement [`Clone`].
+/// * Asynchronous methods for blocking operations.
+///
+/// ## The key statistics for [`TreeMap`]
+///
+/// * Each key-value pair requires an additional heap allocation for the slot.
+/// * A slot is locked for the duration of a read or write operation on the value, and the
+///   [`Entry`] API keeps the slot locked until the [`Entry`] is dropped.
+pub struct TreeMap<K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    tree: TreeIndex<K, Arc<Slot<V>>>,
+}
+
+/// [`Entry`] represents a single entry in a [`TreeMap`].
+pub enum Entry<'t, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'t, K, V>),
+
+    /// A vacant entry.
+    Vacant(VacantEntry<'t, K, V>),
+}
+
+/// [`OccupiedEntry`] is a view into an occupied entry in a [`TreeMap`].
+pub struct OccupiedEntry<'t, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    locker: EntryLocker<'t, K, V>,
+}
+
+/// [`VacantEntry`] is a view into a vacant entry in a [`TreeMap`].
+pub struct VacantEntry<'t, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    locker: EntryLocker<'t, K, V>,
+}
+
+/// [`Slot`] stores a value and protects it with a customized mutex.
+///
+/// A [`Slot`] that becomes vacant is killed when the exclusive lock is released, and the
+/// lock holder removes it from the tree.
+struct Slot<V> {
+    /// The lock of the [`Slot`].
+    lock: Lock,
+
+    /// `true` if the [`Slot`] contains a value.
+    occupied: AtomicBool,
+
+    /// The value stored in the [`Slot`].
+    value: UnsafeCell<Option<V>>,
+}
+
+/// [`Locker`] holds a shared or exclusive lock on a [`Slot`] in the tree.
+///
+/// The [`Slot`] is removed from the tree when the lock is released if it has become vacant.
+struct Locker<'b, K, V, const EXCLUSIVE: bool>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    treemap: &'b TreeMap<K, V>,
+    key: &'b K,
+    slot: &'b Arc<Slot<V>>,
+}
+
+/// [`EntryLocker`] holds an exclusive lock on the [`Slot`] of an [`Entry`].
+///
+/// The [`Slot`] is removed from the tree when the lock is released if it has become vacant.
+struct EntryLocker<'t, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    treemap: &'t TreeMap<K, V>,
+    key: K,
+    slot: Arc<Slot<V>>,
+}
+
+impl<K, V> TreeMap<K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Creates an empty [`TreeMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, String> = TreeMap::new();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new() -> TreeMap<K, V> {
+        TreeMap {
+            tree: TreeIndex::new(),
+        }
+    }
+
+    /// Gets the entry associated with the given key in the map for in-place manipulation.
+    ///
+    /// The slot of the entry stays locked until the [`Entry`] is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<char, u32> = TreeMap::new();
+    ///
+    /// for ch in "a short treatise on fungi".chars() {
+    ///     treemap.entry(ch).and_modify(|counter| *counter += 1).or_insert(1);
+    /// }
+    ///
+    /// assert_eq!(treemap.read(&'s', |_, v| *v), Some(2));
+    /// assert_eq!(treemap.read(&'t', |_, v| *v), Some(3));
+    /// assert!(treemap.read(&'y', |_, v| *v).is_none());
+    /// ```
+    #[inline]
+    pub fn entry(&self, key: K) -> Entry<'_, K, V> {
+        loop {
+            {
+                let barrier = Barrier::new();
+                let result = self.lock_slot::<_, true>(&key, &barrier);
+                if let Some(locker) = result {
+                    return Entry::Occupied(OccupiedEntry {
+                        locker: locker.into_entry_locker(self, key),
+                    });
+                }
+            }
+            if let Some(entry) = self.try_insert_vacant(&key) {
+                return entry;
+            }
+        }
+    }
+
+    /// Gets the entry associated with the given key in the map for in-place manipulation.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<char, u32> = TreeMap::new();
+    ///
+    /// let future_entry = treemap.entry_async('b');
+    /// ```
+    #[inline]
+    pub async fn entry_async(&self, key: K) -> Entry<'_, K, V> {
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            {
+                let barrier = Barrier::new();
+                let result =
+                    self.try_lock_slot::<_, true>(&key, async_wait_pinned.mut_ptr(), &barrier);
+                if let Ok(result) = result {
+                    if let Some(locker) = result {
+                        return Entry::Occupied(OccupiedEntry {
+                            locker: locker.into_entry_locker(self, key),
+                        });
+                    }
+                    if let Some(entry) = self.try_insert_vacant(&key) {
+                        return entry;
+                    }
+                    continue;
+                }
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Inserts a key-value pair.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied key-value pair if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.insert(1, 10).is_ok());
+    /// assert_eq!(treemap.insert(1, 11).err().unwrap(), (1, 11));
+    /// assert_eq!(treemap.read(&1, |_, v| *v).unwrap(), 10);
+    /// ```
+    #[inline]
+    pub fn insert(&self, key: K, val: V) -> Result<(), (K, V)> {
+        let mut key = key;
+        let mut slot = Arc::new(Slot::new(val));
+        loop {
+            match self.tree.insert(key, slot) {
+                Ok(()) => return Ok(()),
+                Err((k, s)) => {
+                    key = k;
+                    slot = s;
+                }
+            }
+            let barrier = Barrier::new();
+            if self.lock_slot::<_, false>(&key, &barrier).is_some() {
+                return Err((key, Slot::into_value(slot)));
+            }
+        }
+    }
+
+    /// Inserts a key-value pair.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied key-value pair if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    /// let future_insert = treemap.insert_async(11, 17);
+    /// ```
+    #[inline]
+    pub async fn insert_async(&self, key: K, val: V) -> Result<(), (K, V)> {
+        let mut key = key;
+        let mut slot = Arc::new(Slot::new(val));
+        loop {
+            match self.tree.insert(key, slot) {
+                Ok(()) => return Ok(()),
+                Err((k, s)) => {
+                    key = k;
+                    slot = s;
+                }
+            }
+            loop {
+                let mut async_wait = AsyncWait::default();
+                let mut async_wait_pinned = Pin::new(&mut async_wait);
+                {
+                    let barrier = Barrier::new();
+                    let result =
+                        self.try_lock_slot::<_, false>(&key, async_wait_pinned.mut_ptr(), &barrier);
+                    if let Ok(result) = result {
+                        if result.is_some() {
+                            return Err((key, Slot::into_value(slot)));
+                        }
+                        break;
+                    }
+                }
+                async_wait_pinned.await;
+            }
+        }
+    }
+
+    /// Constructs the value in-place, or modifies an existing value corresponding to the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// treemap.upsert(1, || 2, |_, v| *v = 2);
+    /// assert_eq!(treemap.read(&1, |_, v| *v).unwrap(), 2);
+    /// treemap.upsert(1, || 2, |_, v| *v = 3);
+    /// assert_eq!(treemap.read(&1, |_, v| *v).unwrap(), 3);
+    /// ```
+    #[inline]
+    pub fn upsert<FI: FnOnce() -> V, FU: FnOnce(&K, &mut V)>(
+        &self,
+        key: K,
+        constructor: FI,
+        updater: FU,
+    ) {
+        match self.entry(key) {
+            Entry::Occupied(mut o) => {
+                let (k, v) = o.locker.key_value_mut();
+                updater(k, v);
+            }
+            Entry::Vacant(v) => {
+                v.insert_entry(constructor());
+            }
+        }
+    }
+
+    /// Constructs the value in-place, or modifies an existing value corresponding to the key.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    /// let future_upsert = treemap.upsert_async(1, || 2, |_, v| *v = 3);
+    /// ```
+    #[inline]
+    pub async fn upsert_async<FI: FnOnce() -> V, FU: FnOnce(&K, &mut V)>(
+        &self,
+        key: K,
+        constructor: FI,
+        updater: FU,
+    ) {
+        match self.entry_async(key).await {
+            Entry::Occupied(mut o) => {
+                let (k, v) = o.locker.key_value_mut();
+                updater(k, v);
+            }
+            Entry::Vacant(v) => {
+                v.insert_entry(constructor());
+            }
+        }
+    }
+
+    /// Updates an existing key-value pair in-place.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.update(&1, |_, _| true).is_none());
+    /// assert!(treemap.insert(1, 0).is_ok());
+    /// assert_eq!(treemap.update(&1, |_, v| { *v = 2; *v }).unwrap(), 2);
+    /// assert_eq!(treemap.read(&1, |_, v| *v).unwrap(), 2);
+    /// ```
+    #[inline]
+    pub fn update<Q, F, R>(&self, key_ref: &Q, updater: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        F: FnOnce(&K, &mut V) -> R,
+    {
+        let barrier = Barrier::new();
+        self.lock_slot::<_, true>(key_ref, &barrier)
+            .map(|locker| updater(locker.key, locker.value_mut()))
+    }
+
+    /// Updates an existing key-value pair in-place.
+    ///
+    /// Returns `None` if the key does not exist. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.insert(1, 0).is_ok());
+    /// let future_update = treemap.update_async(&1, |_, v| { *v = 2; *v });
+    /// ```
+    #[inline]
+    pub async fn update_async<Q, F, R>(&self, key_ref: &Q, updater: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        F: FnOnce(&K, &mut V) -> R,
+    {
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            {
+                let barrier = Barrier::new();
+                let result =
+                    self.try_lock_slot::<_, true>(key_ref, async_wait_pinned.mut_ptr(), &barrier);
+                if let Ok(result) = result {
+                    return result.map(|locker| updater(locker.key, locker.value_mut()));
+                }
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Removes a key-value pair if the key exists.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.remove(&1).is_none());
+    /// assert!(treemap.insert(1, 0).is_ok());
+    /// assert_eq!(treemap.remove(&1).unwrap(), (1, 0));
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&self, key_ref: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_if(key_ref, |_| true)
+    }
+
+    /// Removes a key-value pair if the key exists.
+    ///
+    /// Returns `None` if the key does not exist. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    /// let future_insert = treemap.insert_async(11, 17);
+    /// let future_remove = treemap.remove_async(&11);
+    /// ```
+    #[inline]
+    pub async fn remove_async<Q>(&self, key_ref: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_if_async(key_ref, |_| true).await
+    }
+
+    /// Removes a key-value pair if the key exists and the given condition is met.
+    ///
+    /// Returns `None` if the key does not exist or the condition was not met.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.insert(1, 0).is_ok());
+    /// assert!(treemap.remove_if(&1, |v| *v == 1).is_none());
+    /// assert_eq!(treemap.remove_if(&1, |v| *v == 0).unwrap(), (1, 0));
+    /// ```
+    #[inline]
+    pub fn remove_if<Q, F: FnOnce(&V) -> bool>(&self, key_ref: &Q, condition: F) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let barrier = Barrier::new();
+        self.lock_slot::<_, true>(key_ref, &barrier)
+            .and_then(|locker| Self::remove_locked(locker, condition))
+    }
+
+    /// Removes a key-value pair if the key exists and the given condition is met.
+    ///
+    /// Returns `None` if the key does not exist or the condition was not met. It is an
+    /// asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    /// let future_insert = treemap.insert_async(11, 17);
+    /// let future_remove = treemap.remove_if_async(&11, |_| true);
+    /// ```
+    #[inline]
+    pub async fn remove_if_async<Q, F: FnOnce(&V) -> bool>(
+        &self,
+        key_ref: &Q,
+        condition: F,
+    ) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            {
+                let barrier = Barrier::new();
+                let result =
+                    self.try_lock_slot::<_, true>(key_ref, async_wait_pinned.mut_ptr(), &barrier);
+                if let Ok(result) = result {
+                    return result.and_then(|locker| Self::remove_locked(locker, condition));
+                }
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Reads a key-value pair.
+    ///
+    /// Returns `None` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.read(&1, |_, v| *v).is_none());
+    /// assert!(treemap.insert(1, 10).is_ok());
+    /// assert_eq!(treemap.read(&1, |_, v| *v).unwrap(), 10);
+    /// ```
+    #[inline]
+    pub fn read<Q, R, F: FnOnce(&K, &V) -> R>(&self, key_ref: &Q, reader: F) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let barrier = Barrier::new();
+        self.lock_slot::<_, false>(key_ref, &barrier)
+            .map(|locker| reader(locker.key, locker.value()))
+    }
+
+    /// Reads a key-value pair.
+    ///
+    /// Returns `None` if the key does not exist. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    /// let future_insert = treemap.insert_async(11, 17);
+    /// let future_read = treemap.read_async(&11, |_, v| *v);
+    /// ```
+    #[inline]
+    pub async fn read_async<Q, R, F: FnOnce(&K, &V) -> R>(
+        &self,
+        key_ref: &Q,
+        reader: F,
+    ) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        loop {
+            let mut async_wait = AsyncWait::default();
+            let mut async_wait_pinned = Pin::new(&mut async_wait);
+            {
+                let barrier = Barrier::new();
+                let result =
+                    self.try_lock_slot::<_, false>(key_ref, async_wait_pinned.mut_ptr(), &barrier);
+                if let Ok(result) = result {
+                    return result.map(|locker| reader(locker.key, locker.value()));
+                }
+            }
+            async_wait_pinned.await;
+        }
+    }
+
+    /// Checks if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(!treemap.contains(&1));
+    /// assert!(treemap.insert(1, 0).is_ok());
+    /// assert!(treemap.contains(&1));
+    /// ```
+    #[inline]
+    pub fn contains<Q>(&self, key_ref: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.read(key_ref, |_, _| ()).is_some()
+    }
+
+    /// Scans all the key-value pairs in ascending key order.
+    ///
+    /// Each value is read-locked while the supplied closure is invoked on it. Key-value pairs that
+    /// are inserted or removed during the scan may or may not be observed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.insert(2, 1).is_ok());
+    /// assert!(treemap.insert(1, 0).is_ok());
+    ///
+    /// let mut keys = Vec::new();
+    /// treemap.scan(|k, _| keys.push(*k));
+    /// assert_eq!(keys, vec![1, 2]);
+    /// ```
+    #[inline]
+    pub fn scan<F: FnMut(&K, &V)>(&self, mut scanner: F) {
+        let barrier = Barrier::new();
+        for (key, slot) in self.tree.iter(&barrier) {
+            if let Some(locker) = self.lock_or_remove::<false>(key, slot) {
+                scanner(key, locker.value());
+            }
+        }
+    }
+
+    /// Scans the key-value pairs in the specified range in ascending key order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// for k in 0..8 {
+    ///     assert!(treemap.insert(k, 0).is_ok());
+    /// }
+    ///
+    /// let mut keys = Vec::new();
+    /// treemap.scan_range(2..5, |k, _| keys.push(*k));
+    /// assert_eq!(keys, vec![2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn scan_range<R: 'static + RangeBounds<K>, F: FnMut(&K, &V)>(
+        &self,
+        range: R,
+        mut scanner: F,
+    ) {
+        let barrier = Barrier::new();
+        for (key, slot) in self.tree.range(range, &barrier) {
+            if let Some(locker) = self.lock_or_remove::<false>(key, slot) {
+                scanner(key, locker.value());
+            }
+        }
+    }
+
+    /// Iterates over all the key-value pairs in ascending key order, and modifies the values
+    /// in-place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.insert(1, 0).is_ok());
+    /// assert!(treemap.insert(2, 1).is_ok());
+    ///
+    /// treemap.for_each(|_, v| *v += 1);
+    /// assert_eq!(treemap.read(&1, |_, v| *v).unwrap(), 1);
+    /// assert_eq!(treemap.read(&2, |_, v| *v).unwrap(), 2);
+    /// ```
+    #[inline]
+    pub fn for_each<F: FnMut(&K, &mut V)>(&self, mut updater: F) {
+        let barrier = Barrier::new();
+        for (key, slot) in self.tree.iter(&barrier) {
+            if let Some(locker) = self.lock_or_remove::<true>(key, slot) {
+                updater(key, locker.value_mut());
+            }
+        }
+    }
+
+    /// Iterates over the key-value pairs in the specified range in ascending key order, and
+    /// modifies the values in-place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// for k in 0..8 {
+    ///     assert!(treemap.insert(k, 0).is_ok());
+    /// }
+    ///
+    /// treemap.for_each_range(..4, |_, v| *v += 1);
+    /// assert_eq!(treemap.read(&3, |_, v| *v).unwrap(), 1);
+    /// assert_eq!(treemap.read(&4, |_, v| *v).unwrap(), 0);
+    /// ```
+    #[inline]
+    pub fn for_each_range<R: 'static + RangeBounds<K>, F: FnMut(&K, &mut V)>(
+        &self,
+        range: R,
+        mut updater: F,
+    ) {
+        let barrier = Barrier::new();
+        for (key, slot) in self.tree.range(range, &barrier) {
+            if let Some(locker) = self.lock_or_remove::<true>(key, slot) {
+                updater(key, locker.value_mut());
+            }
+        }
+    }
+
+    /// Clears the [`TreeMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.insert(1, 0).is_ok());
+    /// treemap.clear();
+    /// assert!(!treemap.contains(&1));
+    /// ```
+    #[inline]
+    pub fn clear(&self) {
+        self.tree.clear();
+    }
+
+    /// Returns the number of key-value pairs in the [`TreeMap`].
+    ///
+    /// It scans the entire tree, therefore its time complexity is `O(N)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.insert(1, 10).is_ok());
+    /// assert_eq!(treemap.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        let barrier = Barrier::new();
+        self.tree
+            .iter(&barrier)
+            .filter(|(_, slot)| slot.is_occupied())
+            .count()
+    }
+
+    /// Returns `true` if the [`TreeMap`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert!(treemap.is_empty());
+    /// assert!(treemap.insert(1, 10).is_ok());
+    /// assert!(!treemap.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        let barrier = Barrier::new();
+        !self.tree.iter(&barrier).any(|(_, slot)| slot.is_occupied())
+    }
+
+    /// Finds the slot associated with the key, and locks it.
+    ///
+    /// Killed slots that are found are removed from the tree before retrying.
+    fn lock_slot<'b, Q, const EXCLUSIVE: bool>(
+        &'b self,
+        key_ref: &Q,
+        barrier: &'b Barrier,
+    ) -> Option<Locker<'b, K, V, EXCLUSIVE>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        while let Some((key, slot)) = self.search(key_ref, barrier) {
+            if let Some(locker) = self.lock_or_remove::<EXCLUSIVE>(key, slot) {
+                return Some(locker);
+            }
+        }
+        None
+    }
+
+    /// Finds the slot associated with the key, and tries to lock it.
+    ///
+    /// Returns an error if the slot is locked, and `async_wait` is pushed into its wait queue.
+    #[allow(clippy::type_complexity)]
+    fn try_lock_slot<'b, Q, const EXCLUSIVE: bool>(
+        &'b self,
+        key_ref: &Q,
+        async_wait: *mut AsyncWait,
+        barrier: &'b Barrier,
+    ) -> Result<Option<Locker<'b, K, V, EXCLUSIVE>>, ()>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        while let Some((key, slot)) = self.search(key_ref, barrier) {
+            if let Some(locker) = Locker::try_lock_or_wait(self, key, slot, async_wait)? {
+                return Ok(Some(locker));
+            }
+            self.remove_slot(key, slot);
+        }
+        Ok(None)
+    }
+
+    /// Searches the tree for the slot associated with the key.
+    fn search<'b, Q>(&self, key_ref: &Q, barrier: &'b Barrier) -> Option<(&'b K, &'b Arc<Slot<V>>)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree
+            .ceiling(key_ref, barrier)
+            .filter(|(key, _)| (*key).borrow() == key_ref)
+    }
+
+    /// Locks the slot, or removes it from the tree if it has been killed.
+    fn lock_or_remove<'b, const EXCLUSIVE: bool>(
+        &'b self,
+        key: &'b K,
+        slot: &'b Arc<Slot<V>>,
+    ) -> Option<Locker<'b, K, V, EXCLUSIVE>> {
+        let locker = Locker::lock(self, key, slot);
+        if locker.is_none() {
+            self.remove_slot(key, slot);
+        }
+        locker
+    }
+
+    /// Tries to insert a locked vacant slot for the key.
+    fn try_insert_vacant(&self, key: &K) -> Option<Entry<'_, K, V>> {
+        let slot = Arc::new(Slot::vacant());
+        if self.tree.insert(key.clone(), slot.clone()).is_ok() {
+            return Some(Entry::Vacant(VacantEntry {
+                locker: EntryLocker {
+                    treemap: self,
+                    key: key.clone(),
+                    slot,
+                },
+            }));
+        }
+        None
+    }
+
+    /// Takes the value out of the exclusively locked slot if the condition is met, and removes
+    /// the slot from the tree.
+    fn remove_locked<F: FnOnce(&V) -> bool>(
+        locker: Locker<K, V, true>,
+        condition: F,
+    ) -> Option<(K, V)> {
+        if !condition(locker.value()) {
+            return None;
+        }
+        let val = locker.slot.take();
+        let key = locker.key.clone();
+        drop(locker);
+        Some((key, val))
+    }
+
+    /// Removes the slot from the tree if the key is still associated with it.
+    fn remove_slot(&self, key: &K, slot: &Arc<Slot<V>>) {
+        self.tree
+            .remove_if(key, |s| ptr::eq(s.as_ptr(), slot.as_ptr()));
+    }
+}
+
+impl<K, V> Default for TreeMap<K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Creates a [`TreeMap`] with the default parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::default();
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'t, K, V> Entry<'t, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Ensures a value is in the entry by inserting the supplied instance if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// treemap.entry(3).or_insert(7);
+    /// assert_eq!(treemap.read(&3, |_, v| *v), Some(7));
+    /// ```
+    #[inline]
+    pub fn or_insert(self, val: V) -> OccupiedEntry<'t, K, V> {
+        self.or_insert_with(|| val)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the supplied closure if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// treemap.entry(19).or_insert_with(|| 5);
+    /// assert_eq!(treemap.read(&19, |_, v| *v), Some(5));
+    /// ```
+    #[inline]
+    pub fn or_insert_with<F: FnOnce() -> V>(self, constructor: F) -> OccupiedEntry<'t, K, V> {
+        self.or_insert_with_key(|_| constructor())
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the supplied closure if empty.
+    ///
+    /// The reference to the moved key is provided, therefore cloning or copying the key is
+    /// unnecessary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u64> = TreeMap::new();
+    ///
+    /// treemap.entry(11).or_insert_with_key(|k| if *k == 11 { 7 } else { 3 });
+    /// assert_eq!(treemap.read(&11, |_, v| *v), Some(7));
+    /// ```
+    #[inline]
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, constructor: F) -> OccupiedEntry<'t, K, V> {
+        match self {
+            Self::Occupied(o) => o,
+            Self::Vacant(v) => {
+                let val = constructor(v.key());
+                v.insert_entry(val)
+            }
+        }
+    }
+
+    /// Returns a reference to the key of this entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    /// assert_eq!(treemap.entry(31).key(), &31);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn key(&self) -> &K {
+        match self {
+            Self::Occupied(o) => o.key(),
+            Self::Vacant(v) => v.key(),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// treemap.entry(37).and_modify(|v| { *v += 1 }).or_insert(47);
+    /// assert_eq!(treemap.read(&37, |_, v| *v), Some(47));
+    ///
+    /// treemap.entry(37).and_modify(|v| { *v += 1 }).or_insert(3);
+    /// assert_eq!(treemap.read(&37, |_, v| *v), Some(48));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            Self::Occupied(mut o) => {
+                f(o.get_mut());
+                Self::Occupied(o)
+            }
+            Self::Vacant(_) => self,
+        }
+    }
+
+    /// Sets the value of the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    /// let entry = treemap.entry(11).insert_entry(17);
+    /// assert_eq!(entry.key(), &11);
+    /// ```
+    #[inline]
+    pub fn insert_entry(self, val: V) -> OccupiedEntry<'t, K, V> {
+        match self {
+            Self::Occupied(mut o) => {
+                o.insert(val);
+                o
+            }
+            Self::Vacant(v) => v.insert_entry(val),
+        }
+    }
+}
+
+impl<'t, K, V> Entry<'t, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Default + Send + Sync,
+{
+    /// Ensures a value is in the entry by inserting the default value if empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    /// treemap.entry(11).or_default();
+    /// assert_eq!(treemap.read(&11, |_, v| *v), Some(0));
+    /// ```
+    #[inline]
+    pub fn or_default(self) -> OccupiedEntry<'t, K, V> {
+        self.or_insert_with(Default::default)
+    }
+}
+
+impl<K, V> Debug for Entry<'_, K, V>
+where
+    K: 'static + Clone + Debug + Ord + Send + Sync,
+    V: 'static + Debug + Send + Sync,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Vacant(v) => f.debug_tuple("Entry").field(v).finish(),
+            Self::Occupied(o) => f.debug_tuple("Entry").field(o).finish(),
+        }
+    }
+}
+
+impl<K, V> OccupiedEntry<'_, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Gets a reference to the key in the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// assert_eq!(treemap.entry(29).or_default().key(), &29);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn key(&self) -> &K {
+        &self.locker.key
+    }
+
+    /// Takes ownership of the key and value from the [`TreeMap`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    /// use scc::tree_map::Entry;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// treemap.entry(11).or_insert(17);
+    ///
+    /// if let Entry::Occupied(o) = treemap.entry(11) {
+    ///     assert_eq!(o.remove_entry(), (11, 17));
+    /// };
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn remove_entry(self) -> (K, V) {
+        let val = self.locker.slot.take();
+        (self.locker.into_key(), val)
+    }
+
+    /// Gets a reference to the value in the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    /// use scc::tree_map::Entry;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// treemap.entry(19).or_insert(11);
+    ///
+    /// if let Entry::Occupied(o) = treemap.entry(19) {
+    ///     assert_eq!(o.get(), &11);
+    /// };
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn get(&self) -> &V {
+        // The presence of `locker` prevents the value from being modified outside it.
+        unsafe { self.locker.slot.value() }
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    /// use scc::tree_map::Entry;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// treemap.entry(37).or_insert(11);
+    ///
+    /// if let Entry::Occupied(mut o) = treemap.entry(37) {
+    ///     *o.get_mut() += 18;
+    ///     assert_eq!(*o.get(), 29);
+    /// }
+    ///
+    /// assert_eq!(treemap.read(&37, |_, v| *v), Some(29));
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut V {
+        self.locker.key_value_mut().1
+    }
+
+    /// Sets the value of the entry, and returns the old value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    /// use scc::tree_map::Entry;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// treemap.entry(37).or_insert(11);
+    ///
+    /// if let Entry::Occupied(mut o) = treemap.entry(37) {
+    ///     assert_eq!(o.insert(17), 11);
+    /// }
+    ///
+    /// assert_eq!(treemap.read(&37, |_, v| *v), Some(17));
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, val: V) -> V {
+        replace(self.get_mut(), val)
+    }
+
+    /// Takes the value out of the entry, and returns it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    /// use scc::tree_map::Entry;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// treemap.entry(11).or_insert(17);
+    ///
+    /// if let Entry::Occupied(o) = treemap.entry(11) {
+    ///     assert_eq!(o.remove(), 17);
+    /// };
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+impl<K, V> Debug for OccupiedEntry<'_, K, V>
+where
+    K: 'static + Clone + Debug + Ord + Send + Sync,
+    V: 'static + Debug + Send + Sync,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("key", self.key())
+            .field("value", self.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'t, K, V> VacantEntry<'t, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Gets a reference to the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    /// assert_eq!(treemap.entry(11).key(), &11);
+    /// ```
+    #[inline]
+    pub fn key(&self) -> &K {
+        &self.locker.key
+    }
+
+    /// Takes ownership of the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    /// use scc::tree_map::Entry;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// if let Entry::Vacant(v) = treemap.entry(17) {
+    ///     assert_eq!(v.into_key(), 17);
+    /// };
+    /// ```
+    #[inline]
+    pub fn into_key(self) -> K {
+        self.locker.into_key()
+    }
+
+    /// Sets the value of the entry with its key, and returns an [`OccupiedEntry`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeMap;
+    /// use scc::tree_map::Entry;
+    ///
+    /// let treemap: TreeMap<u64, u32> = TreeMap::new();
+    ///
+    /// if let Entry::Vacant(o) = treemap.entry(19) {
+    ///     o.insert_entry(29);
+    /// }
+    ///
+    /// assert_eq!(treemap.read(&19, |_, v| *v), Some(29));
+    /// ```
+    #[inline]
+    pub fn insert_entry(self, val: V) -> OccupiedEntry<'t, K, V> {
+        self.locker.slot.fill(val);
+        OccupiedEntry {
+            locker: self.locker,
+        }
+    }
+}
+
+impl<K, V> Debug for VacantEntry<'_, K, V>
+where
+    K: 'static + Clone + Debug + Ord + Send + Sync,
+    V: 'static + Debug + Send + Sync,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VacantEntry").field(self.key()).finish()
+    }
+}
+
+impl<V> Slot<V> {
+    /// Creates a new unlocked [`Slot`] containing the value.
+    fn new(val: V) -> Slot<V> {
+        Slot {
+            lock: Lock::default(),
+            occupied: AtomicBool::new(true),
+            value: UnsafeCell::new(Some(val)),
+        }
+    }
+
+    /// Creates a new vacant [`Slot`] that is exclusively locked by the caller.
+    fn vacant() -> Slot<V> {
+        Slot {
+            lock: Lock::new_locked(),
+            occupied: AtomicBool::new(false),
+            value: UnsafeCell::new(None),
+        }
+    }
+
+    /// Extracts the value from a [`Slot`] that has never been shared.
+    fn into_value(slot: Arc<Slot<V>>) -> V {
+        // The `Arc` is returned without having been exposed to other threads.
+        let slot = unsafe { slot.try_unwrap() }.unwrap_or_else(|_| unreachable!());
+        slot.value.into_inner().unwrap_or_else(|| unreachable!())
+    }
+
+    /// Returns `true` if the [`Slot`] contains a value that is not being removed.
+    fn is_occupied(&self) -> bool {
+        !self.lock.killed() && self.occupied.load(Relaxed)
+    }
+
+    /// Returns a reference to the value.
+    ///
+    /// The caller must hold a lock on the [`Slot`], and the [`Slot`] must not be vacant.
+    unsafe fn value(&self) -> &V {
+        (*self.value.get()).as_ref().unwrap()
+    }
+
+    /// Returns a mutable reference to the value.
+    ///
+    /// The caller must exclusively lock the [`Slot`], and the [`Slot`] must not be vacant.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn value_mut(&self) -> &mut V {
+        (*self.value.get()).as_mut().unwrap()
+    }
+
+    /// Puts the value into the exclusively locked vacant [`Slot`].
+    fn fill(&self, val: V) {
+        unsafe {
+            *self.value.get() = Some(val);
+        }
+        self.occupied.store(true, Relaxed);
+    }
+
+    /// Takes the value out of the exclusively locked [`Slot`], making it vacant.
+    fn take(&self) -> V {
+        self.occupied.store(false, Relaxed);
+        unsafe { (*self.value.get()).take().unwrap() }
+    }
+
+    /// Releases the exclusive lock, and kills the [`Slot`] if it is vacant.
+    ///
+    /// Returns `true` if the [`Slot`] has been killed.
+    fn unlock_exclusive(&self) -> bool {
+        if self.occupied.load(Relaxed) {
+            self.lock.unlock::<true>();
+            false
+        } else {
+            self.lock.kill_and_unlock();
+            true
+        }
+    }
+}
+
+unsafe impl<V: Send + Sync> Sync for Slot<V> {}
+
+impl<'b, K, V, const EXCLUSIVE: bool> Locker<'b, K, V, EXCLUSIVE>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Locks the [`Slot`], waiting for the lock to be released by others.
+    ///
+    /// Returns `None` if the [`Slot`] has been killed.
+    fn lock(
+        treemap: &'b TreeMap<K, V>,
+        key: &'b K,
+        slot: &'b Arc<Slot<V>>,
+    ) -> Option<Locker<'b, K, V, EXCLUSIVE>> {
+        slot.lock
+            .lock::<EXCLUSIVE>()
+            .then(|| Locker { treemap, key, slot })
+    }
+
+    /// Tries to lock the [`Slot`], and pushes `async_wait` into the wait queue on failure.
+    fn try_lock_or_wait(
+        treemap: &'b TreeMap<K, V>,
+        key: &'b K,
+        slot: &'b Arc<Slot<V>>,
+        async_wait: *mut AsyncWait,
+    ) -> Result<Option<Locker<'b, K, V, EXCLUSIVE>>, ()> {
+        slot.lock
+            .try_lock_or_wait::<EXCLUSIVE>(async_wait)
+            .map(|locked| locked.then(|| Locker { treemap, key, slot }))
+    }
+
+    /// Returns a reference to the value.
+    fn value(&self) -> &'b V {
+        unsafe { self.slot.value() }
+    }
+}
+
+impl<'b, K, V> Locker<'b, K, V, true>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Returns a mutable reference to the value.
+    fn value_mut(&self) -> &'b mut V {
+        unsafe { self.slot.value_mut() }
+    }
+
+    /// Transfers the lock to an [`EntryLocker`].
+    fn into_entry_locker(self, treemap: &TreeMap<K, V>, key: K) -> EntryLocker<'_, K, V> {
+        let slot = self.slot.clone();
+        forget(self);
+        EntryLocker { treemap, key, slot }
+    }
+}
+
+impl<K, V, const EXCLUSIVE: bool> Drop for Locker<'_, K, V, EXCLUSIVE>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    #[inline]
+    fn drop(&mut self) {
+        if !EXCLUSIVE {
+            self.slot.lock.unlock::<false>();
+        } else if self.slot.unlock_exclusive() {
+            self.treemap.remove_slot(self.key, self.slot);
+        }
+    }
+}
+
+impl<K, V> EntryLocker<'_, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    /// Returns a reference to the key and a mutable reference to the value.
+    fn key_value_mut(&mut self) -> (&K, &mut V) {
+        (&self.key, unsafe { self.slot.value_mut() })
+    }
+
+    /// Releases the lock, and removes the [`Slot`] from the tree if it is vacant.
+    fn release(&self) {
+        if self.slot.unlock_exclusive() {
+            self.treemap.remove_slot(&self.key, &self.slot);
+        }
+    }
+
+    /// Releases the lock, and returns the key.
+    fn into_key(self) -> K {
+        self.release();
+        let this = ManuallyDrop::new(self);
+        let (key, slot) = unsafe {
+            (
+                ptr::read(addr_of!(this.key)),
+                ptr::read(addr_of!(this.slot)),
+            )
+        };
+        drop(slot);
+        key
+    }
+}
+
+impl<K, V> Drop for EntryLocker<'_, K, V>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    V: 'static + Send + Sync,
+{
+    #[inline]
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vacant_slots() {
+        let treemap: TreeMap<usize, usize> = TreeMap::default();
+        for k in 0..64 {
+            assert!(treemap.insert(k, k).is_ok());
+        }
+        for k in 0..64 {
+            match k % 4 {
+                0 => assert_eq!(treemap.remove(&k), Some((k, k))),
+                1 => assert_eq!(treemap.remove_if(&k, |v| *v == k), Some((k, k))),
+                2 => {
+                    if let Entry::Occupied(o) = treemap.entry(k) {
+                        assert_eq!(o.remove(), k);
+                    } else {
+                        unreachable!();
+                    }
+                }
+                _ => assert!(treemap.remove_if(&k, |v| *v != k).is_none()),
+            }
+        }
+        for k in 64..128 {
+            assert!(matches!(treemap.entry(k), Entry::Vacant(_)));
+        }
+
+        // Slots that became vacant are removed from the tree as soon as they are unlocked.
+        assert_eq!(treemap.len(), 16);
+        assert_eq!(treemap.tree.len(), 16);
+    }
+}
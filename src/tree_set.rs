@@ -0,0 +1,649 @@
+//! [`TreeSet`] is a concurrent and asynchronous ordered set.
+
+use super::ebr::Barrier;
+use super::tree_index::{self, TreeIndex};
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::iter::{FusedIterator, Peekable};
+use std::ops::RangeBounds;
+
+/// Scalable concurrent ordered set.
+///
+/// [`TreeSet`] is a concurrent and asynchronous ordered set based on [`TreeIndex`]. Read and scan
+/// operations are lock-free, and the set-algebra iterators [`Union`], [`Intersection`], and
+/// [`Difference`] merge the keys of two sets in ascending order without locking either of them.
+pub struct TreeSet<K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    tree: TreeIndex<K, ()>,
+}
+
+impl<K> TreeSet<K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    /// Creates an empty [`TreeSet`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new() -> TreeSet<K> {
+        TreeSet {
+            tree: TreeIndex::new(),
+        }
+    }
+
+    /// Creates an empty [`TreeSet`] with the given leaf capacity and node fan-out.
+    ///
+    /// See [`TreeIndex::with_config`] for the supported ranges of the parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::with_config(4, 8);
+    /// assert!(treeset.insert(1).is_ok());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_config(leaf_capacity: usize, fan_out: usize) -> TreeSet<K> {
+        TreeSet {
+            tree: TreeIndex::with_config(leaf_capacity, fan_out),
+        }
+    }
+
+    /// Inserts a key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied key if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    ///
+    /// assert!(treeset.insert(1).is_ok());
+    /// assert_eq!(treeset.insert(1).unwrap_err(), 1);
+    /// ```
+    #[inline]
+    pub fn insert(&self, key: K) -> Result<(), K> {
+        self.tree.insert(key, ()).map_err(|(k, ())| k)
+    }
+
+    /// Inserts a key.
+    ///
+    /// It is an asynchronous method returning an `impl Future` for the caller to await.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error along with the supplied key if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    /// let future_insert = treeset.insert_async(1);
+    /// ```
+    #[inline]
+    pub async fn insert_async(&self, key: K) -> Result<(), K> {
+        self.tree.insert_async(key, ()).await.map_err(|(k, ())| k)
+    }
+
+    /// Removes a key.
+    ///
+    /// Returns `false` if the key does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    ///
+    /// assert!(!treeset.remove(&1));
+    /// assert!(treeset.insert(1).is_ok());
+    /// assert!(treeset.remove(&1));
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&self, key_ref: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree.remove(key_ref)
+    }
+
+    /// Removes a key.
+    ///
+    /// Returns `false` if the key does not exist. It is an asynchronous method returning an
+    /// `impl Future` for the caller to await.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    /// let future_remove = treeset.remove_async(&1);
+    /// ```
+    #[inline]
+    pub async fn remove_async<Q>(&self, key_ref: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree.remove_async(key_ref).await
+    }
+
+    /// Checks if the key exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    ///
+    /// assert!(!treeset.contains(&1));
+    /// assert!(treeset.insert(1).is_ok());
+    /// assert!(treeset.contains(&1));
+    /// ```
+    #[inline]
+    pub fn contains<Q>(&self, key_ref: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.tree.read(key_ref, |_, ()| ()).is_some()
+    }
+
+    /// Returns the minimum key.
+    ///
+    /// The returned reference can live as long as the supplied [`Barrier`]. It returns `None` if
+    /// the [`TreeSet`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    ///
+    /// let barrier = Barrier::new();
+    /// assert!(treeset.first(&barrier).is_none());
+    ///
+    /// assert!(treeset.insert(3).is_ok());
+    /// assert!(treeset.insert(1).is_ok());
+    /// assert_eq!(treeset.first(&barrier), Some(&1));
+    /// ```
+    #[inline]
+    pub fn first<'b>(&self, barrier: &'b Barrier) -> Option<&'b K> {
+        self.tree.first(barrier).map(|(k, ())| k)
+    }
+
+    /// Returns the maximum key.
+    ///
+    /// The returned reference can live as long as the supplied [`Barrier`]. It returns `None` if
+    /// the [`TreeSet`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    ///
+    /// let barrier = Barrier::new();
+    /// assert!(treeset.last(&barrier).is_none());
+    ///
+    /// assert!(treeset.insert(3).is_ok());
+    /// assert!(treeset.insert(1).is_ok());
+    /// assert_eq!(treeset.last(&barrier), Some(&3));
+    /// ```
+    #[inline]
+    pub fn last<'b>(&self, barrier: &'b Barrier) -> Option<&'b K> {
+        self.tree.last(barrier).map(|(k, ())| k)
+    }
+
+    /// Clears the [`TreeSet`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    ///
+    /// assert!(treeset.insert(1).is_ok());
+    /// treeset.clear();
+    /// assert!(!treeset.contains(&1));
+    /// ```
+    #[inline]
+    pub fn clear(&self) {
+        self.tree.clear();
+    }
+
+    /// Returns the number of keys in the [`TreeSet`].
+    ///
+    /// It internally scans all the leaf nodes, and therefore the time complexity is O(N).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    ///
+    /// assert!(treeset.insert(1).is_ok());
+    /// assert_eq!(treeset.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Returns `true` if the [`TreeSet`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::new();
+    ///
+    /// assert!(treeset.is_empty());
+    /// assert!(treeset.insert(1).is_ok());
+    /// assert!(!treeset.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns a [`Visitor`] that scans all the keys in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeset: TreeSet<u64> = [3, 1, 2].into_iter().collect();
+    ///
+    /// let barrier = Barrier::new();
+    /// let keys: Vec<u64> = treeset.iter(&barrier).copied().collect();
+    /// assert_eq!(keys, vec![1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn iter<'t, 'b>(&'t self, barrier: &'b Barrier) -> Visitor<'t, 'b, K> {
+        Visitor {
+            visitor: self.tree.iter(barrier),
+        }
+    }
+
+    /// Returns a [`Range`] that scans keys in the given range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let treeset: TreeSet<u64> = (0..16).collect();
+    ///
+    /// let barrier = Barrier::new();
+    /// assert_eq!(treeset.range(4..8, &barrier).count(), 4);
+    /// assert_eq!(treeset.range(4..8, &barrier).next_back(), Some(&7));
+    /// ```
+    #[inline]
+    pub fn range<'t, 'b, R: 'static + RangeBounds<K>>(
+        &'t self,
+        range: R,
+        barrier: &'b Barrier,
+    ) -> Range<'t, 'b, K, R> {
+        Range {
+            range: self.tree.range(range, barrier),
+        }
+    }
+
+    /// Returns a [`Union`] that scans the keys in either `self` or `other` in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let a: TreeSet<u64> = [1, 2, 3].into_iter().collect();
+    /// let b: TreeSet<u64> = [2, 3, 4].into_iter().collect();
+    ///
+    /// let barrier = Barrier::new();
+    /// let union: Vec<u64> = a.union(&b, &barrier).copied().collect();
+    /// assert_eq!(union, vec![1, 2, 3, 4]);
+    /// ```
+    #[inline]
+    pub fn union<'t, 'b>(
+        &'t self,
+        other: &'t TreeSet<K>,
+        barrier: &'b Barrier,
+    ) -> Union<'t, 'b, K> {
+        Union {
+            left: self.iter(barrier).peekable(),
+            right: other.iter(barrier).peekable(),
+        }
+    }
+
+    /// Returns an [`Intersection`] that scans the keys in both `self` and `other` in ascending
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let a: TreeSet<u64> = [1, 2, 3].into_iter().collect();
+    /// let b: TreeSet<u64> = [2, 3, 4].into_iter().collect();
+    ///
+    /// let barrier = Barrier::new();
+    /// let intersection: Vec<u64> = a.intersection(&b, &barrier).copied().collect();
+    /// assert_eq!(intersection, vec![2, 3]);
+    /// ```
+    #[inline]
+    pub fn intersection<'t, 'b>(
+        &'t self,
+        other: &'t TreeSet<K>,
+        barrier: &'b Barrier,
+    ) -> Intersection<'t, 'b, K> {
+        Intersection {
+            left: self.iter(barrier).peekable(),
+            right: other.iter(barrier).peekable(),
+        }
+    }
+
+    /// Returns a [`Difference`] that scans the keys in `self` but not in `other` in ascending
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    /// use scc::ebr::Barrier;
+    ///
+    /// let a: TreeSet<u64> = [1, 2, 3].into_iter().collect();
+    /// let b: TreeSet<u64> = [2, 3, 4].into_iter().collect();
+    ///
+    /// let barrier = Barrier::new();
+    /// let difference: Vec<u64> = a.difference(&b, &barrier).copied().collect();
+    /// assert_eq!(difference, vec![1]);
+    /// ```
+    #[inline]
+    pub fn difference<'t, 'b>(
+        &'t self,
+        other: &'t TreeSet<K>,
+        barrier: &'b Barrier,
+    ) -> Difference<'t, 'b, K> {
+        Difference {
+            left: self.iter(barrier).peekable(),
+            right: other.iter(barrier).peekable(),
+        }
+    }
+}
+
+impl<K> Default for TreeSet<K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    /// Creates a [`TreeSet`] with the default parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = TreeSet::default();
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        TreeSet::new()
+    }
+}
+
+impl<K> FromIterator<K> for TreeSet<K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    /// Builds a [`TreeSet`] from keys in any order; duplicate keys are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scc::TreeSet;
+    ///
+    /// let treeset: TreeSet<u64> = [3, 1, 3, 2].into_iter().collect();
+    ///
+    /// assert_eq!(treeset.len(), 3);
+    /// ```
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut keys: Vec<K> = iter.into_iter().collect();
+        keys.sort();
+        keys.dedup();
+        TreeSet {
+            tree: TreeIndex::from_sorted_iter(keys.into_iter().map(|k| (k, ()))),
+        }
+    }
+}
+
+/// [`Visitor`] scans all the keys in the [`TreeSet`].
+///
+/// It has the same guarantees as [`tree_index::Visitor`].
+pub struct Visitor<'t, 'b, K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    visitor: tree_index::Visitor<'t, 'b, K, ()>,
+}
+
+impl<'b, K> Iterator for Visitor<'_, 'b, K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    type Item = &'b K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.visitor.next().map(|(k, ())| k)
+    }
+}
+
+impl<K> DoubleEndedIterator for Visitor<'_, '_, K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.visitor.next_back().map(|(k, ())| k)
+    }
+}
+
+impl<K> FusedIterator for Visitor<'_, '_, K> where K: 'static + Clone + Ord + Send + Sync {}
+
+/// [`Range`] represents a range of keys in the [`TreeSet`].
+///
+/// It has the same guarantees as [`tree_index::Range`].
+pub struct Range<'t, 'b, K, R>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    R: 'static + RangeBounds<K>,
+{
+    range: tree_index::Range<'t, 'b, K, (), R>,
+}
+
+impl<'b, K, R> Iterator for Range<'_, 'b, K, R>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    R: 'static + RangeBounds<K>,
+{
+    type Item = &'b K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|(k, ())| k)
+    }
+}
+
+impl<K, R> DoubleEndedIterator for Range<'_, '_, K, R>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    R: 'static + RangeBounds<K>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(|(k, ())| k)
+    }
+}
+
+impl<K, R> FusedIterator for Range<'_, '_, K, R>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+    R: 'static + RangeBounds<K>,
+{
+}
+
+/// [`Union`] scans the keys in either of two [`TreeSet`] instances in ascending order.
+///
+/// Keys in both sets are returned once. Each set is scanned by a [`Visitor`], therefore it is
+/// guaranteed to return all the keys that outlive the [`Union`].
+pub struct Union<'t, 'b, K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    left: Peekable<Visitor<'t, 'b, K>>,
+    right: Peekable<Visitor<'t, 'b, K>>,
+}
+
+impl<'b, K> Iterator for Union<'_, 'b, K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    type Item = &'b K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let ordering = match (self.left.peek(), self.right.peek()) {
+            (Some(left), Some(right)) => left.cmp(right),
+            (Some(_), None) => Ordering::Less,
+            (None, _) => Ordering::Greater,
+        };
+        match ordering {
+            Ordering::Less => self.left.next(),
+            Ordering::Equal => {
+                self.right.next();
+                self.left.next()
+            }
+            Ordering::Greater => self.right.next(),
+        }
+    }
+}
+
+impl<K> FusedIterator for Union<'_, '_, K> where K: 'static + Clone + Ord + Send + Sync {}
+
+/// [`Intersection`] scans the keys in both of two [`TreeSet`] instances in ascending order.
+///
+/// Each set is scanned by a [`Visitor`], therefore it is guaranteed to return all the keys that
+/// are in both sets and outlive the [`Intersection`].
+pub struct Intersection<'t, 'b, K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    left: Peekable<Visitor<'t, 'b, K>>,
+    right: Peekable<Visitor<'t, 'b, K>>,
+}
+
+impl<'b, K> Iterator for Intersection<'_, 'b, K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    type Item = &'b K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let left = *self.left.peek()?;
+            let right = *self.right.peek()?;
+            match left.cmp(right) {
+                Ordering::Less => {
+                    self.left.next();
+                }
+                Ordering::Equal => {
+                    self.right.next();
+                    return self.left.next();
+                }
+                Ordering::Greater => {
+                    self.right.next();
+                }
+            }
+        }
+    }
+}
+
+impl<K> FusedIterator for Intersection<'_, '_, K> where K: 'static + Clone + Ord + Send + Sync {}
+
+/// [`Difference`] scans the keys in one [`TreeSet`] that are not in another in ascending order.
+///
+/// Each set is scanned by a [`Visitor`]; a key that is inserted into the other set during the
+/// scan may or may not be excluded.
+pub struct Difference<'t, 'b, K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    left: Peekable<Visitor<'t, 'b, K>>,
+    right: Peekable<Visitor<'t, 'b, K>>,
+}
+
+impl<'b, K> Iterator for Difference<'_, 'b, K>
+where
+    K: 'static + Clone + Ord + Send + Sync,
+{
+    type Item = &'b K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let left = *self.left.peek()?;
+            let ordering = self
+                .right
+                .peek()
+                .map_or(Ordering::Less, |right| left.cmp(right));
+            match ordering {
+                Ordering::Less => return self.left.next(),
+                Ordering::Equal => {
+                    self.left.next();
+                    self.right.next();
+                }
+                Ordering::Greater => {
+                    self.right.next();
+                }
+            }
+        }
+    }
+}
+
+impl<K> FusedIterator for Difference<'_, '_, K> where K: 'static + Clone + Ord + Send + Sync {}
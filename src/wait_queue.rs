@@ -2,8 +2,8 @@ use std::future::Future;
 use std::mem::transmute;
 use std::pin::Pin;
 use std::ptr::addr_of_mut;
-use std::sync::atomic::AtomicUsize;
-use std::sync::atomic::Ordering::{AcqRel, Relaxed};
+use std::sync::atomic::Ordering::{AcqRel, Relaxed, SeqCst};
+use std::sync::atomic::{fence, AtomicUsize};
 use std::sync::{Condvar, Mutex};
 use std::task::{Context, Poll, Waker};
 
@@ -35,6 +35,9 @@ impl WaitQueue {
             entry.next = current;
         }
 
+        // Pairs with the fence in `signal_if_waiting`.
+        fence(SeqCst);
+
         // Execute the closure.
         let result = f();
         if result.is_ok() {
@@ -60,7 +63,9 @@ impl WaitQueue {
 
         let mut current = self.wait_queue.load(Relaxed);
         async_wait_mut.next = current;
-        async_wait_mut.mutex.replace(Mutex::new((false, None)));
+        async_wait_mut
+            .mutex
+            .replace(Mutex::new((false, None, false)));
 
         while let Err(actual) = self.wait_queue.compare_exchange(
             current,
@@ -72,6 +77,9 @@ impl WaitQueue {
             async_wait_mut.next = current;
         }
 
+        // Pairs with the fence in `signal_if_waiting`.
+        fence(SeqCst);
+
         // Execute the closure.
         if let Ok(result) = f() {
             self.signal();
@@ -86,6 +94,19 @@ impl WaitQueue {
         Err(())
     }
 
+    /// Signals the threads in the wait queue if there is any.
+    ///
+    /// The caller must have made the change that the waiting threads are waiting for before the
+    /// call; either the waiting threads observe the change, or the [`WaitQueue`] is observed
+    /// non-empty.
+    #[inline]
+    pub(crate) fn signal_if_waiting(&self) {
+        fence(SeqCst);
+        if self.wait_queue.load(Relaxed) != 0 {
+            self.signal();
+        }
+    }
+
     /// Signals the threads in the wait queue.
     #[inline]
     pub(crate) fn signal(&self) {
@@ -99,9 +120,9 @@ impl WaitQueue {
                 current = next;
             } else {
                 // Asynchronous.
-                let entry_ref = unsafe { &*AsyncWait::reinterpret(current & (!ASYNC)) };
-                let next = entry_ref.next;
-                entry_ref.signal();
+                let entry_ptr = unsafe { AsyncWait::reinterpret(current & (!ASYNC)) };
+                let next = unsafe { (*entry_ptr).next };
+                unsafe { AsyncWait::signal(entry_ptr) };
                 current = next;
             }
         }
@@ -112,7 +133,10 @@ impl WaitQueue {
 #[derive(Debug, Default)]
 pub(crate) struct AsyncWait {
     next: usize,
-    mutex: Option<Mutex<(bool, Option<Waker>)>>,
+
+    /// Whether it has been signalled, the [`Waker`] to wake up, and whether it has been
+    /// deregistered.
+    mutex: Option<Mutex<(bool, Option<Waker>, bool)>>,
 }
 
 impl AsyncWait {
@@ -122,22 +146,55 @@ impl AsyncWait {
         addr_of_mut!(*self)
     }
 
+    /// Deregisters the boxed [`AsyncWait`] without waiting for a signal.
+    ///
+    /// If it has yet to be signalled, the [`WaitQueue`] that it was pushed into takes ownership
+    /// of it, and drops it when signalling it.
+    pub(crate) fn deregister(async_wait: Box<AsyncWait>) {
+        let async_wait_ptr = Box::into_raw(async_wait);
+        let signalled = if let Some(mutex) = unsafe { (*async_wait_ptr).mutex.as_ref() } {
+            if let Ok(mut locked) = mutex.lock() {
+                if !locked.0 {
+                    locked.1.take();
+                    locked.2 = true;
+                }
+                locked.0
+            } else {
+                // The state is unknown, therefore it cannot be dropped.
+                false
+            }
+        } else {
+            true
+        };
+        if signalled {
+            drop(unsafe { Box::from_raw(async_wait_ptr) });
+        }
+    }
+
     /// Sends a signal.
-    fn signal(&self) {
-        if let Some(mutex) = self.mutex.as_ref() {
+    ///
+    /// The [`AsyncWait`] is dropped if it has been deregistered.
+    unsafe fn signal(async_wait_ptr: *mut AsyncWait) {
+        let deregistered = if let Some(mutex) = (*async_wait_ptr).mutex.as_ref() {
             if let Ok(mut locked) = mutex.lock() {
                 locked.0 = true;
                 if let Some(waker) = locked.1.take() {
                     waker.wake();
                 }
+                locked.2
+            } else {
+                false
             }
         } else {
             unreachable!();
+        };
+        if deregistered {
+            drop(Box::from_raw(async_wait_ptr));
         }
     }
 
     /// Tries to receive a signal.
-    fn try_wait(&self) -> bool {
+    pub(crate) fn try_wait(&self) -> bool {
         if let Some(mutex) = self.mutex.as_ref() {
             if let Ok(locked) = mutex.lock() {
                 if locked.0 {